@@ -47,7 +47,13 @@ just one space
 & ampersands &
 last line";
 
-    let tokens = dbg!(Stendhal::tokenize_string(input).unwrap());
+    let tokens = match Stendhal::new().tokenize_from_string(input) {
+        Ok(tokens) => dbg!(tokens),
+        Err(error) => {
+            eprint!("{}", error.render(input));
+            std::process::exit(1);
+        }
+    };
     let html = Html::export_token_vector_to_string(tokens);
 
     print!("{html}");
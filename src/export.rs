@@ -17,4 +17,14 @@
 
 //! Implementations of [`Export`][`crate::Export`].
 
+pub use crate::format::ansi::Ansi;
+pub use crate::format::ansi::ColorDepth;
+pub use crate::format::epub::Epub;
+pub use crate::format::epub::ExportError as EpubExportError;
+pub use crate::format::html::ContentElement;
 pub use crate::format::html::Html;
+pub use crate::format::html::TextDirection;
+pub use crate::format::latex::Latex;
+pub use crate::format::markdown::Markdown;
+pub use crate::format::plain_text::PlainText;
+pub use crate::format::stendhal::Stendhal;
@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Exporting for terminals, using ANSI escape sequences.
+//!
+//! See [`Ansi`] for more details.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use crafty_novels::{
+//!    export::{Ansi, ColorDepth},
+//!    syntax::{
+//!        minecraft::{Color, Format},
+//!        Token, TokenList,
+//!    },
+//!    Export,
+//! };
+//!
+//! let input_tokens = Box::new([
+//!     Token::Text("Some".into()),
+//!     Token::Space,
+//!     Token::Format(Format::Color(Color::Red.into())),
+//!     Token::Text("RED".into()),
+//!     Token::Space,
+//!     Token::Format(Format::Italic),
+//!     Token::Text("text".into()),
+//!     Token::Format(Format::Reset),
+//!     Token::LineBreak,
+//! ]);
+//! let input = TokenList::new_from_boxed(Box::new([]), input_tokens);
+//!
+//! assert_eq!(
+//!     Ansi::new().export_to_string(&input).as_ref(),
+//!     "Some \x1b[38;2;255;85;85mRED \x1b[3mtext\x1b[0m\n"
+//! );
+//! assert_eq!(
+//!     Ansi::new()
+//!         .color_depth(ColorDepth::Palette16)
+//!         .export_to_string(&input)
+//!         .as_ref(),
+//!     "Some \x1b[91mRED \x1b[3mtext\x1b[0m\n"
+//! );
+//! ```
+
+use crate::{resolve::Untranslated, syntax::TokenList, writer::Utf8Writer, Export};
+use std::io::Write;
+
+#[cfg(test)]
+mod test;
+mod token_handling;
+
+/// The colors a terminal is able to display.
+///
+/// Most modern terminals support [`ColorDepth::TrueColor`], which is usually advertised by the
+/// `COLORTERM` environment variable being `truecolor` or `24bit`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ColorDepth {
+    /// 24-bit color, where every color is written exactly (`ESC[38;2;R;G;Bm`).
+    #[default]
+    TrueColor,
+    /// The 256-color palette, where every color is written as the closest color in the palette
+    /// (`ESC[38;5;Nm`).
+    Palette256,
+    /// The 16-color palette, where every color is written as its classic terminal equivalent (ex.
+    /// `ESC[91m` for bright red), leaving the exact shade up to the terminal's theme.
+    Palette16,
+}
+
+/// Exporting for terminals, using ANSI escape sequences (Select Graphic Rendition).
+///
+/// # Format
+///
+/// Opens with the [metadata][`crate::syntax::Metadata`], followed by a blank line:
+///
+/// - The title, in bold
+/// - `"by {author}"`, in italics
+///
+/// Inside of the contents:
+///
+/// - Text and spaces are written as-is, except for control characters (ex. `ESC`), which are
+///   replaced with `'\u{FFFD}'` so that the text cannot control the terminal
+/// - Line breaks and paragraph breaks are written as line endings
+/// - Thematic breaks are written as a dim horizontal line (`"────"`)
+/// - Colored text is written with the color's [foreground][`crate::syntax::minecraft::ColorValue::fg`]
+///   in the chosen [`ColorDepth`]
+/// - Bold, italic, underline, and strikethrough text are written with their own SGR codes (`1`,
+///   `3`, `4`, and `9`)
+/// - Obfuscated text is written in reverse video (`7`)
+///
+/// All formatting is reset (`ESC[0m`) at a reset format code and at the end of every line, and
+/// reapplied on the next line if it is still active.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Ansi {
+    color_depth: ColorDepth,
+}
+
+impl Ansi {
+    /// Create a new [`Ansi`] exporter with the default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            color_depth: ColorDepth::TrueColor,
+        }
+    }
+
+    /// The colors the terminal is able to display, [`ColorDepth::TrueColor`] by default.
+    #[must_use]
+    pub const fn color_depth(mut self, color_depth: ColorDepth) -> Self {
+        self.color_depth = color_depth;
+        self
+    }
+}
+
+impl Export for Ansi {
+    /// Parse a given abstract syntax vector into ANSI-formatted text using these options, then
+    /// output that into a writer, like [`std::io::Stdout`].
+    ///
+    /// Guaranteed to only write valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into `output`
+    fn export_to_writer(&self, tokens: &TokenList, output: &mut impl Write) -> std::io::Result<()> {
+        let tokens = &tokens.resolve(&Untranslated).resolve_styles();
+        let mut writer = Utf8Writer::new(output);
+
+        token_handling::start_document(&mut writer, tokens.metadata_as_slice())?;
+
+        let mut document = token_handling::Document::new(&mut writer, self.color_depth);
+        for token in tokens.tokens_as_slice() {
+            document.token(token)?;
+        }
+
+        document.finish()
+    }
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for exporting to the [ANSI][`super::Ansi`] format.
+
+use super::{Ansi, ColorDepth};
+use crate::{
+    import::Stendhal,
+    syntax::{
+        minecraft::{Color, Format, Rgb},
+        Metadata, Token, TokenList,
+    },
+    Export, Tokenize,
+};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Insert a [`Token::Format`] with the given variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Insert a [`Token::Format`] with the given color.
+macro_rules! color {
+    ($color:expr) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::Color($color.into()))
+    };
+}
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+#[test]
+fn test_stendhal_document() -> Result {
+    let input = "title: crafty_novels
+author: RemasteredArch
+pages:
+#- Some §cRED text
+Italic:§o text §rreset
+#- §l§nBold §mand§r plain";
+
+    let expected = concat!(
+        "\x1b[1mcrafty_novels\x1b[0m\n",
+        "\x1b[3mby RemasteredArch\x1b[0m\n",
+        "\n",
+        "\x1b[2m────────────────────\x1b[0m\n",
+        "Some \x1b[38;2;255;85;85mRED text\x1b[0m\n",
+        "Italic:\x1b[3m text \x1b[0mreset\n",
+        "\x1b[2m────────────────────\x1b[0m\n",
+        "\x1b[1;4mBold \x1b[9mand\x1b[0m plain\n",
+    );
+
+    let ansi = Ansi::export_token_vector_to_string(Stendhal::tokenize_string(input)?);
+    assert_eq!(ansi.as_ref(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_formatting() {
+    use Token::{LineBreak, Space};
+
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            format!(Obfuscated),
+            // Like in Minecraft, a color clears the formatting before it
+            color!(Color::Gold),
+            text!("one"),
+            LineBreak,
+            // Formatting is reapplied after the end of a line
+            text!("two"),
+            // A new color replaces the old one, which needs a reset
+            color!(Color::Aqua),
+            Space,
+            text!("three"),
+            format!(Obfuscated),
+            text!("four"),
+            // Reapplying a format that is already applied changes nothing
+            format!(Obfuscated),
+            text!("five"),
+        ]),
+    );
+
+    assert_eq!(
+        Ansi::new().export_to_string(&input).as_ref(),
+        concat!(
+            "\x1b[38;2;255;170;0mone\x1b[0m\n",
+            "\x1b[38;2;255;170;0mtwo",
+            "\x1b[0m\x1b[38;2;85;255;255m three\x1b[7mfourfive\x1b[0m",
+        )
+    );
+}
+
+#[test]
+fn test_color_depth() {
+    /// Export each of `$colors` with `$depth`, and compare their SGR parameters to `$expects`.
+    macro_rules! test {
+        ( $depth:expr; $( $color:ident => $expects:expr ),+ $(,)? ) => {
+            $(
+                let input = TokenList::new_from_boxed(
+                    Box::new([]),
+                    Box::new([color!(Color::$color), text!("text")]),
+                );
+
+                assert_eq!(
+                    Ansi::new().color_depth($depth).export_to_string(&input).as_ref(),
+                    std::format!("\x1b[{}mtext\x1b[0m", $expects),
+                    "{:?}",
+                    Color::$color,
+                );
+            )+
+        };
+    }
+
+    test!(
+        ColorDepth::TrueColor;
+        DarkBlue => "38;2;0;0;170",
+        Gray => "38;2;170;170;170",
+    );
+
+    test!(
+        ColorDepth::Palette256;
+        Black => "38;5;16",
+        DarkBlue => "38;5;19",
+        Gold => "38;5;214",
+        // Grays are closer to the grayscale ramp than the color cube
+        Gray => "38;5;248",
+        DarkGray => "38;5;240",
+        Red => "38;5;203",
+        White => "38;5;231",
+    );
+
+    test!(
+        ColorDepth::Palette16;
+        Black => 30,
+        DarkBlue => 34,
+        Gold => 33,
+        Gray => 37,
+        DarkGray => 90,
+        Red => 91,
+        White => 97,
+    );
+}
+
+#[test]
+fn test_rgb() {
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            Token::Format(Format::Color(Rgb::new(250, 160, 10).into())),
+            text!("text"),
+        ]),
+    );
+
+    for (depth, expected) in [
+        (ColorDepth::TrueColor, "38;2;250;160;10"),
+        (ColorDepth::Palette256, "38;5;214"),
+        // The nearest legacy color is gold
+        (ColorDepth::Palette16, "33"),
+    ] {
+        assert_eq!(
+            Ansi::new()
+                .color_depth(depth)
+                .export_to_string(&input)
+                .as_ref(),
+            std::format!("\x1b[{expected}mtext\x1b[0m"),
+            "{depth:?}",
+        );
+    }
+}
+
+#[test]
+fn test_control_characters() {
+    let input = TokenList::new_from_boxed(
+        Box::new([
+            Metadata::Title("crafty\x1b]0;novels\x07".into()),
+            Metadata::Author("Remastered\u{9b}2JArch".into()),
+        ]),
+        Box::new([text!("a\x1b[31mb\nc"), Token::LineBreak, text!("d")]),
+    );
+
+    // Text cannot write escape sequences or line endings of its own
+    assert_eq!(
+        Ansi::export_token_vector_to_string(input).as_ref(),
+        concat!(
+            "\x1b[1mcrafty\u{FFFD}]0;novels\u{FFFD}\x1b[0m\n",
+            "\x1b[3mby Remastered\u{FFFD}2JArch\x1b[0m\n",
+            "\n",
+            "a\u{FFFD}[31mb\u{FFFD}c\n",
+            "d",
+        )
+    );
+}
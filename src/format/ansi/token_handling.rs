@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The actual, under the hood, token-by-token exporting for the [ANSI][`super::Ansi`] format.
+
+use super::ColorDepth;
+use crate::{
+    syntax::{
+        minecraft::{Color, Format, Rgb},
+        Metadata, Token,
+    },
+    writer::Utf8Writer,
+};
+use std::{
+    borrow::Cow,
+    io::{Result, Write},
+};
+
+/// The Control Sequence Introducer that starts every escape sequence.
+const CSI: &str = "\x1b[";
+
+/// The escape sequence that resets all formatting.
+const RESET: &str = "\x1b[0m";
+
+/// The number of `'─'` characters that make up a thematic break.
+const RULE_WIDTH: usize = 20;
+
+/// The intensities of each channel in the 6x6x6 color cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Write the title (in bold) and author of a work, followed by a blank line.
+///
+/// Other [`Metadata`] has no meaning in the document itself, and is ignored.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn start_document(output: &mut Utf8Writer<impl Write>, metadata: &[Metadata]) -> Result<()> {
+    let mut header = false;
+
+    for data in metadata {
+        match data {
+            Metadata::Title(t) => writeln!(output, "{CSI}1m{}{RESET}", printable(t))?,
+            Metadata::Author(a) => writeln!(output, "{CSI}3mby {}{RESET}", printable(a))?,
+            _ => continue,
+        }
+
+        header = true;
+    }
+
+    if header {
+        output.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// Returns `text` with every control character replaced by `'\u{FFFD}'`, so that text cannot
+/// start escape sequences of its own, or otherwise change the state of the terminal.
+///
+/// This includes line endings, which are only written for [`Token::LineBreak`]s and the like.
+fn printable(text: &str) -> Cow<'_, str> {
+    if text.contains(char::is_control) {
+        Cow::Owned(
+            text.chars()
+                .map(|char| if char.is_control() { '\u{FFFD}' } else { char })
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Returns the Select Graphic Rendition (SGR) parameters that apply a [`Format`], ex. `"1"` for
+/// bold, or `"38;2;255;85;85"` for red in 24-bit color.
+fn parameters(format: Format, depth: ColorDepth) -> String {
+    match format {
+        Format::Color(color) => match depth {
+            ColorDepth::TrueColor => {
+                let (red, green, blue) = color.rgb().as_tuple();
+                format!("38;2;{red};{green};{blue}")
+            }
+            ColorDepth::Palette256 => format!("38;5;{}", palette_256(color.rgb())),
+            ColorDepth::Palette16 => palette_16(color.nearest_legacy()).to_string(),
+        },
+        Format::Bold => "1".into(),
+        Format::Italic => "3".into(),
+        Format::Underline => "4".into(),
+        // Reverse video, since there is no way to scramble text in a terminal
+        Format::Obfuscated => "7".into(),
+        Format::Strikethrough => "9".into(),
+        Format::Reset => "0".into(),
+        // Never applied, see [`Document::token`]
+        Format::Font(_) | Format::ShadowColor(_) => String::new(),
+    }
+}
+
+/// Returns the index of the color in the 256-color palette closest to `rgb`, out of the 6x6x6
+/// color cube (16-231) and the grayscale ramp (232-255).
+fn palette_256(rgb: Rgb) -> u8 {
+    let (red, green, blue) = rgb.as_tuple();
+
+    let distance = |(r, g, b): (u8, u8, u8)| {
+        [(red, r), (green, g), (blue, b)]
+            .into_iter()
+            .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+            .sum::<u32>()
+    };
+
+    let nearest_level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&index| CUBE_LEVELS[index].abs_diff(channel))
+            .unwrap_or_default()
+    };
+    let cube = [
+        nearest_level(red),
+        nearest_level(green),
+        nearest_level(blue),
+    ];
+    let cube_color = (
+        CUBE_LEVELS[cube[0]],
+        CUBE_LEVELS[cube[1]],
+        CUBE_LEVELS[cube[2]],
+    );
+
+    let gray = (0..24_u8)
+        .min_by_key(|&step| {
+            let level = 8 + step * 10;
+            distance((level, level, level))
+        })
+        .unwrap_or_default();
+    let gray_level = 8 + gray * 10;
+
+    #[allow(clippy::cast_possible_truncation)] // Both are always < 256
+    if distance((gray_level, gray_level, gray_level)) < distance(cube_color) {
+        232 + gray
+    } else {
+        (16 + 36 * cube[0] + 6 * cube[1] + cube[2]) as u8
+    }
+}
+
+/// Returns the SGR parameter of the 16-color palette that a [`Color`] corresponds to.
+///
+/// Minecraft's sixteen colors are the same sixteen colors as the classic terminal palette, so
+/// they map one-to-one, even if a terminal's exact shades differ.
+const fn palette_16(color: Color) -> u8 {
+    match color {
+        Color::Black => 30,
+        Color::DarkRed => 31,
+        Color::DarkGreen => 32,
+        Color::Gold => 33,
+        Color::DarkBlue => 34,
+        Color::DarkPurple => 35,
+        Color::DarkAqua => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::Red => 91,
+        Color::Green => 92,
+        Color::Yellow => 93,
+        Color::Blue => 94,
+        Color::LightPurple => 95,
+        Color::Aqua => 96,
+        Color::White => 97,
+    }
+}
+
+/// Writes the body of a work, keeping track of which formats are applied in the terminal.
+///
+/// Formatting is reset at the end of every line and reapplied on the next, so that it does not
+/// leak into the rest of the terminal (or get lost by pagers like `less`, which reset every line).
+pub struct Document<'w, W: Write> {
+    output: &'w mut Utf8Writer<W>,
+    depth: ColorDepth,
+    /// The formats that apply to the next text, in the order they were applied.
+    active: Vec<Format>,
+    /// The formats that have been applied in the output since the last reset.
+    applied: Vec<Format>,
+}
+
+impl<'w, W: Write> Document<'w, W> {
+    /// Create a new [`Document`] that writes into `output`, writing colors with `depth`.
+    pub const fn new(output: &'w mut Utf8Writer<W>, depth: ColorDepth) -> Self {
+        Self {
+            output,
+            depth,
+            active: vec![],
+            applied: vec![],
+        }
+    }
+
+    /// Write a token into the output.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn token(&mut self, token: &Token) -> Result<()> {
+        match token {
+            Token::Text(text) => {
+                self.apply()?;
+                self.output.write_str(printable(text))?;
+            }
+            Token::Space => {
+                self.apply()?;
+                self.output.write_char(' ')?;
+            }
+            Token::Format(Format::Reset) => self.active.clear(),
+            // A terminal chooses its own font, has no text shadows, and has no way to click or
+            // hover over text. Content is filled in before exporting
+            Token::Format(Format::Font(_) | Format::ShadowColor(_))
+            | Token::Click(_)
+            | Token::Hover(_)
+            | Token::Insertion(_)
+            | Token::Content(_) => (),
+            Token::Format(format) => {
+                // A terminal only has one foreground color, so a new color replaces the last
+                let replaced = self.active.iter().position(|active| {
+                    matches!((active, format), (Format::Color(_), Format::Color(_)))
+                });
+
+                match replaced {
+                    Some(index) => self.active[index] = *format,
+                    None if !self.active.contains(format) => self.active.push(*format),
+                    None => (),
+                }
+            }
+            Token::LineBreak | Token::ParagraphBreak => {
+                self.reset()?;
+                self.output.write_char('\n')?;
+            }
+            Token::ThematicBreak => {
+                self.reset()?;
+                writeln!(self.output, "{CSI}2m{}{RESET}", "─".repeat(RULE_WIDTH))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reset any formatting left in the terminal.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn finish(mut self) -> Result<()> {
+        self.reset()?;
+        self.output.flush()
+    }
+
+    /// Bring the formats applied in the output in line with the active formats.
+    ///
+    /// Formats can be added on top of what is applied already, but removing one requires a reset.
+    fn apply(&mut self) -> Result<()> {
+        if self.applied == self.active {
+            return Ok(());
+        }
+
+        let start = if self.active.starts_with(&self.applied) {
+            self.applied.len()
+        } else {
+            self.reset()?;
+            0
+        };
+
+        if self.active.len() == start {
+            return Ok(());
+        }
+
+        let parameters: Vec<String> = self.active[start..]
+            .iter()
+            .map(|&format| parameters(format, self.depth))
+            .collect();
+        write!(self.output, "{CSI}{}m", parameters.join(";"))?;
+
+        self.applied.clone_from(&self.active);
+
+        Ok(())
+    }
+
+    /// Reset all formatting in the output, if any is applied.
+    fn reset(&mut self) -> Result<()> {
+        if !self.applied.is_empty() {
+            self.output.write_str(RESET)?;
+            self.applied.clear();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Error definitions for [`super::Anvil`].
+//!
+//! See [`TokenizeError`].
+
+use crate::format::nbt::NbtError;
+
+/// All the errors that could occur while finding books in an Anvil world.
+#[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
+#[derive(thiserror::Error, Debug)]
+pub enum TokenizeError {
+    /// Encountered when a chunk's location in the region file's header points outside of the
+    /// file, or its length does not fit inside of the file.
+    #[error("chunk {0} lies outside of the region file")]
+    InvalidChunk(usize),
+    /// Encountered when a chunk uses a compression scheme that is not supported, ex. LZ4.
+    #[error("unsupported chunk compression type {0}")]
+    UnsupportedCompression(u8),
+    /// Encountered when a chunk is stored in a separate `.mcc` file, but the region was not read
+    /// from a file.
+    #[error("chunk {0} is stored outside of the region file")]
+    ExternalChunk(usize),
+    /// Encountered when a chunk does not contain valid NBT.
+    #[error("could not read NBT: {0}")]
+    Nbt(#[from] NbtError),
+    /// Encountered when an I/O action fails in some way.
+    #[error("could not perform I/O action: {0}")]
+    Io(#[from] std::io::Error),
+}
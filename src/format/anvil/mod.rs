@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Finding written books in Minecraft: Java Edition worlds, stored in the [Anvil] format.
+//! See [`Anvil`] for more details.
+//!
+//! [Anvil]: https://minecraft.wiki/w/Anvil_file_format
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use crafty_novels::{export::Html, import::Anvil, syntax::Metadata, Export};
+//! # use std::error::Error;
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let found = Anvil::tokenize_world("saves/New World")?;
+//! for skipped in found.skipped() {
+//!     eprintln!("skipped a book at {:?}: {}", skipped.location(), skipped.error());
+//! }
+//!
+//! for book in found.into_books() {
+//!     for metadata in book.metadata_as_slice() {
+//!         if let Metadata::Position { x, y, z } = metadata {
+//!             eprintln!("found a book at {x} {y} {z}");
+//!         }
+//!     }
+//!
+//!     println!("{}", Html::export_token_vector_to_string(book));
+//! }
+//! #
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::format::nbt::{book::FoundBooks, Tag};
+pub use error::TokenizeError;
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+mod error;
+mod region;
+mod scan;
+#[cfg(test)]
+mod test;
+
+/// The dimensions of a vanilla world, and the directories they are stored in.
+const DIMENSIONS: [(&str, &str); 3] = [
+    ("minecraft:overworld", ""),
+    ("minecraft:the_nether", "DIM-1"),
+    ("minecraft:the_end", "DIM1"),
+];
+
+/// Finds written books in Minecraft: Java Edition worlds.
+///
+/// # Expected format
+///
+/// A world is a directory (ex. `.minecraft/saves/New World`) where each dimension holds:
+///
+/// - `region/r.X.Z.mca`, region files holding chunks with block entities (and, before 1.17,
+///   entities)
+/// - `entities/r.X.Z.mca`, region files holding entities (1.17+)
+///
+/// The dimensions are found in:
+///
+/// - The overworld, in the world directory itself
+/// - The Nether, in `DIM-1`
+/// - The End, in `DIM1`
+/// - Custom dimensions, in `dimensions/NAMESPACE/NAME`
+///
+/// Chunks can be compressed with gzip or zlib, or be uncompressed, and can be stored in separate
+/// `c.X.Z.mcc` files if they are too large for the region file.
+///
+/// Every block entity and entity is searched for written books and writable book drafts, including
+/// inside of items that hold other items, like shulker boxes. Each book becomes its own
+/// [`TokenList`], tokenized like [SNBT][`crate::import::Snbt`] books, with the following
+/// [`Metadata`][`crate::syntax::Metadata`] after the title and author (which drafts do not have):
+///
+/// - [`Dimension`][`crate::syntax::Metadata::Dimension`], when reading a whole world
+/// - [`Container`][`crate::syntax::Metadata::Container`], the ID of the block entity or entity
+///   holding the book
+/// - [`Position`][`crate::syntax::Metadata::Position`], the block position of the block entity or
+///   entity holding the book
+///
+/// A book that is not valid, ex. one with an unknown color name, does not stop the rest from being
+/// found. It is [skipped][`FoundBooks::skipped`] and reported with the same metadata for where it
+/// was found.
+pub struct Anvil;
+
+impl Anvil {
+    /// Find every written book in a world directory.
+    ///
+    /// Books are returned in the order of their dimension (overworld, Nether, End, then custom
+    /// dimensions), then their region file, then their chunk.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Io`] if a directory or file cannot be read
+    /// - Any error from [`Self::tokenize_region`]
+    pub fn tokenize_world(world: impl AsRef<Path>) -> Result<FoundBooks, TokenizeError> {
+        let world = world.as_ref();
+
+        let mut dimensions: Vec<(Box<str>, PathBuf)> = DIMENSIONS
+            .iter()
+            .map(|&(name, directory)| (name.into(), world.join(directory)))
+            .collect();
+
+        let custom = world.join("dimensions");
+        if custom.is_dir() {
+            for namespace in sorted_entries(&custom)? {
+                for dimension in sorted_entries(&namespace)? {
+                    let name = format!(
+                        "{}:{}",
+                        namespace.file_name().unwrap_or_default().to_string_lossy(),
+                        dimension.file_name().unwrap_or_default().to_string_lossy()
+                    );
+                    dimensions.push((name.into(), dimension));
+                }
+            }
+        }
+
+        let mut output = FoundBooks::default();
+
+        for (name, directory) in dimensions {
+            for kind in ["region", "entities"] {
+                let directory = directory.join(kind);
+                if !directory.is_dir() {
+                    continue;
+                }
+
+                for path in sorted_entries(&directory)? {
+                    if path.extension().is_some_and(|extension| extension == "mca") {
+                        read_region_file(&mut output, &path, Some(&name))?;
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Find every written book in a region file (`.mca`).
+    ///
+    /// Because the dimension of a region file cannot be known from the file alone, the books are
+    /// not tagged with a [`Dimension`][`crate::syntax::Metadata::Dimension`].
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Io`] if the file, or a `.mcc` file next to it, cannot be read
+    /// - Any error from [`Self::tokenize_region`]
+    pub fn tokenize_region_file(path: impl AsRef<Path>) -> Result<FoundBooks, TokenizeError> {
+        let mut output = FoundBooks::default();
+
+        read_region_file(&mut output, path.as_ref(), None)?;
+
+        Ok(output)
+    }
+
+    /// Find every written book in a region, read from `input`.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::InvalidChunk`] if a chunk lies outside of the region
+    /// - [`TokenizeError::UnsupportedCompression`] if a chunk uses an unknown compression type
+    /// - [`TokenizeError::ExternalChunk`] if a chunk is stored in a separate `.mcc` file, which
+    ///   can only be read by [`Self::tokenize_region_file`]
+    /// - [`TokenizeError::Nbt`] if a chunk is not valid NBT
+    /// - [`TokenizeError::Io`] if `input` cannot be read
+    pub fn tokenize_region(mut input: impl Read) -> Result<FoundBooks, TokenizeError> {
+        let mut bytes = vec![];
+        input.read_to_end(&mut bytes)?;
+
+        let mut output = FoundBooks::default();
+
+        region::chunks(
+            &bytes,
+            |index| Err(TokenizeError::ExternalChunk(index)),
+            |chunk: Tag| scan::chunk(&mut output, &chunk, None),
+        )?;
+
+        Ok(output)
+    }
+}
+
+/// Find every written book in the region file at `path`, pushing them into `output`.
+///
+/// # Errors
+///
+/// See [`Anvil::tokenize_region_file`].
+fn read_region_file(
+    output: &mut FoundBooks,
+    path: &Path,
+    dimension: Option<&str>,
+) -> Result<(), TokenizeError> {
+    let bytes = fs::read(path)?;
+
+    // Region files are named `r.X.Z.mca`, and their external chunks `c.X.Z.mcc`, where `X` and `Z`
+    // are in chunks rather than regions
+    let region = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| {
+            let mut parts = name.split('.').skip(1).map(str::parse::<i32>);
+            Some((parts.next()?.ok()?, parts.next()?.ok()?))
+        });
+
+    let external = |index: usize| -> Result<Vec<u8>, TokenizeError> {
+        let (region_x, region_z) = region.ok_or(TokenizeError::ExternalChunk(index))?;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)] // Always < 32
+        let (x, z) = (
+            region_x * 32 + (index % 32) as i32,
+            region_z * 32 + (index / 32) as i32,
+        );
+
+        Ok(fs::read(path.with_file_name(format!("c.{x}.{z}.mcc")))?)
+    };
+
+    region::chunks(&bytes, external, |chunk: Tag| {
+        scan::chunk(output, &chunk, dimension);
+    })
+}
+
+/// Returns the paths of the entries of a directory, sorted so that results are predictable.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if the directory cannot be read
+fn sorted_entries(directory: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    Ok(entries)
+}
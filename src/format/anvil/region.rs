@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading chunks out of Anvil region files (`.mca`).
+//!
+//! A region file holds up to 32×32 chunks. It opens with a 4 KiB table of where each chunk is
+//! located, followed by a 4 KiB table of timestamps, followed by the chunks themselves, each
+//! aligned to 4 KiB sectors.
+
+use super::TokenizeError;
+use crate::format::nbt::{binary, Tag};
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+/// The size of a sector in a region file, in bytes.
+const SECTOR: usize = 4096;
+
+/// The number of chunks a region file can hold.
+pub const CHUNKS: usize = 32 * 32;
+
+/// Decompress and read every chunk in `region`, calling `chunk` with each.
+///
+/// Chunks that are stored in separate `.mcc` files are read with `external`, which is given the
+/// index of the chunk in the region (`x + z * 32`).
+///
+/// # Errors
+///
+/// - [`TokenizeError::InvalidChunk`] if a chunk lies outside of `region`
+/// - [`TokenizeError::UnsupportedCompression`] if a chunk uses an unknown compression type
+/// - [`TokenizeError::Nbt`] if a chunk is not valid NBT
+/// - Any error returned by `external`
+pub fn chunks(
+    region: &[u8],
+    external: impl Fn(usize) -> Result<Vec<u8>, TokenizeError>,
+    mut chunk: impl FnMut(Tag),
+) -> Result<(), TokenizeError> {
+    for index in 0..CHUNKS {
+        let Some(location) = region.get(index * 4..index * 4 + 4) else {
+            // A region file can be truncated to only the chunks it holds
+            break;
+        };
+        let offset = usize::from(location[0]) << 16
+            | usize::from(location[1]) << 8
+            | usize::from(location[2]);
+        if offset == 0 {
+            // This chunk has not been generated
+            continue;
+        }
+
+        let start = offset * SECTOR;
+        let header = region
+            .get(start..start + 5)
+            .ok_or(TokenizeError::InvalidChunk(index))?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let compression = header[4];
+
+        // The length includes the compression type
+        let data = region
+            .get(start + 5..(start + 4).saturating_add(length))
+            .ok_or(TokenizeError::InvalidChunk(index))?;
+
+        // The high bit marks chunks that are too large, and stored in a separate file
+        if compression & 0x80 == 0 {
+            chunk(decompress(data, compression)?);
+        } else {
+            chunk(decompress(&external(index)?, compression & 0x7F)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Decompress and read the NBT of a single chunk.
+///
+/// # Errors
+///
+/// - [`TokenizeError::UnsupportedCompression`] if `compression` is not gzip (`1`), zlib (`2`), or
+///   uncompressed (`3`)
+/// - [`TokenizeError::Nbt`] if `data` is not valid NBT
+fn decompress(data: &[u8], compression: u8) -> Result<Tag, TokenizeError> {
+    Ok(match compression {
+        1 => binary::read(GzDecoder::new(data))?,
+        2 => binary::read(ZlibDecoder::new(data))?,
+        3 => binary::read(data)?,
+        _ => return Err(TokenizeError::UnsupportedCompression(compression)),
+    })
+}
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Finding written books inside of chunk NBT.
+//!
+//! Books can be held by block entities (ex. chests, barrels, shulker boxes, lecterns) and entities
+//! (ex. item frames, minecarts with chests, dropped items), as well as by items inside of them
+//! (ex. shulker boxes and bundles), so every block entity and entity is searched all the way
+//! down.
+
+use crate::{
+    format::nbt::{
+        book::{self, FoundBooks},
+        Tag,
+    },
+    syntax::Metadata,
+};
+
+/// Find every written book in a chunk, pushing them into `output`.
+///
+/// Understands chunks from region files (`block_entities`, or `Level.TileEntities` and
+/// `Level.Entities` before 1.18) and from entity files (`Entities`, 1.17+).
+///
+/// Each book is tagged with `dimension` (if known) and with the ID and position of the block
+/// entity or entity holding it, in the form of [`Metadata`]. Books that are not valid are skipped
+/// with that same location.
+pub fn chunk(output: &mut FoundBooks, chunk: &Tag, dimension: Option<&str>) {
+    let level = chunk.get("Level");
+
+    let holders = [
+        chunk.get("block_entities"),
+        level.and_then(|level| level.get("TileEntities")),
+        chunk.get("Entities"),
+        level.and_then(|level| level.get("Entities")),
+    ];
+
+    for holder in holders
+        .into_iter()
+        .flatten()
+        .filter_map(Tag::as_list)
+        .flatten()
+    {
+        let mut books: Vec<&Tag> = vec![];
+        book::find(&mut books, holder);
+
+        for found in books {
+            let mut location = vec![];
+            if let Some(dimension) = dimension {
+                location.push(Metadata::Dimension(dimension.into()));
+            }
+            if let Some(id) = holder.get("id").and_then(Tag::as_str) {
+                location.push(Metadata::Container(id.into()));
+            }
+            if let Some(position) = position(holder) {
+                location.push(position);
+            }
+
+            output.push(found, location);
+        }
+    }
+}
+
+/// Returns the block position of a block entity (`x`, `y`, and `z`) or entity (`Pos`).
+fn position(holder: &Tag) -> Option<Metadata> {
+    if let [Some(Tag::Int(x)), Some(Tag::Int(y)), Some(Tag::Int(z))] =
+        [holder.get("x"), holder.get("y"), holder.get("z")]
+    {
+        return Some(Metadata::Position {
+            x: *x,
+            y: *y,
+            z: *z,
+        });
+    }
+
+    // Entities can be anywhere within a block, so round down to the block they are in
+    #[allow(clippy::cast_possible_truncation)] // Minecraft worlds are within `i32`'s range
+    let block = |tag: &Tag| match tag {
+        Tag::Double(double) => Some(double.floor() as i32),
+        _ => None,
+    };
+
+    match holder.get("Pos")?.as_list()? {
+        [x, y, z] => Some(Metadata::Position {
+            x: block(x)?,
+            y: block(y)?,
+            z: block(z)?,
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,391 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for finding books in [Anvil][`super::Anvil`] worlds, using synthetic region files.
+
+use super::{Anvil, TokenizeError};
+use crate::{
+    format::nbt::{
+        test::{book, compound, encode, string},
+        BookError, Tag,
+    },
+    syntax::{Metadata, TokenList},
+};
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+use std::{fs, io::Write, path::PathBuf};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Build a region file out of `(index, compression, chunk)`, compressing each chunk accordingly.
+fn region(chunks: &[(usize, u8, Tag)]) -> Vec<u8> {
+    let mut locations = vec![0; 4096];
+    let mut sectors: Vec<u8> = vec![];
+
+    for (index, compression, chunk) in chunks {
+        let nbt = encode(chunk);
+        let data = match compression & 0x7F {
+            1 => {
+                let mut encoder = GzEncoder::new(vec![], Compression::default());
+                encoder
+                    .write_all(&nbt)
+                    .expect("writing to a `Vec` is infallible");
+                encoder.finish().expect("writing to a `Vec` is infallible")
+            }
+            2 => {
+                let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+                encoder
+                    .write_all(&nbt)
+                    .expect("writing to a `Vec` is infallible");
+                encoder.finish().expect("writing to a `Vec` is infallible")
+            }
+            _ => nbt,
+        };
+
+        let offset = 2 + sectors.len() / 4096;
+        locations[index * 4..index * 4 + 3].copy_from_slice(&offset.to_be_bytes()[5..]);
+
+        let length = u32::try_from(data.len() + 1).expect("test chunks should be small");
+        sectors.extend(length.to_be_bytes());
+        sectors.push(*compression);
+        if compression & 0x80 == 0 {
+            sectors.extend(data);
+        }
+        sectors.resize(sectors.len().div_ceil(4096) * 4096, 0);
+        locations[index * 4 + 3] =
+            u8::try_from(sectors.len() / 4096 + 2 - offset).expect("test chunks should be small");
+    }
+
+    // Locations, then timestamps, then chunks
+    locations.resize(8192, 0);
+    locations.extend(sectors);
+    locations
+}
+
+/// Returns the titles and the metadata following them of every book in `books`.
+fn summarize(books: &[TokenList]) -> Vec<&[Metadata]> {
+    books
+        .iter()
+        .map(|book| &book.metadata_as_slice()[1..])
+        .collect()
+}
+
+/// Build the metadata of a book, following its title.
+macro_rules! metadata {
+    ( $( $metadata:expr ),* $(,)? ) => {
+        [
+            Metadata::Author("RemasteredArch".into()),
+            $( $metadata ),*
+        ].as_slice()
+    };
+}
+
+#[allow(clippy::too_many_lines)]
+#[test]
+fn test_region() -> Result {
+    // 1.18+ chunk, with nested containers
+    let modern = compound!(
+        "DataVersion" => Tag::Int(3465),
+        "block_entities" => Tag::List(Box::new([
+            compound!(
+                "id" => string("minecraft:chest"),
+                "x" => Tag::Int(1),
+                "y" => Tag::Int(64),
+                "z" => Tag::Int(-2),
+                "Items" => Tag::List(Box::new([
+                    compound!("Slot" => Tag::Byte(0), "id" => string("minecraft:stone"), "Count" => Tag::Byte(64)),
+                    book("In a chest", &["Page one"]),
+                    compound!(
+                        "id" => string("minecraft:shulker_box"),
+                        "Count" => Tag::Byte(1),
+                        "tag" => compound!("BlockEntityTag" => compound!(
+                            "Items" => Tag::List(Box::new([book("In a shulker box", &["Page one"])])),
+                        )),
+                    ),
+                ])),
+            ),
+            compound!(
+                "id" => string("minecraft:lectern"),
+                "x" => Tag::Int(5),
+                "y" => Tag::Int(65),
+                "z" => Tag::Int(6),
+                "Book" => book("On a lectern", &["Page one"]),
+            ),
+            // A book without contents is not a book worth reading
+            compound!(
+                "id" => string("minecraft:barrel"),
+                "x" => Tag::Int(0),
+                "y" => Tag::Int(0),
+                "z" => Tag::Int(0),
+                "Items" => Tag::List(Box::new([compound!("id" => string("minecraft:written_book"))])),
+            ),
+        ])),
+    );
+
+    // Pre-1.18 chunk, with entities
+    let legacy = compound!(
+        "Level" => compound!(
+            "TileEntities" => Tag::List(Box::new([])),
+            "Entities" => Tag::List(Box::new([
+                compound!(
+                    "id" => string("minecraft:item_frame"),
+                    "Pos" => Tag::List(Box::new([Tag::Double(10.5), Tag::Double(70.0), Tag::Double(-3.5)])),
+                    "Item" => book("In an item frame", &["Page one"]),
+                ),
+                compound!(
+                    "id" => string("minecraft:item"),
+                    "Pos" => Tag::List(Box::new([Tag::Double(0.2), Tag::Double(1.0), Tag::Double(0.0)])),
+                    "Item" => book("Dropped", &["Page one"]),
+                ),
+            ])),
+        ),
+    );
+
+    // 1.20.5+ entity chunk, with data components
+    let entities = compound!(
+        "Entities" => Tag::List(Box::new([compound!(
+            "id" => string("minecraft:chest_minecart"),
+            "Pos" => Tag::List(Box::new([Tag::Double(-0.5), Tag::Double(80.0), Tag::Double(3.0)])),
+            "Items" => Tag::List(Box::new([compound!(
+                "id" => string("minecraft:bundle"),
+                "count" => Tag::Int(1),
+                "components" => compound!(
+                    "minecraft:bundle_contents" => Tag::List(Box::new([compound!(
+                        "id" => string("minecraft:written_book"),
+                        "count" => Tag::Int(1),
+                        "components" => compound!(
+                            "minecraft:written_book_content" => compound!(
+                                "title" => compound!("raw" => string("In a bundle")),
+                                "author" => string("RemasteredArch"),
+                                "pages" => Tag::List(Box::new([string("Page one")])),
+                            ),
+                        ),
+                    )])),
+                ),
+            )])),
+        )])),
+    );
+
+    let bytes = region(&[(0, 2, modern), (5, 1, legacy), (1023, 3, entities)]);
+    let books = Anvil::tokenize_region(bytes.as_slice())?.into_books();
+
+    /// Insert a [`Metadata::Position`] with the given coordinates.
+    macro_rules! position {
+        ($x:expr, $y:expr, $z:expr) => {
+            Metadata::Position {
+                x: $x,
+                y: $y,
+                z: $z,
+            }
+        };
+    }
+
+    /// Insert a [`Metadata::Container`] with the given ID.
+    macro_rules! container {
+        ($id:expr) => {
+            Metadata::Container($id.into())
+        };
+    }
+
+    let titles: Vec<&Metadata> = books
+        .iter()
+        .map(|book| &book.metadata_as_slice()[0])
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            "In a chest",
+            "In a shulker box",
+            "On a lectern",
+            "In an item frame",
+            "Dropped",
+            "In a bundle",
+        ]
+        .map(|title| Metadata::Title(title.into()))
+        .iter()
+        .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        summarize(&books),
+        [
+            metadata!(container!("minecraft:chest"), position!(1, 64, -2)),
+            metadata!(container!("minecraft:chest"), position!(1, 64, -2)),
+            metadata!(container!("minecraft:lectern"), position!(5, 65, 6)),
+            metadata!(container!("minecraft:item_frame"), position!(10, 70, -4)),
+            metadata!(container!("minecraft:item"), position!(0, 1, 0)),
+            metadata!(container!("minecraft:chest_minecart"), position!(-1, 80, 3)),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_world() -> Result {
+    let world: PathBuf =
+        std::env::temp_dir().join(format!("crafty_novels-anvil-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&world);
+
+    let chest = |title: &str| {
+        compound!("block_entities" => Tag::List(Box::new([compound!(
+            "id" => string("minecraft:chest"),
+            "x" => Tag::Int(0),
+            "y" => Tag::Int(0),
+            "z" => Tag::Int(0),
+            "Items" => Tag::List(Box::new([book(title, &["Page one"])])),
+        )])))
+    };
+
+    for (directory, file, title) in [
+        ("region", "r.0.0.mca", "Overworld"),
+        ("DIM-1/region", "r.-1.0.mca", "Nether"),
+        (
+            "dimensions/crafty_novels/library/region",
+            "r.0.0.mca",
+            "Library",
+        ),
+    ] {
+        fs::create_dir_all(world.join(directory))?;
+        fs::write(
+            world.join(directory).join(file),
+            region(&[(0, 2, chest(title))]),
+        )?;
+    }
+
+    // A chunk too large for its region file, stored in `c.X.Z.mcc` instead
+    fs::create_dir_all(world.join("DIM1/entities"))?;
+    fs::write(
+        world.join("DIM1/entities/r.-1.1.mca"),
+        region(&[(33, 0x82, compound!())]),
+    )?;
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(&encode(&chest("End")))?;
+    fs::write(world.join("DIM1/entities/c.-31.33.mcc"), encoder.finish()?)?;
+
+    // Empty region files are left behind by the game, and other files should be ignored
+    fs::write(world.join("region/r.1.0.mca"), [])?;
+    fs::write(world.join("region/notes.txt"), "not a region")?;
+
+    let books = Anvil::tokenize_world(&world);
+    fs::remove_dir_all(&world)?;
+
+    let dimensions: Vec<(Metadata, Metadata)> = books?
+        .books()
+        .iter()
+        .map(|book| {
+            let metadata = book.metadata_as_slice();
+            (metadata[0].clone(), metadata[2].clone())
+        })
+        .collect();
+    assert_eq!(
+        dimensions,
+        [
+            ("Overworld", "minecraft:overworld"),
+            ("Nether", "minecraft:the_nether"),
+            ("End", "minecraft:the_end"),
+            ("Library", "crafty_novels:library"),
+        ]
+        .map(|(title, dimension)| (
+            Metadata::Title(title.into()),
+            Metadata::Dimension(dimension.into())
+        ))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    /// Assert that reading the region `$input` fails with an error matching `$pattern`.
+    macro_rules! fails {
+        ( $( $input:expr => $pattern:pat ),+ $(,)? ) => {
+            $(
+                let result = Anvil::tokenize_region($input.as_slice());
+                assert!(matches!(result, Err($pattern)), "{result:?}");
+            )+
+        };
+    }
+
+    let mut outside = region(&[(3, 2, compound!())]);
+    outside.truncate(8192);
+
+    let mut garbage = region(&[(0, 3, compound!())]);
+    garbage[8192 + 5] = 0xFF;
+
+    fails!(
+        outside => TokenizeError::InvalidChunk(3),
+        region(&[(0, 4, compound!())]) => TokenizeError::UnsupportedCompression(4),
+        region(&[(7, 0x82, compound!())]) => TokenizeError::ExternalChunk(7),
+        garbage => TokenizeError::Nbt(_),
+    );
+}
+
+#[test]
+fn test_skipped() -> Result {
+    let chunk = compound!("block_entities" => Tag::List(Box::new([
+        compound!(
+            "id" => string("minecraft:chest"),
+            "x" => Tag::Int(1),
+            "y" => Tag::Int(2),
+            "z" => Tag::Int(3),
+            "Items" => Tag::List(Box::new([
+                book("Unknown color", &[r#"{"text":"a","color":"mauve"}"#]),
+                book("Valid", &["Page one"]),
+                book("Missing format code", &["ends with §"]),
+            ])),
+        ),
+        compound!(
+            "id" => string("minecraft:barrel"),
+            "x" => Tag::Int(4),
+            "y" => Tag::Int(5),
+            "z" => Tag::Int(6),
+            "Items" => Tag::List(Box::new([book("Also valid", &["Page one"])])),
+        ),
+    ])));
+
+    // The invalid books do not stop the valid ones from being found
+    let found = Anvil::tokenize_region(region(&[(0, 2, chunk)]).as_slice())?;
+    assert!(!found.is_complete());
+    assert_eq!(
+        found
+            .books()
+            .iter()
+            .map(|book| &book.metadata_as_slice()[0])
+            .collect::<Vec<_>>(),
+        [
+            &Metadata::Title("Valid".into()),
+            &Metadata::Title("Also valid".into())
+        ]
+    );
+
+    // Each is reported with where it was found
+    let location = [
+        Metadata::Container("minecraft:chest".into()),
+        Metadata::Position { x: 1, y: 2, z: 3 },
+    ];
+    let skipped = found.skipped();
+    assert_eq!(skipped.len(), 2);
+    for skipped in skipped {
+        assert_eq!(skipped.location(), location);
+    }
+    assert!(matches!(skipped[0].error(), BookError::Component(_)));
+    assert!(matches!(skipped[1].error(), BookError::Conversion(_)));
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Error definitions for [`super::Epub`].
+//!
+//! See [`ExportError`].
+
+/// All the errors that could occur while exporting to EPUB.
+#[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    /// Encountered when the ZIP container cannot be written.
+    #[error("could not write ZIP container: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    /// Encountered when an I/O action fails in some way.
+    #[error("could not perform I/O action: {0}")]
+    Io(#[from] std::io::Error),
+}
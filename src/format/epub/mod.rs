@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Exporting for EPUB 3 e-books.
+//!
+//! See [`Epub`] for more details.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use crafty_novels::{
+//!    export::Epub,
+//!    syntax::{Metadata, Token, TokenList},
+//! };
+//!
+//! let input_metadata = Box::new([
+//!     Metadata::Title("crafty_novels".into()),
+//!     Metadata::Author("RemasteredArch".into()),
+//! ]);
+//! let input_tokens = Box::new([
+//!     Token::ThematicBreak,
+//!     Token::Text("Page".into()),
+//!     Token::Space,
+//!     Token::Text("one".into()),
+//!     Token::ThematicBreak,
+//!     Token::Text("Page".into()),
+//!     Token::Space,
+//!     Token::Text("two".into()),
+//! ]);
+//! let input = TokenList::new_from_boxed(input_metadata, input_tokens);
+//!
+//! let bytes = Epub::new().language("en-US").export_to_bytes(&input).unwrap();
+//! assert_eq!(&bytes[30..38], b"mimetype");
+//! assert_eq!(&bytes[38..58], b"application/epub+zip");
+//! ```
+
+use crate::{
+    resolve::Untranslated,
+    syntax::{Metadata, Token, TokenList},
+    writer::Utf8Writer,
+};
+use std::{
+    io::{Cursor, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+pub use error::ExportError;
+
+mod error;
+mod package;
+#[cfg(test)]
+mod test;
+
+/// Exporting for EPUB 3 e-books.
+///
+/// Because an EPUB is a ZIP archive rather than text, this does not implement
+/// [`Export`][`crate::Export`]. Use [`Epub::export_to_writer`] or [`Epub::export_to_bytes`]
+/// instead.
+///
+/// # Format
+///
+/// Each [page][`TokenList::pages`] of the book becomes its own XHTML document in the reading
+/// order, with a navigation document linking to each of them. A book without any contents still
+/// has one empty page.
+///
+/// Inside of each page, tokens are written the same way as [HTML][`crate::export::Html`], except
+/// that legacy colors use classes from a shared stylesheet instead of inline styles, and special
+/// characters are escaped with numeric character references, because XHTML does not know HTML's
+/// named entities.
+///
+/// The [title and author][`crate::syntax::Metadata`] are written into the package metadata. A book
+/// without a title is called "Untitled".
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Epub {
+    identifier: Option<Box<str>>,
+    language: Box<str>,
+    modified: Option<SystemTime>,
+}
+
+impl Epub {
+    /// Create a new [`Epub`] exporter with the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            identifier: None,
+            language: "en".into(),
+            modified: None,
+        }
+    }
+
+    /// The unique identifier of the book, like a UUID URN or an ISBN.
+    ///
+    /// Defaults to an identifier derived from the contents of the book, so that exporting the same
+    /// book twice produces the same identifier.
+    #[must_use]
+    pub fn identifier(mut self, identifier: impl Into<Box<str>>) -> Self {
+        self.identifier = Some(identifier.into());
+        self
+    }
+
+    /// The language of the book, as a BCP 47 language tag. Defaults to `"en"`.
+    #[must_use]
+    pub fn language(mut self, language: impl Into<Box<str>>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    /// When the book was last modified. Defaults to the time of exporting.
+    #[must_use]
+    pub const fn modified(mut self, modified: SystemTime) -> Self {
+        self.modified = Some(modified);
+        self
+    }
+
+    /// Parse a given abstract syntax vector into an EPUB using these options, then output that as
+    /// bytes.
+    ///
+    /// # Errors
+    ///
+    /// - [`ExportError::Zip`] if the ZIP container cannot be written
+    pub fn export_to_bytes(&self, tokens: &TokenList) -> Result<Box<[u8]>, ExportError> {
+        let tokens = &tokens.resolve(&Untranslated);
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        self.write_container(&mut zip, tokens)?;
+
+        Ok(zip.finish()?.into_inner().into_boxed_slice())
+    }
+
+    /// Parse a given abstract syntax vector into an EPUB using these options, then output that
+    /// into a writer, like a [`std::fs::File`].
+    ///
+    /// The whole archive is built in memory before any of it is written.
+    ///
+    /// # Errors
+    ///
+    /// - [`ExportError::Zip`] if the ZIP container cannot be written
+    /// - [`ExportError::Io`] if it cannot write into `output`
+    pub fn export_to_writer(
+        &self,
+        tokens: &TokenList,
+        output: &mut impl Write,
+    ) -> Result<(), ExportError> {
+        output.write_all(&self.export_to_bytes(tokens)?)?;
+        Ok(output.flush()?)
+    }
+
+    /// Write every file of the book into `zip`.
+    fn write_container(
+        &self,
+        zip: &mut ZipWriter<Cursor<Vec<u8>>>,
+        tokens: &TokenList,
+    ) -> Result<(), ExportError> {
+        let mut title = None;
+        let mut author = None;
+        for metadata in tokens.metadata_as_slice() {
+            match metadata {
+                Metadata::Title(t) => title = Some(t.as_ref()),
+                Metadata::Author(a) => author = Some(a.as_ref()),
+                // Where a book was found has no meaning in the document itself
+                Metadata::Dimension(_)
+                | Metadata::Container(_)
+                | Metadata::Position { .. }
+                | Metadata::Owner(_)
+                | Metadata::Slot(_)
+                | Metadata::Custom { .. } => (),
+            }
+        }
+
+        let identifier = self
+            .identifier
+            .clone()
+            .unwrap_or_else(|| default_identifier(tokens));
+        let modified = timestamp(self.modified.unwrap_or_else(SystemTime::now));
+        let metadata = package::Metadata {
+            identifier: &identifier,
+            title: title.unwrap_or("Untitled"),
+            author,
+            language: &self.language,
+            modified: &modified,
+        };
+
+        let mut pages: Vec<&[Token]> = tokens.pages().map(|page| page.tokens()).collect();
+        // An EPUB cannot have an empty reading order
+        if pages.is_empty() {
+            pages.push(&[]);
+        }
+
+        // The OCF specification requires `mimetype` to be the first file, and to be stored
+        // uncompressed, so that the type of the archive can be read from a fixed offset
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default();
+
+        zip.start_file("mimetype", stored)?;
+        zip.write_all(package::MIMETYPE.as_bytes())?;
+
+        zip.start_file("META-INF/container.xml", deflated)?;
+        zip.write_all(package::container().as_bytes())?;
+
+        zip.start_file(package::PACKAGE_PATH, deflated)?;
+        zip.write_all(package::package(&metadata, pages.len()).as_bytes())?;
+
+        zip.start_file("OEBPS/nav.xhtml", deflated)?;
+        zip.write_all(package::navigation(&metadata, pages.len()).as_bytes())?;
+
+        zip.start_file("OEBPS/style.css", deflated)?;
+        zip.write_all(package::stylesheet().as_bytes())?;
+
+        for (index, page) in pages.iter().enumerate() {
+            let number = index + 1;
+            zip.start_file(format!("OEBPS/{}", package::page_path(number)), deflated)?;
+            package::page(&mut Utf8Writer::new(&mut *zip), &metadata, number, page)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Epub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Return an identifier derived from the checksum of the book's metadata and contents.
+fn default_identifier(tokens: &TokenList) -> Box<str> {
+    let mut crc = flate2::Crc::new();
+    crc.update(format!("{:?}", tokens.metadata_as_slice()).as_bytes());
+    crc.update(format!("{:?}", tokens.tokens_as_slice()).as_bytes());
+
+    format!("crafty_novels:{:08x}", crc.sum()).into_boxed_str()
+}
+
+/// Format `time` as `CCYY-MM-DDThh:mm:ssZ`, the format required for `dcterms:modified`.
+///
+/// Times before the Unix epoch are written as the epoch itself.
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+
+    // Converting days since the epoch to a civil date, following Howard Hinnant's
+    // `civil_from_days`: https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Generating the individual files that make up an [EPUB][`super::Epub`].
+
+use crate::{
+    format::html::token_handling::{self, Dialect},
+    syntax::{
+        minecraft::{Color, ColorValue},
+        tree::Tree,
+        Token, TokenList,
+    },
+    writer::Utf8Writer,
+};
+use std::{fmt::Write as _, io::Write};
+
+/// The contents of `mimetype`, which must be the first file in the container.
+pub const MIMETYPE: &str = "application/epub+zip";
+
+/// The path of the package document, relative to the root of the container.
+pub const PACKAGE_PATH: &str = "OEBPS/content.opf";
+
+/// The opening of every XHTML document, up to the `<title>`.
+const XHTML_START: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+    "\n<!DOCTYPE html>\n",
+    r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops""#,
+);
+
+/// The metadata of the book, as written into the package and navigation documents.
+pub struct Metadata<'m> {
+    pub identifier: &'m str,
+    pub title: &'m str,
+    pub author: Option<&'m str>,
+    pub language: &'m str,
+    /// When the book was last modified, in the form `CCYY-MM-DDThh:mm:ssZ`.
+    pub modified: &'m str,
+}
+
+/// Returns `text` with the characters that have special meaning in XML escaped.
+pub fn escape(text: &str) -> String {
+    let mut bytes: Vec<u8> = vec![];
+
+    {
+        let mut writer = Utf8Writer::new(&mut bytes);
+        token_handling::insert_string_as_html(&mut writer, text, Dialect::Xhtml)
+            .and_then(|()| writer.flush())
+            .expect("writing into a `Vec<u8>` is infallible");
+    }
+
+    String::from_utf8(bytes).expect("`Utf8Writer` only writes UTF-8 encoded types")
+}
+
+/// Returns the name of the XHTML file for a page, counting from one.
+pub fn page_path(page: usize) -> String {
+    format!("page-{page}.xhtml")
+}
+
+/// Returns `META-INF/container.xml`, which points to the package document.
+pub fn container() -> String {
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">"#,
+            "\n<rootfiles>\n",
+            r#"<rootfile full-path="{}" media-type="application/oebps-package+xml" />"#,
+            "\n</rootfiles>\n</container>\n",
+        ),
+        PACKAGE_PATH
+    )
+}
+
+/// Returns the package document, listing the metadata, every file, and the reading order of
+/// `pages` pages.
+pub fn package(metadata: &Metadata, pages: usize) -> String {
+    let mut output = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id" xml:lang="{language}">"#,
+            "\n",
+            r#"<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">"#,
+            "\n",
+            r#"<dc:identifier id="id">{identifier}</dc:identifier>"#,
+            "\n<dc:title>{title}</dc:title>\n",
+        ),
+        language = escape(metadata.language),
+        identifier = escape(metadata.identifier),
+        title = escape(metadata.title),
+    );
+
+    if let Some(author) = metadata.author {
+        writeln!(output, "<dc:creator>{}</dc:creator>", escape(author)).expect(INFALLIBLE);
+    }
+
+    write!(
+        output,
+        concat!(
+            "<dc:language>{}</dc:language>\n",
+            r#"<meta property="dcterms:modified">{}</meta>"#,
+            "\n</metadata>\n<manifest>\n",
+            r#"<item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav" />"#,
+            "\n",
+            r#"<item id="style" href="style.css" media-type="text/css" />"#,
+            "\n",
+        ),
+        escape(metadata.language),
+        metadata.modified,
+    )
+    .expect(INFALLIBLE);
+
+    for page in 1..=pages {
+        writeln!(
+            output,
+            r#"<item id="page-{page}" href="{}" media-type="application/xhtml+xml" />"#,
+            page_path(page)
+        )
+        .expect(INFALLIBLE);
+    }
+
+    output.push_str("</manifest>\n<spine>\n");
+    for page in 1..=pages {
+        writeln!(output, r#"<itemref idref="page-{page}" />"#).expect(INFALLIBLE);
+    }
+    output.push_str("</spine>\n</package>\n");
+
+    output
+}
+
+/// Returns the navigation document, with a table of contents linking to each of `pages` pages.
+pub fn navigation(metadata: &Metadata, pages: usize) -> String {
+    let title = escape(metadata.title);
+    let mut output = format!(
+        concat!(
+            "{XHTML_START}",
+            r#" lang="{language}" xml:lang="{language}">"#,
+            "\n",
+            r#"<head><meta charset="utf-8" /><title>{title}</title></head>"#,
+            "\n<body>\n",
+            r#"<nav epub:type="toc" id="toc"><h1>{title}</h1>"#,
+            "\n<ol>\n",
+        ),
+        XHTML_START = XHTML_START,
+        language = escape(metadata.language),
+        title = title,
+    );
+
+    for page in 1..=pages {
+        writeln!(
+            output,
+            r#"<li><a href="{}">Page {page}</a></li>"#,
+            page_path(page)
+        )
+        .expect(INFALLIBLE);
+    }
+
+    output.push_str("</ol>\n</nav>\n</body>\n</html>\n");
+
+    output
+}
+
+/// Returns the stylesheet shared by every page, with a class for every [`Color`].
+pub fn stylesheet() -> String {
+    let mut output = String::from(concat!(
+        "article { white-space: pre-wrap; }\n",
+        "code { font-family: monospace; }\n",
+    ));
+    output.push_str(token_handling::TOOLTIP_STYLE);
+    output.push('\n');
+    output.push_str(token_handling::FONT_STYLE);
+    output.push('\n');
+
+    for color in Color::ALL {
+        let value = ColorValue::from(color);
+        let (red, green, blue) = value.fg().as_tuple();
+        writeln!(
+            output,
+            ".color-{} {{ color: rgb({red}, {green}, {blue}); }}",
+            value.name()
+        )
+        .expect(INFALLIBLE);
+    }
+
+    output
+}
+
+/// Write a single page into `output` as an XHTML document, using the same token handling as
+/// [HTML][`crate::export::Html`].
+///
+/// The page is sorted into its own [`Tree`], so that every page is well-formed on its own.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn page(
+    output: &mut Utf8Writer<impl Write>,
+    metadata: &Metadata,
+    page: usize,
+    tokens: &[Token],
+) -> std::io::Result<()> {
+    write!(
+        output,
+        concat!(
+            "{}",
+            r#" lang="{language}" xml:lang="{language}">"#,
+            "\n",
+            r#"<head><meta charset="utf-8" /><title>{title} - Page {page}</title>"#,
+            r#"<link rel="stylesheet" type="text/css" href="style.css" /></head>"#,
+            "\n<body><article>",
+        ),
+        XHTML_START,
+        language = escape(metadata.language),
+        title = escape(metadata.title),
+        page = page,
+    )?;
+
+    let tree = Tree::from(&TokenList::new_from_boxed(Box::new([]), tokens.into()));
+    let mut state = token_handling::State::new(tokens);
+    token_handling::handle_nodes(output, &mut state, tree.nodes(), Dialect::Xhtml)?;
+
+    output.write_str("</article></body>\n</html>\n")?;
+    output.flush()
+}
+
+/// The reason that writing into a [`String`] is guaranteed to succeed.
+const INFALLIBLE: &str = "writing into a `String` is infallible";
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for exporting to the [EPUB][`super::Epub`] format.
+
+use super::{timestamp, Epub};
+use crate::{
+    import::Stendhal,
+    syntax::{Metadata, Token, TokenList},
+    Tokenize,
+};
+use std::{
+    io::{Cursor, Read},
+    time::{Duration, UNIX_EPOCH},
+};
+use zip::{CompressionMethod, ZipArchive};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Insert a [`Token::Format`] with the given variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+const INPUT: &str = "title: crafty_novels
+author: RemasteredArch
+pages:
+#- This is the start of the page
+First line
+#- New Page
+Some §cRED§r & <escaped> text
+#- Italic:§o text";
+
+/// Read the file at `name` out of `archive` as a string.
+fn read(archive: &mut ZipArchive<Cursor<Box<[u8]>>>, name: &str) -> std::io::Result<String> {
+    let mut contents = String::new();
+    archive.by_name(name)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+#[test]
+fn test_container() -> Result {
+    let input = Stendhal::tokenize_string(INPUT)?;
+    let bytes = Epub::new()
+        .identifier("urn:isbn:9780000000000")
+        .modified(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        .export_to_bytes(&input)?;
+
+    // `mimetype` must be first, uncompressed, and without any extra fields
+    assert_eq!(&bytes[..4], b"PK\x03\x04");
+    assert_eq!(&bytes[30..38], b"mimetype");
+    assert_eq!(&bytes[38..58], b"application/epub+zip");
+
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    assert_eq!(archive.len(), 8);
+    assert_eq!(archive.by_index(0)?.name(), "mimetype");
+    assert_eq!(
+        archive.by_index(0)?.compression(),
+        CompressionMethod::Stored
+    );
+
+    let container = read(&mut archive, "META-INF/container.xml")?;
+    assert!(container.contains(r#"full-path="OEBPS/content.opf""#));
+
+    let package = read(&mut archive, "OEBPS/content.opf")?;
+    for expected in [
+        r#"<dc:identifier id="id">urn:isbn:9780000000000</dc:identifier>"#,
+        "<dc:title>crafty_novels</dc:title>",
+        "<dc:creator>RemasteredArch</dc:creator>",
+        "<dc:language>en</dc:language>",
+        r#"<meta property="dcterms:modified">2023-11-14T22:13:20Z</meta>"#,
+        r#"properties="nav""#,
+        r#"<itemref idref="page-1" />"#,
+        r#"<itemref idref="page-3" />"#,
+    ] {
+        assert!(package.contains(expected), "missing {expected}");
+    }
+    assert!(!package.contains("page-4"));
+
+    let navigation = read(&mut archive, "OEBPS/nav.xhtml")?;
+    assert!(navigation.contains(r#"<li><a href="page-2.xhtml">Page 2</a></li>"#));
+
+    let stylesheet = read(&mut archive, "OEBPS/style.css")?;
+    assert!(stylesheet.contains(".color-red { color: rgb(255, 85, 85); }"));
+    assert!(stylesheet.contains(".color-dark_blue { color: rgb(0, 0, 170); }"));
+
+    Ok(())
+}
+
+#[test]
+fn test_pages() -> Result {
+    let input = Stendhal::tokenize_string(INPUT)?;
+    let bytes = Epub::new().export_to_bytes(&input)?;
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+
+    let first = read(&mut archive, "OEBPS/page-1.xhtml")?;
+    assert!(first.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert!(first.contains("<title>crafty_novels - Page 1</title>"));
+    assert!(first.contains("This is the start of the page<br />First line"));
+
+    let second = read(&mut archive, "OEBPS/page-2.xhtml")?;
+    assert!(second.contains("Some <span class='color-red'>RED</span> &#38; &#60;escaped&#62; text"));
+
+    // Formatting left open at the end of a page is closed within it
+    let third = read(&mut archive, "OEBPS/page-3.xhtml")?;
+    assert!(third.contains("Italic:<i> text</i>"));
+
+    Ok(())
+}
+
+#[test]
+fn test_defaults() -> Result {
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([text!("Before"), Token::ThematicBreak, format!(Bold)]),
+    );
+    let bytes = Epub::new().language("fr").export_to_bytes(&input)?;
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+
+    // Contents before the first page become a page of their own
+    assert!(archive.by_name("OEBPS/page-2.xhtml").is_ok());
+
+    let package = read(&mut archive, "OEBPS/content.opf")?;
+    assert!(package.contains("<dc:title>Untitled</dc:title>"));
+    assert!(package.contains("<dc:language>fr</dc:language>"));
+    assert!(!package.contains("<dc:creator>"));
+
+    // The default identifier is stable for the same book
+    let again = Epub::new().language("fr").export_to_bytes(&input)?;
+    let mut again = ZipArchive::new(Cursor::new(again))?;
+    let identifier = |package: &str| {
+        package
+            .lines()
+            .find(|l| l.contains("dc:identifier"))
+            .map(str::to_owned)
+    };
+    assert_eq!(
+        identifier(&package),
+        identifier(&read(&mut again, "OEBPS/content.opf")?)
+    );
+
+    // An empty book still has a page
+    let empty =
+        TokenList::new_from_boxed(Box::new([Metadata::Title("Empty".into())]), Box::new([]));
+    let bytes = Epub::new().export_to_bytes(&empty)?;
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    assert!(archive.by_name("OEBPS/page-1.xhtml").is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_timestamp() {
+    assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+    assert_eq!(
+        timestamp(UNIX_EPOCH + Duration::from_hours(264_384)),
+        "2000-02-29T00:00:00Z"
+    );
+    assert_eq!(
+        timestamp(UNIX_EPOCH + Duration::from_secs(4_107_542_399)),
+        "2100-02-28T23:59:59Z"
+    );
+}
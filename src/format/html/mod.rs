@@ -61,7 +61,8 @@
 //! ```
 
 use crate::{
-    syntax::{minecraft::Format, TokenList},
+    resolve::Untranslated,
+    syntax::{tree::Tree, TokenList},
     writer::Utf8Writer,
     Export,
 };
@@ -71,7 +72,7 @@ mod error;
 mod syntax;
 #[cfg(test)]
 mod test;
-mod token_handling;
+pub(super) mod token_handling;
 
 /// Exporting for HTML.
 ///
@@ -84,19 +85,26 @@ mod token_handling;
 ///
 /// ```html
 /// <!DOCTYPE html>
-/// <html lang="en" dir="ltr">
+/// <html lang="{language}" dir="{direction}">
 /// <head>
 ///     <meta charset="utf-8" />
 /// ```
 ///
+/// Where `language` and `direction` are [`Html::language`] and [`Html::direction`], `"en"` and
+/// `"ltr"` by default.
+///
 /// At this point, [metadata][`crate::syntax::Metadata`] is written:
 ///
 /// ```html
 ///     <title>{title}</title>
 ///     <meta name="author" content="{author}" />
+///     <meta name="{key}" content="{value}" />
 /// ```
 ///
-/// And the `<head>` is closed and the contents are opened:
+/// With a `<meta>` tag for each [custom field][`crate::syntax::Metadata::Custom`].
+///
+/// If the contents need any styles, or [`Html::stylesheet`] is set, they are written into a
+/// `<style>`, followed by the stylesheet. And the `<head>` is closed and the contents are opened:
 ///
 /// ```html
 ///     <meta name="viewport" content="width=device-width, initial-scale=1.0" /
@@ -114,13 +122,34 @@ mod token_handling;
 ///       without the need for `&nbsp;`
 /// - Line breaks and paragraph breaks are represented by `<br />`
 /// - Thematic breaks are represented by `<hr />`
-/// - Colored text is represented as `<span style='color:{color}'>`
+/// - Colored text is represented as `<span style='color:{color};text-shadow:{offset} {shadow}'>`
 ///     - Where `color` is a hexademical representation of the color, ex. `#FFFFFF` for pure white
 /// - Obfuscated text is represented as `<code>`
 /// - Bold text is represented as `<b>`
 /// - Strikethrough text is represented as `<s>`
 /// - Underline text is represented as `<u>`
 /// - Italic text is represented as `<i>`
+/// - Text with a click event that changes the page is represented as a link to the start of that
+///   page (`<a href='#page-{page}'>`), where the page's `<hr />` is given an `id`
+/// - Text with a click event that opens a URL is represented as a link to it (`<a href='{url}'>`)
+///     - Only `http` and `https` URLs are linked, like in Minecraft
+///     - Other click events only mean something in the game, and are left out
+/// - Text with a hover event is represented as a tooltip that can be hovered over or focused,
+///   with the hover text following the text inside of a `<span role='tooltip'>`
+///     - The `<head>` then includes a `<style>` that hides the hover text until then
+/// - Text in one of Minecraft's fonts is represented as `<span class='font-{font}'>`
+///     - Where `font` is the name of the font, ex. `alt` for `minecraft:alt`
+///     - The `<head>` then includes a `<style>` that gives each font a `font-family`
+/// - Text with a shadow color is represented as `<span style='text-shadow:{offset} {color}'>`
+///     - Where `color` is a hexadecimal representation of the color with its opacity, ex.
+///       `#FFFFFF80` for half-transparent white
+///     - Colored text without a shadow color is given the shadow Minecraft would draw behind it,
+///       [the color's background value][`crate::syntax::minecraft::ColorValue::bg`], unless
+///       [`Html::text_shadows`] is disabled
+/// - Shift-click insertions only mean something in the game, and are left out
+///
+/// Elements are nested by where their formatting applies (see [`Tree`]), following Minecraft's
+/// rules, so each is closed where its formatting ends, even if the rest continues.
 ///
 /// And finally, the contents are closed:
 ///
@@ -129,69 +158,210 @@ mod token_handling;
 /// </body>
 /// </html>
 /// ```
-pub struct Html {}
+///
+/// The `<article>` can be replaced with another [element][`Html::element`], and given an
+/// [`id`][`Html::id`] and [`class`][`Html::class`].
+///
+/// As a [fragment][`Html::fragment`], only the `<style>` (if any) and the `<article>` are
+/// written, so that the result can be spliced into an existing page.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Html {
+    text_shadows: bool,
+    language: Box<str>,
+    direction: TextDirection,
+    stylesheet: Option<Box<str>>,
+    fragment: bool,
+    element: ContentElement,
+    id: Option<Box<str>>,
+    class: Option<Box<str>>,
+}
 
-impl Export for Html {
-    /// Parse a given abstract syntax vector into HTML, then output that as a string.
-    fn export_token_vector_to_string(tokens: TokenList) -> Box<str> {
-        let mut bytes: Vec<u8> = vec![];
-
-        Self::export_token_vector_to_writer(tokens, &mut bytes)
-            // https://github.com/rust-lang/rust/blob/1.80.1/library/std/src/io/impls.rs#L433-L437
-            // https://github.com/rust-lang/rust/blob/1.80.1/library/alloc/src/vec/mod.rs#L2569-L2592
-            .expect(
-                "the `std::io::Write` implementations for `Vec<u8>` are infallible (as of 1.80.1)",
-            );
-
-        String::from_utf8(bytes)
-            .expect("`Utf8Writer` only writes UTF-8 encoded types")
-            .into_boxed_str()
+impl Html {
+    /// Create a new [`Html`] exporter with the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            text_shadows: true,
+            language: "en".into(),
+            direction: TextDirection::LeftToRight,
+            stylesheet: None,
+            fragment: false,
+            element: ContentElement::Article,
+            id: None,
+            class: None,
+        }
+    }
+
+    /// Whether to write only the contents, for splicing into a larger page (`true`), or a
+    /// standalone document (`false`, the default).
+    ///
+    /// A fragment leaves out the metadata and everything outside of the `<body>`, except for the
+    /// `<style>` the contents need, which is written before them. Any formatting still open at the
+    /// end of the contents is closed, so that it does not spill into the rest of the page.
+    #[must_use]
+    pub const fn fragment(mut self, fragment: bool) -> Self {
+        self.fragment = fragment;
+        self
+    }
+
+    /// The element that holds the contents, [`ContentElement::Article`] by default.
+    #[must_use]
+    pub const fn element(mut self, element: ContentElement) -> Self {
+        self.element = element;
+        self
     }
 
-    /// Parse a given abstract syntax vector into HTML, then output that into a writer, like a
-    /// [`std::fs::File`].
+    /// The `id` of the element that holds the contents, or none (the default).
+    ///
+    /// It is escaped, so it can hold any text.
+    #[must_use]
+    pub fn id(mut self, id: impl Into<Box<str>>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// The `class` of the element that holds the contents, or none (the default).
+    ///
+    /// It is escaped, so it can hold any text, ex. `"book signed"` for two classes.
+    #[must_use]
+    pub fn class(mut self, class: impl Into<Box<str>>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// The language of the text, as a BCP 47 language tag, ex. `"fr"` or `"zh-Hans"`. `"en"` by
+    /// default.
+    #[must_use]
+    pub fn language(mut self, language: impl Into<Box<str>>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    /// The direction of the text, [`TextDirection::LeftToRight`] by default.
+    #[must_use]
+    pub const fn direction(mut self, direction: TextDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// CSS to add to the `<head>`, ex. `"article{font-family:serif}"`, or none (the default).
+    ///
+    /// It is written as-is, after the styles the contents need, so it can override them.
+    #[must_use]
+    pub fn stylesheet(mut self, stylesheet: impl Into<Box<str>>) -> Self {
+        self.stylesheet = Some(stylesheet.into());
+        self
+    }
+
+    /// Whether colored text without a shadow color should be given the shadow that Minecraft draws
+    /// behind text (`true`, the default), or no shadow (`false`).
+    ///
+    /// Minecraft does not draw shadows in books, but does in chat, on signs, and elsewhere.
+    #[must_use]
+    pub const fn text_shadows(mut self, text_shadows: bool) -> Self {
+        self.text_shadows = text_shadows;
+        self
+    }
+}
+
+/// The direction of text in an [`Html`] document, written as its `dir` attribute.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum TextDirection {
+    /// Left to right, like English (`"ltr"`).
+    #[default]
+    LeftToRight,
+    /// Right to left, like Arabic or Hebrew (`"rtl"`).
+    RightToLeft,
+    /// Left to the browser to decide from the text (`"auto"`).
+    Auto,
+}
+
+impl TextDirection {
+    /// The value of the `dir` attribute for this direction.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::LeftToRight => "ltr",
+            Self::RightToLeft => "rtl",
+            Self::Auto => "auto",
+        }
+    }
+}
+
+/// The element that holds the contents of an [`Html`] document.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ContentElement {
+    /// `<article>`, for a self-contained work.
+    #[default]
+    Article,
+    /// `<section>`, for part of a larger work.
+    Section,
+    /// `<div>`, without any meaning of its own.
+    Div,
+    /// `<main>`, for the main contents of a page.
+    Main,
+}
+
+impl ContentElement {
+    /// The name of the element.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Article => "article",
+            Self::Section => "section",
+            Self::Div => "div",
+            Self::Main => "main",
+        }
+    }
+}
+
+impl Default for Html {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Export for Html {
+    /// Parse a given abstract syntax vector into HTML using these options, then output that into
+    /// a writer, like a [`std::fs::File`].
     ///
     /// Guaranteed to only write valid UTF-8.
     ///
     /// # Errors
     ///
     /// - [`std::io::Error`] if it cannot write into `output`
-    fn export_token_vector_to_writer(
-        tokens: TokenList,
-        output: &mut impl Write,
-    ) -> std::io::Result<()> {
+    fn export_to_writer(&self, tokens: &TokenList, output: &mut impl Write) -> std::io::Result<()> {
+        let tokens = &tokens.resolve(&Untranslated);
         let mut writer = Utf8Writer::new(output);
 
-        token_handling::start_document(&mut writer, tokens.metadata_as_slice())?;
-
-        // Most readable
-        writer.write_str("<body><article style=white-space:break-spaces>")?;
-
-        // Most accurate
-        // Does, however, still consume spaces that break, which Minecraft books do not
-        // writer.write_str("<article style=line-break:anywhere>");
-
-        let mut format_token_stack: Vec<Format> = vec![];
-        for token in tokens.tokens_as_slice() {
-            token_handling::handle_token(&mut writer, &mut format_token_stack, token).map_err(
-                |e| match e {
-                    error::ExportError::Io(e) => e,
-                    _ => {
-                        // [`token_handling::handle_token`] states that it could return
-                        // [`Error::UnexpectedToken`], but that it will never cause the necessary
-                        // state to occur on its own.
-                        //
-                        // Because nothing else every mutates `format_token_stack`, this state will
-                        // never occur, and this particular error can be ignored.
-                        unreachable!(
-                            "`token_handling::handle_token` cannot create this error on its own"
-                        )
-                    }
-                },
+        if self.fragment {
+            token_handling::styles(&mut writer, self, tokens.tokens_as_slice())?;
+        } else {
+            token_handling::start_document(
+                &mut writer,
+                self,
+                tokens.metadata_as_slice(),
+                tokens.tokens_as_slice(),
             )?;
+            writer.write_str("<body>")?;
         }
 
-        writer.write_str("</article></body></html>")?;
+        token_handling::open_contents(&mut writer, self)?;
+
+        // The tree closes everything it opens, so nothing spills into the rest of a fragment
+        let mut state =
+            token_handling::State::new(tokens.tokens_as_slice()).shadows(self.text_shadows);
+        token_handling::handle_nodes(
+            &mut writer,
+            &mut state,
+            Tree::from(tokens).nodes(),
+            token_handling::Dialect::Html,
+        )?;
+
+        write!(writer, "</{}>", self.element.as_str())?;
+        if !self.fragment {
+            writer.write_str("</body></html>")?;
+        }
 
         writer.flush()?;
         Ok(())
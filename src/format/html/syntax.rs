@@ -303,13 +303,18 @@ pub struct HtmlEntityValue {
 }
 
 impl HtmlEntityValue {
-    pub fn new(literal: char, number: u16, name: Box<str>) -> Self {
+    pub const fn new(literal: char, number: u16, name: Box<str>) -> Self {
         Self {
             literal,
             number,
             name,
         }
     }
+
+    /// Returns the Unicode code point for the character, as used in `"&#NUMBER;"`.
+    pub const fn number(&self) -> u16 {
+        self.number
+    }
 }
 
 impl Display for HtmlEntityValue {
@@ -17,9 +17,12 @@
 
 //! Tests for parsing the [Stendhal][`super::Stendhal`] format.
 
-use super::Html;
+use super::{token_handling::FONT_STYLE, ContentElement, Html, TextDirection};
 use crate::{
-    syntax::{Token, TokenList},
+    syntax::{
+        minecraft::{ClickEvent, Font, Format, HoverEvent, Rgb, ShadowColor},
+        Token, TokenList,
+    },
     Export,
 };
 use std::sync::Arc;
@@ -49,7 +52,7 @@ macro_rules! format {
 macro_rules! color {
     ($color:ident) => {
         crate::syntax::Token::Format(crate::syntax::minecraft::Format::Color(
-            crate::syntax::minecraft::Color::$color,
+            crate::syntax::minecraft::Color::$color.into(),
         ))
     };
 }
@@ -126,6 +129,25 @@ fn html_string() {
                 "<title>test title</title>",
                 r#"<meta name="author" content="test author" />"#
             ), "body";
+        [
+            crate::syntax::Metadata::Custom {
+                key: "generator".into(),
+                value: "\"stendhal\" & co".into(),
+            },
+        ], [
+            text!("body"),
+        ] =>
+            r#"<meta name="generator" content="&quot;stendhal&quot; &amp; co" />"#, "body";
+        [
+            title!("<script>\"a\" & b"),
+            author!("\" onload=\"alert(1)"),
+        ], [
+            text!("body"),
+        ] =>
+            concat!(
+                "<title>&lt;script&gt;&quot;a&quot; &amp; b</title>",
+                r#"<meta name="author" content="&quot; onload=&quot;alert(1)" />"#
+            ), "body";
     );
     test!(
         [
@@ -159,7 +181,19 @@ fn html_string() {
             text!("RED"), Space,
             text!("text"),
             format!(Reset), LineBreak,
-        ] => "Some <span style='color:#FF5555'>RED text</span><br />";
+        ] => concat!(
+            "Some <span style='color:#FF5555;text-shadow:0.125em 0.125em #3F1515'>",
+            "RED text</span><br />",
+        );
+        [
+            Token::Format(Format::Color(Rgb::new(0x12, 0x0A, 0x34).into())),
+            text!("exact"),
+            color!(DarkBlue),
+            text!("padded"),
+        ] => concat!(
+            "<span style='color:#120A34;text-shadow:0.125em 0.125em #04020D'>exact</span>",
+            "<span style='color:#0000AA;text-shadow:0.125em 0.125em #00002A'>padded</span>",
+        );
         [
             text!("Italic:"),
             format!(Italic), Space,
@@ -190,3 +224,276 @@ fn html_string() {
         ] => "&lt;div&gt;HTML &amp;gt; &amp; &amp;amp;&lt;/div&gt;<br />";
     );
 }
+
+#[test]
+fn html_events() {
+    use Token::{Space, ThematicBreak};
+
+    /// Returns the contents of the `<article>` of `html`.
+    fn body(html: &str) -> &str {
+        let start = html
+            .find("break-spaces>")
+            .expect("the article to be opened")
+            + 13;
+        let end = html.rfind("</article>").expect("the article to be closed");
+        &html[start..end]
+    }
+
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            text!("Contents"),
+            Token::Click(ClickEvent::ChangePage(3)),
+            text!("Chapter"),
+            format!(Reset),
+            ThematicBreak,
+            Token::Click(ClickEvent::OpenUrl("https://example.com/?a=1&b=2".into())),
+            format!(Bold),
+            text!("link"),
+            // A new link replaces the last, while the bold text continues
+            Token::Click(ClickEvent::OpenUrl("javascript:alert(1)".into())),
+            text!("unsafe"),
+            format!(Reset),
+            Space,
+            Token::Click(ClickEvent::RunCommand("/say hi".into())),
+            text!("command"),
+            format!(Reset),
+            ThematicBreak,
+            text!("third"),
+        ]),
+    );
+    let html = Html::export_token_vector_to_string(input);
+
+    assert!(!html.contains("<style>"));
+    assert_eq!(
+        body(&html),
+        concat!(
+            "Contents<a href='#page-3'>Chapter</a><hr />",
+            "<b><a href='https://example.com/?a=1&amp;b=2'>link</a>unsafe</b> command",
+            "<hr id='page-3' />third",
+        )
+    );
+
+    // The first page has no thematic break to hold its anchor
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            text!("top"),
+            ThematicBreak,
+            Token::Click(ClickEvent::ChangePage(1)),
+            text!("back"),
+        ]),
+    );
+    assert_eq!(
+        body(&Html::export_token_vector_to_string(input)),
+        "<span id='page-1'></span>top<hr /><a href='#page-1'>back</a>"
+    );
+
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            Token::Hover(HoverEvent::ShowText(Box::new([
+                format!(Italic),
+                text!("a"),
+                Space,
+                text!("<tip>"),
+            ]))),
+            text!("hover"),
+            format!(Reset),
+            Space,
+            Token::Hover(HoverEvent::ShowText(Box::new([text!("second")]))),
+            text!("again"),
+            format!(Reset),
+        ]),
+    );
+    let html = Html::export_token_vector_to_string(input);
+
+    assert!(html.contains("<style>.tooltip{"));
+    assert_eq!(
+        body(&html),
+        concat!(
+            "<span class='tooltip' tabindex='0' aria-describedby='tooltip-1'>hover",
+            "<span role='tooltip' id='tooltip-1'><i>a &lt;tip&gt;</i></span></span> ",
+            "<span class='tooltip' tabindex='0' aria-describedby='tooltip-2'>again",
+            "<span role='tooltip' id='tooltip-2'>second</span></span>",
+        )
+    );
+}
+
+#[test]
+fn html_fonts_and_shadows() {
+    /// Returns the contents of the `<article>` of `html`.
+    fn body(html: &str) -> &str {
+        let start = html
+            .find("break-spaces>")
+            .expect("the article to be opened")
+            + 13;
+        let end = html.rfind("</article>").expect("the article to be closed");
+        &html[start..end]
+    }
+
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            Token::Format(Format::Font(Font::Alt)),
+            text!("a"),
+            // A new font replaces the last
+            format!(Bold),
+            Token::Format(Format::Font(Font::Uniform)),
+            text!("b"),
+            Token::Insertion("/say hi".into()),
+            format!(Reset),
+            Token::Format(Format::ShadowColor(ShadowColor::from_argb(0x80FF_0000))),
+            color!(Red),
+            text!("c"),
+            format!(Reset),
+            color!(Gold),
+            text!("d"),
+        ]),
+    );
+
+    let html = Html::export_token_vector_to_string(input.clone());
+    assert!(html.contains(FONT_STYLE));
+    assert_eq!(
+        body(&html),
+        concat!(
+            "<span class='font-alt'>a</span><b><span class='font-uniform'>b</span></b>",
+            // Colored text is given its default shadow, unless it already has a shadow color
+            "<span style='color:#FF5555'>",
+            "<span style='text-shadow:0.125em 0.125em #FF000080'>c</span></span>",
+            "<span style='color:#FFAA00;text-shadow:0.125em 0.125em #2A2A00'>d</span>",
+        )
+    );
+    // Unless text shadows are turned off
+    let html = Html::new().text_shadows(false).export_to_string(&input);
+    assert!(body(&html).ends_with("<span style='color:#FFAA00'>d</span>"));
+}
+
+#[test]
+fn html_minecraft_formatting() {
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            format!(Bold),
+            text!("a"),
+            // Like in Minecraft, a color clears the formatting before it
+            color!(Red),
+            text!("b"),
+            format!(Bold),
+            text!("c"),
+            // Formatting ends with the page
+            Token::ThematicBreak,
+            text!("d"),
+        ]),
+    );
+
+    let html = Html::export_token_vector_to_string(input);
+    let start = html.find("<b>").expect("the bold text to be opened");
+    let end = html.rfind("</article>").expect("the article to be closed");
+
+    assert_eq!(
+        &html[start..end],
+        concat!(
+            "<b>a</b><span style='color:#FF5555;text-shadow:0.125em 0.125em #3F1515'>",
+            "b<b>c</b></span><hr />d",
+        )
+    );
+}
+
+#[test]
+fn html_options() {
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([Token::Format(Format::Font(Font::Alt)), text!("a")]),
+    );
+
+    let html = Html::new()
+        .language("he")
+        .direction(TextDirection::RightToLeft)
+        .stylesheet("article{font-family:serif}")
+        .export_to_string(&input);
+    assert!(html.starts_with(concat!(
+        r#"<!DOCTYPE html><html lang="he" dir="rtl"><head><meta charset="utf-8" />"#,
+        "<style>"
+    )));
+    // The stylesheet follows the styles the contents need, so it can override them
+    assert!(html.contains(&[FONT_STYLE, "article{font-family:serif}</style>"].concat()));
+
+    // The language is an attribute value, so it is escaped
+    let html = Html::new()
+        .language(r#""><script>"#)
+        .direction(TextDirection::Auto)
+        .export_to_string(&TokenList::new_from_boxed(Box::new([]), Box::new([])));
+    assert!(html
+        .starts_with(r#"<!DOCTYPE html><html lang="&quot;&gt;&lt;script&gt;" dir="auto"><head>"#));
+    assert!(!html.contains("<style>"));
+}
+
+#[test]
+fn html_fragment() {
+    let input = TokenList::new_from_boxed(
+        Box::new([title!("Ignored")]),
+        Box::new([Token::ThematicBreak, text!("a"), Token::LineBreak]),
+    );
+
+    assert_eq!(
+        Html::new().fragment(true).export_to_string(&input).as_ref(),
+        "<article style=white-space:break-spaces><hr />a<br /></article>"
+    );
+    assert_eq!(
+        Html::new()
+            .fragment(true)
+            .element(ContentElement::Section)
+            .id("book-1")
+            .class("book \"signed\" <&>")
+            .export_to_string(&input)
+            .as_ref(),
+        concat!(
+            r#"<section id="book-1" class="book &quot;signed&quot; &lt;&amp;&gt;" "#,
+            "style=white-space:break-spaces><hr />a<br /></section>",
+        )
+    );
+
+    // The styles the contents need are written before them
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([Token::Format(Format::Font(Font::Alt)), text!("a")]),
+    );
+    let html = Html::new()
+        .fragment(true)
+        .stylesheet("section{}")
+        .element(ContentElement::Section)
+        .export_to_string(&input);
+    assert_eq!(
+        html.as_ref(),
+        [
+            "<style>",
+            FONT_STYLE,
+            "section{}</style>",
+            "<section style=white-space:break-spaces>",
+            "<span class='font-alt'>a</span></section>",
+        ]
+        .concat()
+    );
+
+    // A standalone document uses the element too
+    let html = Html::new()
+        .element(ContentElement::Div)
+        .id("book")
+        .export_to_string(&input);
+    assert!(html.contains(r#"<body><div id="book" style=white-space:break-spaces>"#));
+    assert!(html.ends_with("</div></body></html>"));
+
+    // The `id` cannot break out of its attribute
+    let html = Html::new()
+        .fragment(true)
+        .element(ContentElement::Main)
+        .id(r#"a" onclick="alert(1)"><script>"#)
+        .export_to_string(&input);
+    assert!(html.contains(concat!(
+        r#"<main id="a&quot; onclick=&quot;alert(1)&quot;&gt;&lt;script&gt;" "#,
+        "style=white-space:break-spaces>",
+    )));
+    assert!(!html.contains("<script>"));
+    assert!(html.ends_with("</main>"));
+}
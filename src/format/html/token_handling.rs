@@ -15,38 +15,190 @@
 // You should have received a copy of the GNU Affero General Public License along with
 // crafty_novels. If not, see <https://www.gnu.org/licenses/>.
 
-//! The actual, under the hood, token-by-token exporting for the [HTML][`super::Html`] format.
+//! The actual, under the hood, node-by-node exporting for the [HTML][`super::Html`] format.
+//!
+//! See [`Tree`].
 
-use super::{error::ExportError, syntax::HtmlEntity};
+use super::{
+    syntax::{HtmlEntity, HtmlEntityValue},
+    Html,
+};
 use crate::{
-    syntax::{minecraft::Format, Metadata, Token},
+    syntax::{
+        minecraft::{ClickEvent, ColorValue, Format, HoverEvent, TextColor},
+        tree::{Node, Span, Tree},
+        Metadata, Token, TokenList,
+    },
     writer::Utf8Writer,
 };
-use std::io::Write;
+use std::{collections::BTreeSet, io::Write};
+
+/// The flavors of HTML that tokens can be written as.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dialect {
+    /// HTML, as written by [`super::Html`].
+    Html,
+    /// XHTML, which is HTML parsed as XML, as used by EPUB.
+    ///
+    /// XML only knows of five named entities, so every entity is written by its number instead.
+    /// Legacy colors are written as classes (ex. `class='color-gold'`) to be styled by a
+    /// stylesheet, rather than as inline styles.
+    Xhtml,
+}
+
+/// The state kept across tokens while writing HTML.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct State {
+    /// The pages that [`ClickEvent::ChangePage`] links point to, which need an anchor.
+    targets: BTreeSet<usize>,
+    /// The number of the current page, or zero if no tokens have been written yet.
+    page: usize,
+    /// The number of tooltips written so far, used to give each one a unique ID.
+    tooltips: usize,
+    /// Whether colored text without a shadow color is given the shadow that Minecraft would draw
+    /// behind it.
+    shadows: bool,
+}
+
+impl State {
+    /// Create a new [`State`] for writing `tokens`, finding the pages that need an anchor ahead
+    /// of time.
+    pub fn new(tokens: &[Token]) -> Self {
+        /// Add the target of every [`ClickEvent::ChangePage`] in `tokens` into `targets`.
+        fn find_targets(targets: &mut BTreeSet<usize>, tokens: &[Token]) {
+            for token in tokens {
+                match token {
+                    Token::Click(ClickEvent::ChangePage(page)) => {
+                        targets.insert(*page);
+                    }
+                    Token::Hover(HoverEvent::ShowText(text)) => find_targets(targets, text),
+                    _ => (),
+                }
+            }
+        }
+
+        let mut targets = BTreeSet::new();
+        find_targets(&mut targets, tokens);
+
+        Self {
+            targets,
+            shadows: true,
+            ..Self::default()
+        }
+    }
 
-/// Push the appropriate HTML element(s) for `token` into `output`.
-/// If `token` is [`Token::Format`], it is pushed onto `format_token_stack`.
+    /// Whether colored text without a [`Format::ShadowColor`] should be given the shadow that
+    /// Minecraft would draw behind it, see [`TextColor::shadow`]. Enabled by default.
+    ///
+    /// Only applies to [`Dialect::Html`].
+    pub const fn shadows(mut self, shadows: bool) -> Self {
+        self.shadows = shadows;
+        self
+    }
+}
+
+/// Whether any token in `tokens` is a [`Token::Hover`], which needs [`TOOLTIP_STYLE`].
+fn has_tooltips(tokens: &[Token]) -> bool {
+    tokens.iter().any(|token| matches!(token, Token::Hover(_)))
+}
+
+/// Whether any token in `tokens`, including the contents of tooltips, is a [`Format::Font`],
+/// which needs [`FONT_STYLE`].
+fn has_fonts(tokens: &[Token]) -> bool {
+    tokens.iter().any(|token| match token {
+        Token::Format(Format::Font(_)) => true,
+        Token::Hover(HoverEvent::ShowText(text)) => has_fonts(text),
+        _ => false,
+    })
+}
+
+/// The CSS that hides the contents of a tooltip until its text is hovered over or focused.
+pub const TOOLTIP_STYLE: &str = concat!(
+    ".tooltip{position:relative;text-decoration:underline dotted}",
+    ".tooltip>[role=tooltip]{display:none;position:absolute;left:0;top:100%;z-index:1;",
+    "padding:0.25em 0.5em;white-space:pre;color:#FFFFFF;background:#100010;",
+    "border:2px solid #25015B}",
+    ".tooltip:hover>[role=tooltip],.tooltip:focus>[role=tooltip]{display:block}",
+);
+
+/// The CSS that gives each of Minecraft's fonts a font family, falling back to a monospace font
+/// where the reader does not have it installed.
+pub const FONT_STYLE: &str = concat!(
+    ".font-default{font-family:initial}",
+    ".font-alt{font-family:'Standard Galactic Alphabet',monospace}",
+    ".font-illageralt{font-family:Illageralt,monospace}",
+    ".font-uniform{font-family:Unifont,monospace}",
+);
+
+/// How far a text shadow is drawn from its text, right and down. Minecraft draws it one pixel
+/// away, where text is eight pixels tall.
+const SHADOW_OFFSET: &str = "0.125em 0.125em";
+
+/// Push the appropriate HTML elements for every node in `nodes` into `output`, using `state` to
+/// keep track of pages and tooltips.
 ///
 /// # Errors
 ///
-/// - [`ExportError::UnexpectedToken`] if `format_token_stack` contains [`Format::Reset`] and
-///   `token` is of variant [`Format::Reset`]
-///   - [`handle_token`] itself cannot cause this state, but assumes that the owner of
-///     `format_token_stack` could have done it
-/// - [`ExportError::Io`] if it cannot write into `output`
-pub fn handle_token(
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn handle_nodes(
     output: &mut Utf8Writer<impl Write>,
-    format_token_stack: &mut Vec<Format>,
+    state: &mut State,
+    nodes: &[Node],
+    dialect: Dialect,
+) -> std::io::Result<()> {
+    for node in nodes {
+        // Contents before the first thematic break are a page of their own
+        if state.page == 0 && !matches!(node, Node::Token(Token::ThematicBreak)) {
+            state.page = 1;
+            if dialect == Dialect::Html && state.targets.contains(&1) {
+                output.write_str("<span id='page-1'></span>")?;
+            }
+        }
+
+        match node {
+            Node::Token(token) => handle_token(output, state, token, dialect)?,
+            Node::Span(span) => {
+                let tooltip = open_span(output, state, span, dialect)?;
+                handle_nodes(output, state, span.children(), dialect)?;
+                close_span(output, state, span, tooltip, dialect)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Push the appropriate HTML element(s) for the visible `token` into `output`.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+fn handle_token(
+    output: &mut Utf8Writer<impl Write>,
+    state: &mut State,
     token: &Token,
-) -> Result<(), ExportError> {
-    match &token {
-        Token::Text(s) => insert_string_as_html(output, s)?,
-        Token::Format(f) => handle_format(output, format_token_stack, *f)?,
+    dialect: Dialect,
+) -> std::io::Result<()> {
+    match token {
+        Token::Text(s) => insert_string_as_html(output, s, dialect)?,
         Token::Space => output.write_str(" ")?,
-        Token::LineBreak => output.write_str("<br />")?,
-        Token::ParagraphBreak => output.write_str("<br />")?,
-        Token::ThematicBreak => output.write_str("<hr />")?,
-    };
+        Token::LineBreak | Token::ParagraphBreak => output.write_str("<br />")?,
+        Token::ThematicBreak => {
+            state.page += 1;
+
+            if dialect == Dialect::Html && state.targets.contains(&state.page) {
+                write!(output, "<hr id='page-{}' />", state.page)?;
+            } else {
+                output.write_str("<hr />")?;
+            }
+        }
+        // Content is filled in before exporting, and hidden tokens are only found as spans
+        Token::Content(_)
+        | Token::Format(_)
+        | Token::Click(_)
+        | Token::Hover(_)
+        | Token::Insertion(_) => (),
+    }
 
     Ok(())
 }
@@ -56,15 +208,29 @@ pub fn handle_token(
 /// For every character in `input`:
 ///
 /// - If a literal character corresponds to an [`HtmlEntity`], write that entity into `output`
+///     - By name for [`Dialect::Html`], by number for [`Dialect::Xhtml`]
 /// - Otherwise, write the character to `output`
 ///
 /// # Errors
 ///
 /// - [`std::io::Error`] if it cannot write into `output`
-fn insert_string_as_html(output: &mut Utf8Writer<impl Write>, input: &str) -> std::io::Result<()> {
+pub fn insert_string_as_html(
+    output: &mut Utf8Writer<impl Write>,
+    input: &str,
+    dialect: Dialect,
+) -> std::io::Result<()> {
     for char in input.chars() {
         if let Ok(as_html_entity) = HtmlEntity::try_from(&char) {
-            write!(output, "{as_html_entity}")?;
+            match dialect {
+                Dialect::Html => write!(output, "{as_html_entity}")?,
+                Dialect::Xhtml => {
+                    write!(
+                        output,
+                        "&#{};",
+                        HtmlEntityValue::from(as_html_entity).number()
+                    )?;
+                }
+            }
         } else {
             output.write_char(char)?;
         }
@@ -73,136 +239,280 @@ fn insert_string_as_html(output: &mut Utf8Writer<impl Write>, input: &str) -> st
     Ok(())
 }
 
-/// Push the appropriate HTML element for `format_token` into `output`.
-/// Pushes the `format_token` onto `format_token_stack`.
+/// Write the opening tag of `span` into `output`.
 ///
-/// If it hits [`Format::Reset`], it will call [`close_formatting_tags`].
+/// Returns the number of the tooltip that `span` opens, if it is a [`Token::Hover`]. A
+/// [`Token::Insertion`] only means something in the game, so it is left out.
 ///
 /// # Errors
 ///
-/// - [`ExportError::UnexpectedToken`] if `format_token_stack` contains [`Format::Reset`] and
-///   `format_token` is of variant [`Format::Reset`]
-///   - [`handle_format`] itself cannot cause this state, but assumes that the owner of
-///     `format_token_stack` could have done it
-/// - [`ExportError::Io`] if it cannot write into `output`
-fn handle_format(
+/// - [`std::io::Error`] if it cannot write into `output`
+fn open_span(
     output: &mut Utf8Writer<impl Write>,
-    format_token_stack: &mut Vec<Format>,
-    format_token: Format,
-) -> Result<(), ExportError> {
-    /// Generates a match statement with [`Format`] variants to write the given HTML (containing
-    /// opening tags) into `output`.
-    ///
-    /// - Provide `$color_var` (to use it inside `$color_html`).
-    /// - Provide `$format_token_stack` (to push `$format_token` into it).
-    macro_rules! open_html {
-        (
-            $output:expr, $format_token_stack:expr, $format_token:expr;
-            Color($color_var:ident) => $color_html:expr;
-            $( $format:ident => $html:expr ),+ ;
-            Reset => $reset_fn:expr;
-        ) => {
-            match $format_token {
-                Format::Color($color_var) => {
-                    $format_token_stack.push($format_token);
-                    write!($output, $color_html)?;
-                }
-                $(
-                    Format::$format => {
-                        $format_token_stack.push($format_token);
-                        $output.write_str($html)?;
-                    }
-                ),+ ,
-                Format::Reset => $reset_fn,
+    state: &mut State,
+    span: &Span,
+    dialect: Dialect,
+) -> std::io::Result<Option<usize>> {
+    match span.token() {
+        Token::Format(format) => match format {
+            Format::Color(color) => {
+                output.write_str(color_tag(*color, span, state, dialect))?;
+            }
+            Format::Font(font) => write!(output, "<span class='font-{}'>", font.name())?,
+            Format::ShadowColor(shadow) => {
+                write!(
+                    output,
+                    "<span style='text-shadow:{SHADOW_OFFSET} {shadow}'>"
+                )?;
             }
-        };
+            Format::Obfuscated => output.write_str("<code>")?,
+            Format::Bold => output.write_str("<b>")?,
+            Format::Strikethrough => output.write_str("<s>")?,
+            Format::Underline => output.write_str("<u>")?,
+            Format::Italic => output.write_str("<i>")?,
+            // A span is never opened by a reset
+            Format::Reset => (),
+        },
+        Token::Click(event) => {
+            if let Some(href) = href(event, dialect) {
+                output.write_str("<a href='")?;
+                insert_string_as_html(output, &href, dialect)?;
+                output.write_str("'>")?;
+            }
+        }
+        Token::Hover(_) => {
+            state.tooltips += 1;
+            write!(
+                output,
+                "<span class='tooltip' tabindex='0' aria-describedby='tooltip-{}'>",
+                state.tooltips
+            )?;
 
+            return Ok(Some(state.tooltips));
+        }
+        _ => (),
     }
 
-    open_html!(
-        output, format_token_stack, format_token;
-        Color(c) => "<span style='color:{c}'>";
-        Obfuscated => "<code>",
-        Bold => "<b>",
-        Strikethrough => "<s>",
-        Underline => "<u>",
-        Italic => "<i>";
-        Reset => close_formatting_tags(output, format_token_stack)?;
-    );
+    Ok(None)
+}
 
-    Ok(())
+/// Returns the opening tag for text of `color`, opened by `span`.
+///
+/// If shadows are enabled in `state`, and the text does not all have a shadow color to take
+/// precedence, the text is also given [its default shadow][`TextColor::shadow`].
+fn color_tag(color: TextColor, span: &Span, state: &State, dialect: Dialect) -> String {
+    /// Whether every visible token in `nodes` is inside of a span with a shadow color.
+    fn has_shadow_color(nodes: &[Node]) -> bool {
+        nodes.iter().all(|node| match node {
+            Node::Token(_) => false,
+            Node::Span(span) => {
+                span.style().shadow_color().is_some() || has_shadow_color(span.children())
+            }
+        })
+    }
+
+    match (dialect, color) {
+        (Dialect::Html, _) => {
+            if !state.shadows
+                || span.style().shadow_color().is_some()
+                || has_shadow_color(span.children())
+            {
+                format!("<span style='color:{color}'>")
+            } else {
+                format!(
+                    "<span style='color:{color};text-shadow:{SHADOW_OFFSET} {}'>",
+                    color.shadow()
+                )
+            }
+        }
+        (Dialect::Xhtml, TextColor::Legacy(c)) => {
+            format!("<span class='color-{}'>", ColorValue::from(c).name())
+        }
+        (Dialect::Xhtml, TextColor::Rgb(_)) => format!("<span style='color:{color}'>"),
+    }
 }
 
-/// Closes all the HTML elements opened in [`handle_format`] by the tokens in `format_token_stack`.
+/// Returns where a link for `event` points to, or [`None`] if it cannot be a link.
+fn href(event: &ClickEvent, dialect: Dialect) -> Option<String> {
+    match event {
+        ClickEvent::ChangePage(page) => Some(match dialect {
+            Dialect::Html => format!("#page-{page}"),
+            // Matches the names of the pages of an EPUB
+            Dialect::Xhtml => format!("page-{page}.xhtml"),
+        }),
+        // Minecraft only opens web links, so anything else (ex. `javascript:`) is left out
+        ClickEvent::OpenUrl(url) if url.starts_with("https://") || url.starts_with("http://") => {
+            Some(url.to_string())
+        }
+        // Commands and the clipboard only mean something in the game
+        _ => None,
+    }
+}
+
+/// Write the closing tag of `span` into `output`, opened in [`open_span`] with the number of its
+/// `tooltip`, if any.
+///
+/// Closing a tooltip writes its contents, so that they are shown when its text is hovered over or
+/// focused.
 ///
 /// # Errors
 ///
-/// - [`ExportError::UnexpectedToken`] if `format_token_stack` contains [`Format::Reset`]
-/// - [`ExportError::Io`] if it cannot write into `output`
-fn close_formatting_tags(
+/// - [`std::io::Error`] if it cannot write into `output`
+fn close_span(
     output: &mut Utf8Writer<impl Write>,
-    format_token_stack: &mut Vec<Format>,
-) -> Result<(), ExportError> {
-    /// Generates a match statement with [`Format`] variants to write the given HTML (containing
-    /// closing tags) into `output`.
-    macro_rules! close_html {
-        (
-            $output:expr, $format_token:expr;
-            Color => $color_html:expr;
-            $( $format:ident => $html:expr ),+ ;
-        ) => {
-            match $format_token {
-                Format::Color(_) => $output.write_str($color_html)?,
-                $(
-                    Format::$format => $output.write_str($html)?
-                ),+ ,
-                Format::Reset => return Err(
-                    ExportError::UnexpectedToken(Token::Format(Format::Reset))
-                ),
+    state: &mut State,
+    span: &Span,
+    tooltip: Option<usize>,
+    dialect: Dialect,
+) -> std::io::Result<()> {
+    match (span.token(), tooltip) {
+        (Token::Format(format), _) => match format {
+            Format::Color(_) | Format::Font(_) | Format::ShadowColor(_) => {
+                output.write_str("</span>")?;
             }
-        };
-    }
+            Format::Obfuscated => output.write_str("</code>")?,
+            Format::Bold => output.write_str("</b>")?,
+            Format::Strikethrough => output.write_str("</s>")?,
+            Format::Underline => output.write_str("</u>")?,
+            Format::Italic => output.write_str("</i>")?,
+            Format::Reset => (),
+        },
+        (Token::Click(event), _) if href(event, dialect).is_some() => output.write_str("</a>")?,
+        (Token::Hover(HoverEvent::ShowText(text)), Some(id)) => {
+            write!(output, "<span role='tooltip' id='tooltip-{id}'>")?;
+
+            // The contents of the tooltip are written with their own formatting, but share the
+            // numbering of tooltips
+            let text = Tree::from(&TokenList::new_from_boxed(Box::new([]), text.clone()));
+            let mut inner = State {
+                tooltips: state.tooltips,
+                page: state.page,
+                shadows: state.shadows,
+                ..State::default()
+            };
+            handle_nodes(output, &mut inner, text.nodes(), dialect)?;
+            state.tooltips = inner.tooltips;
 
-    while let Some(format_token) = format_token_stack.pop() {
-        close_html!(
-            output, format_token;
-            Color => "</span>";
-            Obfuscated => "</code>",
-            Bold => "</b>",
-            Strikethrough => "</s>",
-            Underline => "</u>",
-            Italic => "</i>";
-        );
+            output.write_str("</span></span>")?;
+        }
+        _ => (),
     }
 
     Ok(())
 }
 
-/// With the given [`Metadata`], write some HTML boilerplate, inlcuding `"<head>....</head>"` to
-/// `output`.
+/// With the given [`Metadata`] and the options of `html`, write some HTML boilerplate, inlcuding
+/// `"<head>....</head>"` to `output`.
+///
+/// The `<head>` includes the [styles][`styles`] that `tokens` need.
 ///
 /// # Errors
 ///
 /// - [`std::io::Error`] if it cannot write into `output`
 pub fn start_document(
     output: &mut Utf8Writer<impl Write>,
+    html: &Html,
     metadata: &[Metadata],
+    tokens: &[Token],
 ) -> std::io::Result<()> {
-    // Should this really be assuming English and LTR text?
-    output
-        .write_str(r#"<!DOCTYPE html><html lang="en" dir="ltr"><head><meta charset="utf-8" />"#)?;
+    output.write_str(r#"<!DOCTYPE html><html lang=""#)?;
+    insert_string_as_html(output, &html.language, Dialect::Html)?;
+    write!(
+        output,
+        r#"" dir="{}"><head><meta charset="utf-8" />"#,
+        html.direction.as_str()
+    )?;
 
     for data in metadata {
         match data {
-            // These should be using [`write_string_as_html`]
-            Metadata::Title(t) => write!(output, r#"<title>{t}</title>"#)?,
-            Metadata::Author(a) => write!(output, r#"<meta name="author" content="{a}" />"#)?,
+            Metadata::Title(t) => {
+                output.write_str("<title>")?;
+                insert_string_as_html(output, t, Dialect::Html)?;
+                output.write_str("</title>")?;
+            }
+            Metadata::Author(a) => {
+                output.write_str(r#"<meta name="author" content=""#)?;
+                insert_string_as_html(output, a, Dialect::Html)?;
+                output.write_str(r#"" />"#)?;
+            }
+            Metadata::Custom { key, value } => {
+                output.write_str(r#"<meta name=""#)?;
+                insert_string_as_html(output, key, Dialect::Html)?;
+                output.write_str(r#"" content=""#)?;
+                insert_string_as_html(output, value, Dialect::Html)?;
+                output.write_str(r#"" />"#)?;
+            }
+            // Where a book was found has no meaning in the document itself
+            Metadata::Dimension(_)
+            | Metadata::Container(_)
+            | Metadata::Position { .. }
+            | Metadata::Owner(_)
+            | Metadata::Slot(_) => (),
         }
     }
 
+    styles(output, html, tokens)?;
+
     output.write_str(
         r#"<meta name="viewport" content="width=device-width, initial-scale=1.0" /></head>"#,
     )?;
 
     Ok(())
 }
+
+/// If `tokens` contain any tooltips or fonts, write a `<style>` with [`TOOLTIP_STYLE`] or
+/// [`FONT_STYLE`] respectively, followed by the stylesheet of `html`, if there is one.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn styles(
+    output: &mut Utf8Writer<impl Write>,
+    html: &Html,
+    tokens: &[Token],
+) -> std::io::Result<()> {
+    let tooltips = has_tooltips(tokens);
+    let fonts = has_fonts(tokens);
+
+    if tooltips || fonts || html.stylesheet.is_some() {
+        output.write_str("<style>")?;
+        if tooltips {
+            output.write_str(TOOLTIP_STYLE)?;
+        }
+        if fonts {
+            output.write_str(FONT_STYLE)?;
+        }
+        if let Some(stylesheet) = &html.stylesheet {
+            output.write_str(stylesheet)?;
+        }
+        output.write_str("</style>")?;
+    }
+
+    Ok(())
+}
+
+/// Open the element that holds the contents, with the element name, `id`, and `class` of `html`.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn open_contents(output: &mut Utf8Writer<impl Write>, html: &Html) -> std::io::Result<()> {
+    write!(output, "<{}", html.element.as_str())?;
+
+    for (name, value) in [("id", &html.id), ("class", &html.class)] {
+        if let Some(value) = value {
+            write!(output, r#" {name}=""#)?;
+            insert_string_as_html(output, value, Dialect::Html)?;
+            output.write_char('"')?;
+        }
+    }
+
+    // Most readable
+    output.write_str(" style=white-space:break-spaces>")?;
+
+    // Most accurate
+    // Does, however, still consume spaces that break, which Minecraft books do not
+    // output.write_str(" style=line-break:anywhere>");
+
+    Ok(())
+}
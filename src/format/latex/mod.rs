@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Exporting for LaTeX.
+//!
+//! See [`Latex`] for more details.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use crafty_novels::{
+//!    export::Latex,
+//!    syntax::{minecraft::Format, Token, TokenList},
+//!    Export,
+//! };
+//!
+//! let input_tokens = Box::new([
+//!     Token::ThematicBreak,
+//!     Token::Text("Italic:".into()),
+//!     Token::Format(Format::Italic),
+//!     Token::Space,
+//!     Token::Text("100%".into()),
+//!     Token::Space,
+//!     Token::Format(Format::Reset),
+//!     Token::Text("reset".into()),
+//!     Token::LineBreak,
+//! ]);
+//! let input = TokenList::new_from_boxed(Box::new([]), input_tokens);
+//!
+//! let expected = concat!(
+//!     "\\newpage\n",
+//!     "Italic:\\textit{ 100\\% }reset\n",
+//! );
+//!
+//! let exporter = Latex::new().fragment(true).color_definitions(false);
+//! assert_eq!(exporter.export_to_string(&input).as_ref(), expected);
+//! ```
+
+use crate::{resolve::Untranslated, syntax::TokenList, writer::Utf8Writer, Export};
+use std::io::Write;
+
+#[cfg(test)]
+mod test;
+mod token_handling;
+
+/// Exporting for LaTeX, either as a standalone document or as a fragment to include in a larger
+/// one.
+///
+/// # Format
+///
+/// As a standalone document, opens with a preamble that loads the required packages, defines the
+/// colors, and sets the title and author from the [metadata][`crate::syntax::Metadata`], which are
+/// written with `\maketitle`.
+///
+/// As a [fragment][`Latex::fragment`], only the color definitions and the contents are written.
+/// The document it is included in must load the `xcolor` and `ulem` packages (the latter with the
+/// `normalem` option, so that it leaves `\emph` alone).
+///
+/// Inside of the contents:
+///
+/// - Text is written with LaTeX's special characters escaped
+/// - Repeated spaces are written as non-breaking spaces (`~`), so that LaTeX does not collapse
+///   them
+/// - Line breaks are written as `\\`, paragraph breaks as empty lines, and thematic breaks as
+///   `\newpage`
+/// - Colored text is written with `\textcolor`, using an `xcolor` definition generated for each
+///   [`Color`][`crate::syntax::minecraft::Color`], ex. `minecraft-dark-blue`, or the exact value
+///   of an [`Rgb`][`crate::syntax::minecraft::Rgb`] color
+/// - Bold, italic, underline, and strikethrough text are written with `\textbf`, `\textit`,
+///   `\underline`, and `\sout`
+/// - Obfuscated text is written as plain text, since there is no way to scramble text on paper
+///
+/// Every group is closed at a reset format code and at the end of every line, and reopened on the
+/// next line if it is still active.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Latex {
+    fragment: bool,
+    color_definitions: bool,
+}
+
+impl Latex {
+    /// Create a new [`Latex`] exporter with the default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fragment: false,
+            color_definitions: true,
+        }
+    }
+
+    /// Whether to write only the contents, for inclusion in a larger document (`true`), or a
+    /// standalone document with a preamble (`false`, the default).
+    #[must_use]
+    pub const fn fragment(mut self, fragment: bool) -> Self {
+        self.fragment = fragment;
+        self
+    }
+
+    /// Whether a [fragment][`Self::fragment`] starts with the color definitions (`true`, the
+    /// default).
+    ///
+    /// Disable this when including several fragments in one document, and define the colors once
+    /// with [`Self::write_color_definitions`] instead. A standalone document always defines them.
+    #[must_use]
+    pub const fn color_definitions(mut self, color_definitions: bool) -> Self {
+        self.color_definitions = color_definitions;
+        self
+    }
+
+    /// Write the `xcolor` definitions used for colored text into a writer, for use in the preamble
+    /// of a document that includes [fragments][`Self::fragment`].
+    ///
+    /// Guaranteed to only write valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into `output`
+    pub fn write_color_definitions(output: &mut impl Write) -> std::io::Result<()> {
+        let mut writer = Utf8Writer::new(output);
+
+        token_handling::color_definitions(&mut writer)?;
+
+        writer.flush()
+    }
+}
+
+impl Default for Latex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Export for Latex {
+    /// Parse a given abstract syntax vector into LaTeX using these options, then output that into
+    /// a writer, like a [`std::fs::File`].
+    ///
+    /// Guaranteed to only write valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into `output`
+    fn export_to_writer(&self, tokens: &TokenList, output: &mut impl Write) -> std::io::Result<()> {
+        let tokens = &tokens.resolve(&Untranslated).resolve_styles();
+        let mut writer = Utf8Writer::new(output);
+
+        if !self.fragment {
+            token_handling::start_document(&mut writer, tokens.metadata_as_slice())?;
+        } else if self.color_definitions {
+            token_handling::color_definitions(&mut writer)?;
+            writer.write_char('\n')?;
+        }
+
+        let mut document = token_handling::Document::new(&mut writer);
+        for token in tokens.tokens_as_slice() {
+            document.token(token)?;
+        }
+        document.finish()?;
+
+        if !self.fragment {
+            token_handling::end_document(&mut writer)?;
+        }
+
+        writer.flush()
+    }
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for exporting to the [LaTeX][`super::Latex`] format.
+
+use super::Latex;
+use crate::{
+    import::Stendhal,
+    syntax::{minecraft::Color, Metadata, Token, TokenList},
+    Export, Tokenize,
+};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Insert a [`Token::Format`] with the given variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Insert a [`Token::Format`] with the given color.
+macro_rules! color {
+    ($color:expr) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::Color($color.into()))
+    };
+}
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+#[test]
+fn test_stendhal_document() -> Result {
+    let input = "title: Tales & Legends
+author: RemasteredArch
+pages:
+#- Some §cRED text
+Italic:§o text §rreset
+
+#- §l§nBold §mand§r plain";
+
+    let latex = Latex::export_token_vector_to_string(Stendhal::tokenize_string(input)?);
+
+    assert!(latex.starts_with("\\documentclass{article}\n"));
+    for expected in [
+        "\\usepackage{xcolor}\n",
+        "\\usepackage[normalem]{ulem}\n",
+        "\\definecolor{minecraft-red}{RGB}{255,85,85}\n",
+        "\\definecolor{minecraft-dark-blue}{RGB}{0,0,170}\n",
+        "\\title{Tales \\& Legends}\n\\author{RemasteredArch}\n\\date{}\n",
+    ] {
+        assert!(latex.contains(expected), "missing {expected:?}");
+    }
+
+    let body = concat!(
+        "\\begin{document}\n",
+        "\\maketitle\n",
+        "\n",
+        "\\newpage\n",
+        "Some \\textcolor{minecraft-red}{RED text}\\\\\n",
+        "Italic:\\textit{ text }reset\n",
+        "\n",
+        "\\newpage\n",
+        "\\textbf{\\underline{Bold \\sout{and}}} plain\n",
+        "\n",
+        "\\end{document}\n",
+    );
+    assert!(latex.ends_with(body), "{latex}");
+
+    Ok(())
+}
+
+#[test]
+fn test_fragment() {
+    let input = TokenList::new_from_boxed(
+        Box::new([Metadata::Title("Ignored".into())]),
+        Box::new([text!("Fragment"), Token::LineBreak]),
+    );
+
+    let latex = Latex::new().fragment(true).export_to_string(&input);
+    assert!(latex.starts_with("\\definecolor{minecraft-black}{RGB}{0,0,0}\n"));
+    assert!(latex.ends_with("}\n\nFragment\n"));
+    assert!(!latex.contains("Ignored"));
+    assert!(!latex.contains("\\begin{document}"));
+
+    let latex = Latex::new()
+        .fragment(true)
+        .color_definitions(false)
+        .export_to_string(&input);
+    assert_eq!(latex.as_ref(), "Fragment\n");
+
+    let mut definitions = vec![];
+    Latex::write_color_definitions(&mut definitions).expect("writing into a `Vec<u8>`");
+    assert_eq!(String::from_utf8_lossy(&definitions).lines().count(), 16);
+}
+
+#[test]
+fn test_escaping() {
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([text!(r"\{}$&#^_%~<>|"), Token::LineBreak, text!("[note]")]),
+    );
+
+    assert_eq!(
+        Latex::new()
+            .fragment(true)
+            .color_definitions(false)
+            .export_to_string(&input)
+            .as_ref(),
+        concat!(
+            r"\textbackslash{}\{\}\$\&\#\^{}\_\%\~{}\textless{}\textgreater{}\textbar{}\\",
+            "\n{[}note{]}\n",
+        )
+    );
+}
+
+#[test]
+fn test_formatting() {
+    use Token::{LineBreak, ParagraphBreak, Space};
+
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            format!(Obfuscated),
+            color!(Color::Gold),
+            text!("one"),
+            LineBreak,
+            // Formatting is reopened after the end of a line
+            text!("two"),
+            // A new color replaces the old one, which needs closing every group
+            color!(Color::Aqua),
+            Space,
+            Space,
+            text!("three"),
+            LineBreak,
+            // Empty lines still need something to break
+            LineBreak,
+            format!(Reset),
+            text!("four"),
+            LineBreak,
+            ParagraphBreak,
+            // Repeated paragraph breaks collapse into one
+            ParagraphBreak,
+            Space,
+            text!("five"),
+        ]),
+    );
+
+    assert_eq!(
+        Latex::new()
+            .fragment(true)
+            .color_definitions(false)
+            .export_to_string(&input)
+            .as_ref(),
+        concat!(
+            "\\textcolor{minecraft-gold}{one}\\\\\n",
+            "\\textcolor{minecraft-gold}{two}",
+            "\\textcolor{minecraft-aqua}{ ~three}\\\\\n",
+            "\\mbox{}\\\\\n",
+            "four\n",
+            "\n",
+            "~five\n",
+        )
+    );
+}
@@ -0,0 +1,340 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The actual, under the hood, token-by-token exporting for the [LaTeX][`super::Latex`] format.
+
+use crate::{
+    syntax::{
+        minecraft::{Color, ColorValue, Format, TextColor},
+        Metadata, Token,
+    },
+    writer::Utf8Writer,
+};
+use std::io::{Result, Write};
+
+/// The packages that the exported contents rely on.
+const PACKAGES: &str = concat!(
+    "\\usepackage[T1]{fontenc}\n",
+    "\\usepackage[utf8]{inputenc}\n",
+    "\\usepackage{xcolor}\n",
+    "\\usepackage[normalem]{ulem}\n",
+    "\\usepackage{parskip}\n",
+);
+
+/// Write the preamble of a standalone document, up to and including `\begin{document}` and the
+/// title.
+///
+/// `\maketitle` requires a title, so neither it nor the author are written for a work without one.
+/// Other [`Metadata`] has no meaning in the document itself, and is ignored.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn start_document(output: &mut Utf8Writer<impl Write>, metadata: &[Metadata]) -> Result<()> {
+    output.write_str("\\documentclass{article}\n\n")?;
+    output.write_str(PACKAGES)?;
+    output.write_char('\n')?;
+    color_definitions(output)?;
+    output.write_char('\n')?;
+
+    let title = metadata.iter().find_map(|data| match data {
+        Metadata::Title(t) => Some(t),
+        _ => None,
+    });
+
+    if let Some(title) = title {
+        writeln!(output, "\\title{{{}}}", escape(title))?;
+        for data in metadata {
+            if let Metadata::Author(a) = data {
+                writeln!(output, "\\author{{{}}}", escape(a))?;
+            }
+        }
+        output.write_str("\\date{}\n\n")?;
+    }
+
+    output.write_str("\\begin{document}\n")?;
+
+    if title.is_some() {
+        output.write_str("\\maketitle\n")?;
+    }
+
+    output.write_char('\n')
+}
+
+/// Write the end of a standalone document.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn end_document(output: &mut Utf8Writer<impl Write>) -> Result<()> {
+    output.write_str("\n\\end{document}\n")
+}
+
+/// Write an `xcolor` definition for every [`Color`], named after [`color_name`].
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn color_definitions(output: &mut Utf8Writer<impl Write>) -> Result<()> {
+    for color in Color::ALL {
+        let (red, green, blue) = ColorValue::from(color).fg().as_tuple();
+        writeln!(
+            output,
+            "\\definecolor{{{}}}{{RGB}}{{{red},{green},{blue}}}",
+            color_name(color)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the name of the `xcolor` definition for a [`Color`], ex. `"minecraft-dark-blue"`.
+pub fn color_name(color: Color) -> String {
+    format!(
+        "minecraft-{}",
+        ColorValue::from(color).name().replace('_', "-")
+    )
+}
+
+/// Returns `text` with every character that has a special meaning in LaTeX escaped.
+///
+/// Square brackets are wrapped in a group, so that they can never be read as the optional argument
+/// of the command before them (ex. a line break, `\\`).
+pub fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '\\' => output.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                output.push('\\');
+                output.push(char);
+            }
+            '^' => output.push_str("\\^{}"),
+            '~' => output.push_str("\\~{}"),
+            '<' => output.push_str("\\textless{}"),
+            '>' => output.push_str("\\textgreater{}"),
+            '|' => output.push_str("\\textbar{}"),
+            '[' => output.push_str("{[}"),
+            ']' => output.push_str("{]}"),
+            _ => output.push(char),
+        }
+    }
+
+    output
+}
+
+/// Returns the command that opens a group for a [`Format`], ex. `"\textbf{"` for bold.
+///
+/// Returns [`None`] for formats that cannot be written in LaTeX.
+fn command(format: Format) -> Option<String> {
+    Some(match format {
+        Format::Color(TextColor::Legacy(color)) => {
+            format!("\\textcolor{{{}}}{{", color_name(color))
+        }
+        Format::Color(TextColor::Rgb(rgb)) => {
+            let (red, green, blue) = rgb.as_tuple();
+            format!("\\textcolor[RGB]{{{red},{green},{blue}}}{{")
+        }
+        Format::Bold => "\\textbf{".into(),
+        Format::Italic => "\\textit{".into(),
+        Format::Underline => "\\underline{".into(),
+        Format::Strikethrough => "\\sout{".into(),
+        Format::Obfuscated | Format::Font(_) | Format::ShadowColor(_) | Format::Reset => {
+            return None
+        }
+    })
+}
+
+/// Writes the body of a work, keeping track of which formatting groups are open in the output.
+///
+/// Groups are closed at the end of every line and reopened on the next, because commands like
+/// `\sout` cannot span line or paragraph breaks.
+pub struct Document<'w, W: Write> {
+    output: &'w mut Utf8Writer<W>,
+    /// The formats that apply to the next text, in the order they were applied.
+    active: Vec<Format>,
+    /// The formats that have an open group in the output.
+    open: Vec<Format>,
+    /// Whether the last line ended, without that being written yet.
+    ///
+    /// Line breaks are written lazily, because `\\` cannot end a paragraph.
+    line_break: bool,
+    /// Whether nothing has been written into the current paragraph.
+    paragraph_empty: bool,
+    /// Whether the last thing written was a space or the start of a line, in which case another
+    /// space would be ignored by LaTeX.
+    space_before: bool,
+}
+
+impl<'w, W: Write> Document<'w, W> {
+    /// Create a new [`Document`] that writes into `output`.
+    pub const fn new(output: &'w mut Utf8Writer<W>) -> Self {
+        Self {
+            output,
+            active: vec![],
+            open: vec![],
+            line_break: false,
+            paragraph_empty: true,
+            space_before: true,
+        }
+    }
+
+    /// Write a token into the output.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn token(&mut self, token: &Token) -> Result<()> {
+        match token {
+            Token::Text(text) => {
+                self.start_content()?;
+                self.output.write_str(escape(text))?;
+                self.space_before = false;
+            }
+            Token::Space => {
+                self.start_content()?;
+                // A non-breaking space is never collapsed into the one before it
+                self.output
+                    .write_char(if self.space_before { '~' } else { ' ' })?;
+                self.space_before = true;
+            }
+            Token::Format(Format::Reset) => self.active.clear(),
+            Token::Format(format) => {
+                // Only the innermost color would show, so a new color replaces the last
+                let replaced = self.active.iter().position(|active| {
+                    matches!((active, format), (Format::Color(_), Format::Color(_)))
+                });
+
+                match replaced {
+                    Some(index) => self.active[index] = *format,
+                    None if !self.active.contains(format) => self.active.push(*format),
+                    None => (),
+                }
+            }
+            // Paper has no way to click or hover over text, and content is filled in before
+            // exporting
+            Token::Click(_) | Token::Hover(_) | Token::Insertion(_) | Token::Content(_) => (),
+            Token::LineBreak => {
+                self.close()?;
+
+                // An empty line needs something on it to be broken
+                if self.line_break || self.paragraph_empty {
+                    self.end_line()?;
+                    self.output.write_str("\\mbox{}")?;
+                    self.paragraph_empty = false;
+                }
+
+                self.line_break = true;
+            }
+            Token::ParagraphBreak => {
+                self.close()?;
+
+                if !self.paragraph_empty {
+                    self.output.write_str("\n\n")?;
+                }
+
+                self.new_paragraph();
+            }
+            Token::ThematicBreak => {
+                self.close()?;
+
+                if !self.paragraph_empty {
+                    self.output.write_char('\n')?;
+                }
+                self.output.write_str("\\newpage\n")?;
+
+                self.new_paragraph();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Close any groups left open, and end the last line.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn finish(mut self) -> Result<()> {
+        self.close()?;
+
+        if !self.paragraph_empty {
+            self.output.write_char('\n')?;
+        }
+
+        self.output.flush()
+    }
+
+    /// Write any pending line break, then bring the open groups in line with the active formats.
+    fn start_content(&mut self) -> Result<()> {
+        if self.line_break {
+            self.end_line()?;
+        }
+
+        self.paragraph_empty = false;
+
+        if self.open == self.active {
+            return Ok(());
+        }
+
+        // Groups can be nested inside of what is open already, but removing one requires closing
+        // all of them
+        let start = if self.active.starts_with(&self.open) {
+            self.open.len()
+        } else {
+            self.close()?;
+            0
+        };
+
+        for &format in &self.active[start..] {
+            if let Some(command) = command(format) {
+                self.output.write_str(command)?;
+            }
+        }
+
+        self.open.clone_from(&self.active);
+
+        Ok(())
+    }
+
+    /// Write the pending line break.
+    fn end_line(&mut self) -> Result<()> {
+        self.output.write_str("\\\\\n")?;
+        self.line_break = false;
+        self.space_before = true;
+
+        Ok(())
+    }
+
+    /// Forget about the current paragraph, because a new one has started.
+    const fn new_paragraph(&mut self) {
+        self.line_break = false;
+        self.paragraph_empty = true;
+        self.space_before = true;
+    }
+
+    /// Close every open group.
+    fn close(&mut self) -> Result<()> {
+        for _ in self.open.iter().filter_map(|&format| command(format)) {
+            self.output.write_char('}')?;
+        }
+        self.open.clear();
+
+        Ok(())
+    }
+}
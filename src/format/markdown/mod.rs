@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Exporting for Markdown.
+//!
+//! See [`Markdown`] for more details.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use crafty_novels::{
+//!    export::Markdown,
+//!    syntax::{minecraft::Format, Metadata, Token, TokenList},
+//!    Export,
+//! };
+//!
+//! let input_metadata = Box::new([
+//!     Metadata::Title("crafty_novels".into()),
+//!     Metadata::Author("RemasteredArch".into()),
+//! ]);
+//! let input_tokens = Box::new([
+//!     Token::ThematicBreak,
+//!     Token::Text("Italic:".into()),
+//!     Token::Format(Format::Italic),
+//!     Token::Space,
+//!     Token::Text("text".into()),
+//!     Token::Space,
+//!     Token::Format(Format::Reset),
+//!     Token::Text("reset".into()),
+//!     Token::LineBreak,
+//! ]);
+//! let input = TokenList::new_from_boxed(input_metadata, input_tokens);
+//!
+//! let expected = concat!(
+//!     "# crafty\\_novels\n\n",
+//!     "*RemasteredArch*\n\n",
+//!     "---\n\n",
+//!     "Italic: *text* reset\n",
+//! );
+//!
+//! assert_eq!(
+//!     Markdown::export_token_vector_to_string(input).as_ref(),
+//!     expected
+//! );
+//! ```
+
+use crate::{resolve::Untranslated, syntax::TokenList, writer::Utf8Writer, Export};
+use std::io::Write;
+
+#[cfg(test)]
+mod test;
+mod token_handling;
+
+/// Exporting for Markdown, as described by [CommonMark] and [GitHub Flavored Markdown].
+///
+/// [CommonMark]: https://commonmark.org/
+/// [GitHub Flavored Markdown]: https://github.github.com/gfm/
+///
+/// # Format
+///
+/// Opens with the [metadata][`crate::syntax::Metadata`]:
+///
+/// ```markdown
+/// # {title}
+///
+/// *{author}*
+///
+/// ```
+///
+/// Inside of the contents:
+///
+/// - Plain text is written with Markdown's special characters escaped
+///     - Ex. `'*'` -> `"\*"`
+/// - Spaces are written as plain spaces, except where Markdown would ignore them (at the start of
+///   a line, or after another space), where they are written as `&nbsp;`
+/// - Line breaks are represented by hard breaks (`"\"` at the end of a line)
+/// - Paragraph breaks are represented by blank lines
+/// - Thematic breaks are represented by `---`
+///     - Except at the very start of the document, where it would be read as YAML frontmatter
+/// - Bold text is represented as `**text**`
+/// - Italic text is represented as `*text*`
+/// - Strikethrough text is represented as `~~text~~`
+/// - Formats without a Markdown equivalent fall back to inline HTML, unless
+///   [`Markdown::inline_html`] is disabled, in which case they are dropped:
+///     - Colored text is represented as `<span style="color:{color}">`
+///     - Underline text is represented as `<u>`
+///     - Obfuscated text is represented as `<code>`
+///
+/// Formatting is closed before (and reopened after) spaces and line endings, because Markdown only
+/// recognizes emphasis that sits directly against text.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Markdown {
+    inline_html: bool,
+}
+
+impl Markdown {
+    /// Create a new [`Markdown`] exporter with the default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { inline_html: true }
+    }
+
+    /// Whether formats without a Markdown equivalent (colors, underline, and obfuscation) should
+    /// be written as inline HTML (`true`, the default), or dropped (`false`).
+    ///
+    /// Dropping them is useful for renderers that do not allow inline HTML.
+    #[must_use]
+    pub const fn inline_html(mut self, inline_html: bool) -> Self {
+        self.inline_html = inline_html;
+        self
+    }
+}
+
+impl Default for Markdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Export for Markdown {
+    /// Parse a given abstract syntax vector into Markdown using these options, then output that
+    /// into a writer, like a [`std::fs::File`].
+    ///
+    /// Guaranteed to only write valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into `output`
+    fn export_to_writer(&self, tokens: &TokenList, output: &mut impl Write) -> std::io::Result<()> {
+        let tokens = &tokens.resolve(&Untranslated).resolve_styles();
+        let mut writer = Utf8Writer::new(output);
+        let mut document = token_handling::Document::new(&mut writer, self.inline_html);
+
+        document.start(tokens.metadata_as_slice())?;
+
+        let mut iter = tokens.tokens_as_slice().iter().peekable();
+        while let Some(token) = iter.next() {
+            document.token(token, iter.peek().copied())?;
+        }
+
+        document.finish()
+    }
+}
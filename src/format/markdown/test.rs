@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for exporting to the [Markdown][`super::Markdown`] format.
+
+use super::Markdown;
+use crate::{
+    import::Stendhal,
+    syntax::{Token, TokenList},
+    Export, Tokenize,
+};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Insert a [`Token::Format`] with the given variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Insert a [`Token::Format`] with the given color.
+macro_rules! color {
+    ($color:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::Color(
+            crate::syntax::minecraft::Color::$color.into(),
+        ))
+    };
+}
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+/// Build a [`TokenList`] without metadata out of tokens.
+fn tokens(tokens: impl Into<Box<[Token]>>) -> TokenList {
+    TokenList::new_from_boxed(Box::new([]), tokens.into())
+}
+
+#[test]
+fn test_stendhal_document() -> Result {
+    let input = "title: crafty_novels
+author: RemasteredArch
+pages:
+#- This is the start of the page
+First line
+#- New Page
+Not a #- new page
+ #- also not a new page
+
+
+
+Lots of paragraph breaks
+Some §cRED line breaks
+Some §l BOLD line breaks (2)
+Italic:§o text §rreset
+   lots    of   spaces     
+just one space 
+<div>some HTML</div>
+&gt; <== not an <
+& ampersands &
+last line";
+
+    let expected = r#"# crafty\_novels
+
+*RemasteredArch*
+
+---
+
+This is the start of the page\
+First line
+
+---
+
+New Page\
+Not a #- new page\
+&nbsp;#- also not a new page
+
+Lots of paragraph breaks\
+Some <span style="color:#FF5555">RED line breaks</span>\
+Some &nbsp;**BOLD line breaks (2)**\
+Italic: *text* reset\
+&nbsp;&nbsp;&nbsp;lots &nbsp;&nbsp;&nbsp;of &nbsp;&nbsp;spaces\
+just one space\
+\<div\>some HTML\</div\>\
+\&gt; \<== not an \<\
+\& ampersands \&\
+last line
+"#;
+
+    let markdown = Markdown::export_token_vector_to_string(Stendhal::tokenize_string(input)?);
+    assert_eq!(markdown.as_ref(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_escaping() {
+    use Token::{LineBreak, Space};
+
+    /// Compare the Markdown exported from `$tokens` with `$expects`.
+    macro_rules! test {
+        ( $( $tokens:expr => $expects:expr );+ ; ) => {
+            $(
+                assert_eq!(
+                    Markdown::export_token_vector_to_string(tokens($tokens)).as_ref(),
+                    $expects
+                );
+            )+
+        };
+    }
+
+    test!(
+        [text!("#"), Space, text!("heading"), LineBreak] => "\\# heading\n";
+        [text!("-"), Space, text!("list"), LineBreak] => "\\- list\n";
+        [text!("12."), Space, text!("list"), LineBreak] => "12\\. list\n";
+        [text!("1)"), Space, text!("list"), LineBreak] => "1\\) list\n";
+        [text!("a"), Space, text!("#1."), LineBreak] => "a #1.\n";
+        [text!("*a*"), Space, text!("_b_"), Space, text!("`c`"), LineBreak] =>
+            "\\*a\\* \\_b\\_ \\`c\\`\n";
+        [text!("[link](url)"), Space, text!("~~no~~"), Space, text!("a|b"), LineBreak] =>
+            "\\[link\\](url) \\~\\~no\\~\\~ a\\|b\n";
+        [text!("back\\slash"), LineBreak] => "back\\\\slash\n";
+    );
+}
+
+#[test]
+fn test_formatting() {
+    use Token::{LineBreak, ParagraphBreak, Space, ThematicBreak};
+
+    let input = tokens([
+        ThematicBreak,
+        format!(Bold),
+        text!("bold"),
+        Space,
+        format!(Italic),
+        text!("both"),
+        format!(Reset),
+        Space,
+        format!(Strikethrough),
+        Space,
+        text!("struck"),
+        Space,
+        LineBreak,
+        text!("still"),
+        Space,
+        text!("struck"),
+        LineBreak,
+        ParagraphBreak,
+        format!(Reset),
+        color!(LightPurple),
+        format!(Underline),
+        format!(Obfuscated),
+        text!("fancy"),
+        format!(Reset),
+        LineBreak,
+        ThematicBreak,
+        ThematicBreak,
+        text!("last"),
+    ]);
+
+    // The thematic break at the start is left out, and formatting is closed at line ends
+    assert_eq!(
+        Markdown::new().export_to_string(&input).as_ref(),
+        concat!(
+            "**bold *both*** &nbsp;~~struck~~\\\n",
+            "~~still struck~~\n",
+            "\n",
+            r#"<span style="color:#FF55FF"><u><code>fancy</code></u></span>"#,
+            "\n\n---\n\n---\n\nlast\n",
+        )
+    );
+
+    // Without inline HTML, colors, underline, and obfuscation are dropped
+    assert_eq!(
+        Markdown::new()
+            .inline_html(false)
+            .export_to_string(&input)
+            .as_ref(),
+        concat!(
+            "**bold *both*** &nbsp;~~struck~~\\\n",
+            "~~still struck~~\n",
+            "\n",
+            "fancy\n",
+            "\n---\n\n---\n\nlast\n",
+        )
+    );
+}
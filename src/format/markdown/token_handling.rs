@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The actual, under the hood, token-by-token exporting for the [Markdown][`super::Markdown`]
+//! format.
+
+use crate::{
+    syntax::{minecraft::Format, Metadata, Token},
+    writer::Utf8Writer,
+};
+use std::io::{Result, Write};
+
+/// Characters that are escaped wherever they appear in text.
+const ESCAPED: [char; 11] = ['\\', '`', '*', '_', '[', ']', '<', '>', '&', '~', '|'];
+
+/// Characters that are escaped only at the start of a line, where they would start a block (ex.
+/// a heading or a list).
+const ESCAPED_AT_LINE_START: [char; 4] = ['#', '-', '+', '='];
+
+/// Inserts a string of arbitrary text into Markdown output, escaping any characters that Markdown
+/// would otherwise interpret.
+///
+/// If `line_start` is true, characters that only have meaning at the start of a line are escaped
+/// too, including the `'.'` or `')'` of what would be an ordered list item (ex. `"1."`).
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+fn insert_string_as_markdown(
+    output: &mut Utf8Writer<impl Write>,
+    input: &str,
+    line_start: bool,
+) -> Result<()> {
+    let digits = input.chars().take_while(char::is_ascii_digit).count();
+
+    for (index, char) in input.chars().enumerate() {
+        let escape = ESCAPED.contains(&char)
+            || (line_start && index == 0 && ESCAPED_AT_LINE_START.contains(&char))
+            || (line_start && index == digits && digits > 0 && matches!(char, '.' | ')'));
+
+        if escape {
+            output.write_char('\\')?;
+        }
+        output.write_char(char)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the Markdown (or inline HTML) that opens and closes a [`Format`].
+fn delimiters(format: Format) -> (String, &'static str) {
+    match format {
+        Format::Color(c) => (format!(r#"<span style="color:{c}">"#), "</span>"),
+        Format::Obfuscated => ("<code>".into(), "</code>"),
+        Format::Bold => ("**".into(), "**"),
+        Format::Strikethrough => ("~~".into(), "~~"),
+        Format::Underline => ("<u>".into(), "</u>"),
+        Format::Italic => ("*".into(), "*"),
+        Format::Font(_) | Format::ShadowColor(_) | Format::Reset => (String::new(), ""),
+    }
+}
+
+/// Whether a [`Format`] can only be represented with inline HTML.
+const fn needs_html(format: Format) -> bool {
+    matches!(
+        format,
+        Format::Color(_) | Format::Obfuscated | Format::Underline
+    )
+}
+
+/// Writes the body of a work, keeping track of which formats are open.
+///
+/// Markdown only recognizes emphasis that sits directly against text (`"*text*"`, not
+/// `"* text *"`), so spaces are held back until the next text, and formatting is opened and
+/// closed around them. Formatting is also closed at the end of every line, and reopened on the
+/// next line, so that it never spans multiple blocks.
+#[allow(clippy::struct_excessive_bools)] // Independent pieces of state, not a set of options
+pub struct Document<'w, W: Write> {
+    output: &'w mut Utf8Writer<W>,
+    /// Whether to write the formats that need inline HTML, or to drop them.
+    inline_html: bool,
+    /// The formats that apply to the next text, in the order they were applied.
+    active: Vec<Format>,
+    /// The formats that have been opened in the output and not yet closed.
+    open: Vec<Format>,
+    /// The number of spaces that have not been written yet.
+    spaces: usize,
+    /// Whether anything has been written onto the current line.
+    line_open: bool,
+    /// Whether the last line written was blank, or nothing has been written yet.
+    blank: bool,
+    /// Whether nothing has been written yet.
+    empty: bool,
+}
+
+impl<'w, W: Write> Document<'w, W> {
+    /// Create a new [`Document`] that writes into `output`.
+    ///
+    /// `inline_html` decides whether formats without a Markdown equivalent are written as inline
+    /// HTML or dropped.
+    pub const fn new(output: &'w mut Utf8Writer<W>, inline_html: bool) -> Self {
+        Self {
+            output,
+            inline_html,
+            active: vec![],
+            open: vec![],
+            spaces: 0,
+            line_open: false,
+            blank: true,
+            empty: true,
+        }
+    }
+
+    /// Write the title and author of a work as a heading and an emphasized line.
+    ///
+    /// Other [`Metadata`] has no meaning in the document itself, and is ignored.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn start(&mut self, metadata: &[Metadata]) -> Result<()> {
+        for data in metadata {
+            match data {
+                Metadata::Title(t) => {
+                    self.output.write_str("# ")?;
+                    insert_string_as_markdown(self.output, t, true)?;
+                    self.output.write_str("\n\n")?;
+                }
+                Metadata::Author(a) if !a.trim().is_empty() => {
+                    self.output.write_char('*')?;
+                    insert_string_as_markdown(self.output, a.trim(), true)?;
+                    self.output.write_str("*\n\n")?;
+                }
+                _ => continue,
+            }
+
+            self.empty = false;
+        }
+
+        Ok(())
+    }
+
+    /// Write a token into the output.
+    ///
+    /// `next` is the token following `token`, if any, which is used to decide whether a
+    /// [`Token::LineBreak`] is a hard break or the end of a paragraph.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn token(&mut self, token: &Token, next: Option<&Token>) -> Result<()> {
+        match token {
+            Token::Text(text) => self.text(text)?,
+            Token::Space => self.spaces += 1,
+            Token::Format(Format::Reset) => self.active.clear(),
+            // Markdown has no way to change the font or shadow of text, events only mean
+            // something in the game, and content is filled in before exporting
+            Token::Format(Format::Font(_) | Format::ShadowColor(_))
+            | Token::Click(_)
+            | Token::Hover(_)
+            | Token::Insertion(_)
+            | Token::Content(_) => (),
+            Token::Format(format) => {
+                if (self.inline_html || !needs_html(*format)) && !self.active.contains(format) {
+                    self.active.push(*format);
+                }
+            }
+            Token::LineBreak => {
+                // A hard break cannot end a paragraph, so only use one if the paragraph continues
+                let continues = matches!(
+                    next,
+                    Some(
+                        Token::Text(_)
+                            | Token::Space
+                            | Token::Format(_)
+                            | Token::Click(_)
+                            | Token::Hover(_)
+                            | Token::Insertion(_)
+                            | Token::Content(_)
+                            | Token::LineBreak
+                    )
+                );
+
+                if self.line_open {
+                    self.end_line(continues)?;
+                } else if continues && !self.blank {
+                    self.output.write_str("\\\n")?;
+                }
+            }
+            Token::ParagraphBreak => self.blank_line()?,
+            // Many Markdown renderers read a document starting with `---` as YAML frontmatter, and
+            // a page break at the very start of a document does not separate anything anyway
+            Token::ThematicBreak if self.empty => (),
+            Token::ThematicBreak => {
+                self.blank_line()?;
+                self.output.write_str("---\n\n")?;
+                self.empty = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finish the last line, if it has not been finished already.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn finish(mut self) -> Result<()> {
+        if self.line_open {
+            self.end_line(false)?;
+        }
+
+        self.output.flush()
+    }
+
+    /// Write text, first bringing the open formats in line with the active formats.
+    fn text(&mut self, text: &str) -> Result<()> {
+        let common = self
+            .open
+            .iter()
+            .zip(&self.active)
+            .take_while(|(open, active)| open == active)
+            .count();
+
+        self.close_formats(common)?;
+        self.write_spaces()?;
+
+        for &format in &self.active[common..] {
+            self.output.write_str(delimiters(format).0)?;
+        }
+        self.open.clone_from(&self.active);
+
+        insert_string_as_markdown(self.output, text, !self.line_open)?;
+        self.line_open = true;
+        self.empty = false;
+
+        Ok(())
+    }
+
+    /// Write the spaces that have been held back.
+    ///
+    /// Markdown ignores spaces at the start of a line and collapses consecutive spaces, so those
+    /// are written as non-breaking spaces to keep them.
+    fn write_spaces(&mut self) -> Result<()> {
+        for index in 0..self.spaces {
+            if index == 0 && self.line_open {
+                self.output.write_char(' ')?;
+            } else {
+                self.output.write_str("&nbsp;")?;
+            }
+        }
+
+        if self.spaces > 0 {
+            self.line_open = true;
+        }
+        self.spaces = 0;
+
+        Ok(())
+    }
+
+    /// Close open formats, in reverse order, until only `keep` are left open.
+    fn close_formats(&mut self, keep: usize) -> Result<()> {
+        while self.open.len() > keep {
+            if let Some(format) = self.open.pop() {
+                self.output.write_str(delimiters(format).1)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// End the current line, as a hard break if `hard` is true.
+    ///
+    /// Spaces at the end of a line are not visible in Markdown, so they are dropped.
+    fn end_line(&mut self, hard: bool) -> Result<()> {
+        self.close_formats(0)?;
+        self.spaces = 0;
+
+        self.output.write_str(if hard { "\\\n" } else { "\n" })?;
+        self.line_open = false;
+        self.blank = false;
+
+        Ok(())
+    }
+
+    /// End the current line and paragraph with a blank line, if one is not there already.
+    fn blank_line(&mut self) -> Result<()> {
+        if self.line_open {
+            self.end_line(false)?;
+        }
+        self.spaces = 0;
+
+        if !self.blank {
+            self.output.write_char('\n')?;
+            self.blank = true;
+        }
+
+        Ok(())
+    }
+}
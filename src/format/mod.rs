@@ -20,5 +20,15 @@
 //! This module should never be public. Instead, these modules' implementations should be
 //! re-exported under [`crate::import`] and [`crate::export`].
 
+pub mod ansi;
+pub mod anvil;
+pub mod epub;
 pub mod html;
+pub mod latex;
+pub mod markdown;
+pub mod nbt;
+pub mod plain_text;
+pub mod player_data;
+pub mod snbt;
 pub mod stendhal;
+pub mod text_component;
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading NBT in its binary form, as stored in Minecraft: Java Edition's save files.
+//!
+//! See [`read`].
+
+use super::{NbtError, Tag};
+use std::io::Read;
+
+/// How deeply lists and compounds can be nested, matching Minecraft: Java Edition's own limit.
+pub const MAX_DEPTH: usize = 512;
+
+/// Read a named root tag from uncompressed binary NBT, returning it without its name.
+///
+/// Any compression (ex. gzip for player data, zlib for region chunks) must be removed first.
+///
+/// # Errors
+///
+/// - [`NbtError::RootNotCompound`] if the root tag is not a compound
+/// - [`NbtError::UnknownTagType`] if a tag has an unknown type
+/// - [`NbtError::NegativeLength`] if a string, array, or list has a negative length
+/// - [`NbtError::TooDeep`] if lists and compounds are nested deeper than [`MAX_DEPTH`]
+/// - [`NbtError::InvalidString`] if a string is not valid Modified UTF-8
+/// - [`NbtError::Io`] if `input` cannot be read, including if it ends early
+pub fn read(mut input: impl Read) -> Result<Tag, NbtError> {
+    let mut reader = Reader {
+        input: &mut input,
+        depth: 0,
+    };
+
+    let tag_type = reader.u8()?;
+    if tag_type != 10 {
+        return Err(NbtError::RootNotCompound(tag_type));
+    }
+
+    reader.string()?;
+    reader.payload(tag_type)
+}
+
+/// Reads big-endian binary NBT, keeping track of how deeply it is nested.
+struct Reader<'r, R: Read> {
+    input: &'r mut R,
+    depth: usize,
+}
+
+/// Generates methods to read a big-endian number from the input.
+macro_rules! read_number {
+    ( $( $name:ident => $type:ty ),+ $(,)? ) => {
+        $(
+            fn $name(&mut self) -> Result<$type, NbtError> {
+                let mut bytes = [0; std::mem::size_of::<$type>()];
+                self.input.read_exact(&mut bytes)?;
+
+                Ok(<$type>::from_be_bytes(bytes))
+            }
+        )+
+    };
+}
+
+impl<R: Read> Reader<'_, R> {
+    read_number!(
+        u8 => u8,
+        i8 => i8,
+        u16 => u16,
+        i16 => i16,
+        i32 => i32,
+        i64 => i64,
+        f32 => f32,
+        f64 => f64,
+    );
+
+    /// Read the payload of a tag of the given type.
+    fn payload(&mut self, tag_type: u8) -> Result<Tag, NbtError> {
+        Ok(match tag_type {
+            1 => Tag::Byte(self.i8()?),
+            2 => Tag::Short(self.i16()?),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(self.i64()?),
+            5 => Tag::Float(self.f32()?),
+            6 => Tag::Double(self.f64()?),
+            7 => Tag::ByteArray(self.array(Self::i8)?),
+            8 => Tag::String(self.string()?),
+            9 => Tag::List(self.list()?),
+            10 => self.compound()?,
+            11 => Tag::IntArray(self.array(Self::i32)?),
+            12 => Tag::LongArray(self.array(Self::i64)?),
+            _ => return Err(NbtError::UnknownTagType(tag_type)),
+        })
+    }
+
+    /// Read a length-prefixed array, reading each element with `element`.
+    fn array<T>(
+        &mut self,
+        element: impl Fn(&mut Self) -> Result<T, NbtError>,
+    ) -> Result<Box<[T]>, NbtError> {
+        let length = self.length()?;
+
+        // Don't trust the length for the allocation, it could be arbitrarily large
+        let mut array = Vec::with_capacity(length.min(4096));
+        for _ in 0..length {
+            array.push(element(self)?);
+        }
+
+        Ok(array.into())
+    }
+
+    /// Read a list, whose elements are all the same type.
+    fn list(&mut self) -> Result<Box<[Tag]>, NbtError> {
+        let tag_type = self.u8()?;
+
+        self.nest(|reader| reader.array(|reader| reader.payload(tag_type)))
+    }
+
+    /// Read a compound, which is a sequence of named tags ended by a tag of type `0`.
+    fn compound(&mut self) -> Result<Tag, NbtError> {
+        self.nest(|reader| {
+            let mut compound = vec![];
+
+            loop {
+                let tag_type = reader.u8()?;
+                if tag_type == 0 {
+                    return Ok(Tag::Compound(compound.into()));
+                }
+
+                let name = reader.string()?;
+                compound.push((name, reader.payload(tag_type)?));
+            }
+        })
+    }
+
+    /// Run `read` one level deeper, returning an error if that is too deep.
+    fn nest<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, NbtError>,
+    ) -> Result<T, NbtError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(NbtError::TooDeep);
+        }
+
+        self.depth += 1;
+        let result = read(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    /// Read the length of an array or list.
+    fn length(&mut self) -> Result<usize, NbtError> {
+        let length = self.i32()?;
+
+        usize::try_from(length).map_err(|_| NbtError::NegativeLength(length))
+    }
+
+    /// Read a string, prefixed by its length in bytes.
+    fn string(&mut self) -> Result<Box<str>, NbtError> {
+        let mut bytes = vec![0; usize::from(self.u16()?)];
+        self.input.read_exact(&mut bytes)?;
+
+        modified_utf8(bytes)
+    }
+}
+
+/// Decode Java's Modified UTF-8, which differs from UTF-8 by encoding `'\0'` as two bytes and
+/// characters outside of the Basic Multilingual Plane as two encoded UTF-16 surrogates.
+///
+/// # Errors
+///
+/// - [`NbtError::InvalidString`] if `bytes` is not valid Modified UTF-8
+fn modified_utf8(bytes: Vec<u8>) -> Result<Box<str>, NbtError> {
+    // Most strings are also valid UTF-8, so try that first
+    let bytes = match String::from_utf8(bytes) {
+        Ok(string) => return Ok(string.into()),
+        Err(error) => error.into_bytes(),
+    };
+
+    let continuation = |index: usize| -> Result<u16, NbtError> {
+        match bytes.get(index) {
+            Some(&byte) if byte & 0b1100_0000 == 0b1000_0000 => Ok(u16::from(byte & 0b0011_1111)),
+            _ => Err(NbtError::InvalidString),
+        }
+    };
+
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while let Some(&byte) = bytes.get(index) {
+        let byte = u16::from(byte);
+
+        if byte & 0b1000_0000 == 0 {
+            units.push(byte);
+            index += 1;
+        } else if byte & 0b1110_0000 == 0b1100_0000 {
+            units.push((byte & 0b0001_1111) << 6 | continuation(index + 1)?);
+            index += 2;
+        } else if byte & 0b1111_0000 == 0b1110_0000 {
+            units.push(
+                (byte & 0b0000_1111) << 12
+                    | continuation(index + 1)? << 6
+                    | continuation(index + 2)?,
+            );
+            index += 3;
+        } else {
+            return Err(NbtError::InvalidString);
+        }
+    }
+
+    String::from_utf16(&units)
+        .map(String::into_boxed_str)
+        .map_err(|_| NbtError::InvalidString)
+}
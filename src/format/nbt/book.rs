@@ -0,0 +1,366 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading written books and writable books out of [NBT][`super::Tag`] item data.
+//!
+//! Understands both the legacy item format, where the book is stored in the `tag` compound of the
+//! item stack, and the data component format (1.20.5+), where it is stored in the
+//! `minecraft:written_book_content` or `minecraft:writable_book_content` component.
+//!
+//! Pages of written books can hold plain text or
+//! [text components][`crate::import::TextComponent`], pages of writable books only hold plain
+//! text.
+
+use super::{BookError, Tag};
+use crate::{
+    format::{snbt, stendhal::parse, text_component},
+    syntax::{Metadata, Token, TokenList},
+};
+
+/// The kinds of books that can be read, by item ID and by the data component holding their
+/// contents.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    /// A signed book, ex. `minecraft:written_book`.
+    Written,
+    /// A book and quill, ex. `minecraft:writable_book`, which is a draft without a title or author.
+    Writable,
+}
+
+impl Kind {
+    /// Returns the kind of book an item ID refers to, with or without the `minecraft:` namespace.
+    fn from_id(id: &str) -> Option<Self> {
+        match id.strip_prefix("minecraft:").unwrap_or(id) {
+            "written_book" => Some(Self::Written),
+            "writable_book" => Some(Self::Writable),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the data component that holds the contents of this kind of book.
+    const fn component(self) -> &'static str {
+        match self {
+            Self::Written => "written_book_content",
+            Self::Writable => "writable_book_content",
+        }
+    }
+}
+
+/// Build a [`TokenList`] from either an item stack or the contents of a written book.
+///
+/// An item stack is recognized by its `id` field. Anything else is treated as the contents of a
+/// written book, as found in the legacy `tag` compound or the `minecraft:written_book_content`
+/// component.
+///
+/// # Errors
+///
+/// - [`BookError::NotABook`] if the item stack is not a written or writable book
+/// - [`BookError::MissingField`] if a written book has no title or author, or the item stack does
+///   not hold any book contents
+/// - [`BookError::UnexpectedType`] if a field has the wrong type
+/// - [`BookError::Component`] if a page is an invalid text component
+/// - [`BookError::Conversion`] if the text of a page contains an invalid format code
+pub fn from_tag(tag: &Tag) -> Result<TokenList, BookError> {
+    if tag.get("id").is_some() {
+        from_item_stack(tag)
+    } else {
+        from_contents(tag)
+    }
+}
+
+/// Build a [`TokenList`] from an item stack, ex. `{id:"minecraft:written_book",tag:{...}}`.
+///
+/// Writable books (book and quill) are read with [`from_draft`].
+///
+/// # Errors
+///
+/// See [`from_tag`].
+pub fn from_item_stack(stack: &Tag) -> Result<TokenList, BookError> {
+    let id = string(stack, "id")?;
+    let kind = Kind::from_id(id).ok_or_else(|| BookError::NotABook(id.into()))?;
+
+    let contents =
+        contents(stack, kind).ok_or_else(|| BookError::MissingField(kind.component()))?;
+
+    match kind {
+        Kind::Written => from_contents(contents),
+        Kind::Writable => from_draft(contents),
+    }
+}
+
+/// Whether `stack` is an item stack of a written or writable book that has contents.
+///
+/// A book and quill that was never written in has no contents, and is not considered a book.
+#[must_use]
+pub fn is_book(stack: &Tag) -> bool {
+    stack
+        .get("id")
+        .and_then(Tag::as_str)
+        .and_then(Kind::from_id)
+        .is_some_and(|kind| contents(stack, kind).is_some())
+}
+
+/// Find every written or writable book item stack inside of `tag`, no matter how deeply it is
+/// nested (ex. inside of a shulker box, inside of a chest).
+///
+/// See [`is_book`].
+pub fn find<'t>(books: &mut Vec<&'t Tag>, tag: &'t Tag) {
+    match tag {
+        Tag::Compound(compound) => {
+            if is_book(tag) {
+                books.push(tag);
+                return;
+            }
+
+            for (_, child) in compound {
+                find(books, child);
+            }
+        }
+        Tag::List(list) => {
+            for child in list {
+                find(books, child);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Returns the contents of a book item stack, from either its data components or its legacy
+/// `tag` compound.
+fn contents(stack: &Tag, kind: Kind) -> Option<&Tag> {
+    stack
+        .get("components")
+        .and_then(|components| component(components, kind.component()))
+        .or_else(|| stack.get("tag"))
+}
+
+/// Build a [`TokenList`] from the contents of a book, ex. `{title:"...",author:"...",pages:[]}`.
+///
+/// Each page starts with a [`Token::ThematicBreak`], mirroring the
+/// [Stendhal][`crate::import::Stendhal`] importer.
+///
+/// # Errors
+///
+/// See [`from_tag`].
+pub fn from_contents(contents: &Tag) -> Result<TokenList, BookError> {
+    let metadata = [
+        Metadata::Title(filterable(field(contents, "title")?, "title")?.into()),
+        Metadata::Author(string(contents, "author")?.into()),
+    ];
+
+    let pages = pages(contents)?
+        .iter()
+        .map(|page| {
+            let mut tokens: Vec<Token> = vec![];
+            page_contents(&mut tokens, page.get("raw").unwrap_or(page))?;
+            Ok(tokens)
+        })
+        .collect::<Result<Vec<_>, BookError>>()?;
+
+    Ok(TokenList::new_from_pages(metadata.into(), pages))
+}
+
+/// Build a [`TokenList`] from the contents of a writable book, ex. `{pages:["...",...]}` or
+/// `{pages:[{raw:"..."},...]}`.
+///
+/// Drafts have no title or author, so the [`TokenList`] has no [`Metadata`]. Each page starts with
+/// a [`Token::ThematicBreak`], and holds only plain text.
+///
+/// # Errors
+///
+/// - [`BookError::UnexpectedType`] if a field has the wrong type
+/// - [`BookError::Conversion`] if the text of a page contains an invalid format code
+pub fn from_draft(contents: &Tag) -> Result<TokenList, BookError> {
+    let pages = pages(contents)?
+        .iter()
+        .map(|page| {
+            let mut tokens: Vec<Token> = vec![];
+            page_text(&mut tokens, filterable(page, "pages")?)?;
+            Ok(tokens)
+        })
+        .collect::<Result<Vec<_>, BookError>>()?;
+
+    Ok(TokenList::new_from_pages([].into(), pages))
+}
+
+/// Parse the contents of a single page into `output`.
+///
+/// A page is either a text component or plain text. Text components can be stored directly as
+/// NBT (1.21.5+) or as a string of JSON (before 1.21.5), so strings that parse as JSON are
+/// treated as text components, and anything else is treated as plain text.
+///
+/// # Errors
+///
+/// - [`BookError::Component`] if the page is an invalid text component
+/// - [`BookError::Conversion`] if the text contains an invalid format code
+fn page_contents(output: &mut Vec<Token>, page: &Tag) -> Result<(), BookError> {
+    let Tag::String(text) = page else {
+        text_component::parse::component(output, page)?;
+        return Ok(());
+    };
+
+    let looks_like_json = text.trim_start().starts_with(['{', '[', '"']);
+    match snbt::parse::value(text) {
+        Ok(component) if looks_like_json => text_component::parse::component(output, &component)?,
+        _ => page_text(output, text)?,
+    }
+
+    Ok(())
+}
+
+/// Parse the text of a single page into `output`.
+///
+/// Empty lines are considered paragraph breaks, like in [`parse::line`], but text is never
+/// considered the start of a new page.
+///
+/// # Errors
+///
+/// - [`BookError::Conversion`] if the text contains an invalid format code
+fn page_text(output: &mut Vec<Token>, text: &str) -> Result<(), BookError> {
+    for line in text.lines() {
+        if line.is_empty() {
+            output.push(Token::ParagraphBreak);
+        } else {
+            parse::formatted_line(output, line)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the pages of a book, or no pages if there are none.
+fn pages(contents: &Tag) -> Result<&[Tag], BookError> {
+    contents.get("pages").map_or(Ok(&[]), |pages| {
+        pages.as_list().ok_or(BookError::UnexpectedType {
+            field: "pages",
+            expected: "a list",
+        })
+    })
+}
+
+/// Return the value of a data component, with or without the `minecraft:` namespace.
+fn component<'t>(components: &'t Tag, name: &str) -> Option<&'t Tag> {
+    components
+        .get(&format!("minecraft:{name}"))
+        .or_else(|| components.get(name))
+}
+
+/// Return the value of the field `name`, or an error if it is missing.
+fn field<'t>(tag: &'t Tag, name: &'static str) -> Result<&'t Tag, BookError> {
+    tag.get(name).ok_or(BookError::MissingField(name))
+}
+
+/// Return the string in the field `name`, or an error if it is missing or not a string.
+fn string<'t>(tag: &'t Tag, name: &'static str) -> Result<&'t str, BookError> {
+    field(tag, name)?.as_str().ok_or(BookError::UnexpectedType {
+        field: name,
+        expected: "a string",
+    })
+}
+
+/// Return the unfiltered text of a filterable string, which is either a plain string or a
+/// compound like `{raw:"...",filtered:"..."}`.
+fn filterable<'t>(tag: &'t Tag, name: &'static str) -> Result<&'t str, BookError> {
+    tag.get("raw")
+        .unwrap_or(tag)
+        .as_str()
+        .ok_or(BookError::UnexpectedType {
+            field: name,
+            expected: "a string",
+        })
+}
+
+/// The books found in game files, alongside the books that could not be read.
+///
+/// One book that cannot be read does not stop the others from being found, it is skipped and
+/// reported instead.
+#[derive(Debug, Default)]
+pub struct FoundBooks {
+    /// Every book that was read, in the order they were found.
+    books: Vec<TokenList>,
+    /// Every book that could not be read, in the order they were found.
+    skipped: Vec<SkippedBook>,
+}
+
+impl FoundBooks {
+    /// Every book that was read, in the order they were found.
+    #[must_use]
+    pub fn books(&self) -> &[TokenList] {
+        &self.books
+    }
+
+    /// Returns every book that was read.
+    #[must_use]
+    pub fn into_books(self) -> Box<[TokenList]> {
+        self.books.into()
+    }
+
+    /// Every book that could not be read, in the order they were found.
+    #[must_use]
+    pub fn skipped(&self) -> &[SkippedBook] {
+        &self.skipped
+    }
+
+    /// Whether every book could be read.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    /// Read the book item stack `stack`, and keep it with `location` after its own metadata, or
+    /// keep the error with `location` if it cannot be read.
+    pub(crate) fn push(&mut self, stack: &Tag, location: Vec<Metadata>) {
+        match from_item_stack(stack) {
+            Ok(book) => {
+                let mut metadata = book.metadata_as_slice().to_vec();
+                metadata.extend(location);
+                self.books
+                    .push(TokenList::new(metadata.into(), book.tokens()));
+            }
+            Err(error) => self.skipped.push(SkippedBook {
+                location: location.into(),
+                error,
+            }),
+        }
+    }
+}
+
+/// A book that could not be read, and where it was found.
+///
+/// See [`FoundBooks`].
+#[derive(Debug)]
+pub struct SkippedBook {
+    /// The [`Metadata`] that the book would have been given for where it was found.
+    location: Box<[Metadata]>,
+    /// Why the book could not be read.
+    error: BookError,
+}
+
+impl SkippedBook {
+    /// Where the book was found, as the [`Metadata`] that it would have been given, ex.
+    /// [`Metadata::Container`] and [`Metadata::Position`].
+    #[must_use]
+    pub const fn location(&self) -> &[Metadata] {
+        &self.location
+    }
+
+    /// Why the book could not be read.
+    #[must_use]
+    pub const fn error(&self) -> &BookError {
+        &self.error
+    }
+}
@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Error definitions for reading [NBT][`super::Tag`] data and the books inside of it.
+//!
+//! See [`NbtError`] and [`BookError`].
+
+use crate::{format::text_component::ComponentError, syntax::ConversionError};
+
+/// All the errors that could occur while reading binary NBT data.
+#[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
+#[derive(thiserror::Error, Debug)]
+pub enum NbtError {
+    /// Encountered when a tag has a type that does not exist.
+    #[error("unknown tag type {0}")]
+    UnknownTagType(u8),
+    /// Encountered when the root tag is a type other than a compound.
+    #[error("expected the root tag to be a compound, received tag type {0}")]
+    RootNotCompound(u8),
+    /// Encountered when a string, array, or list has a negative length.
+    #[error("negative length {0}")]
+    NegativeLength(i32),
+    /// Encountered when lists and compounds are nested too deeply.
+    #[error("tags are nested deeper than {}", super::binary::MAX_DEPTH)]
+    TooDeep,
+    /// Encountered when a string is not valid Modified UTF-8.
+    #[error("string is not valid Modified UTF-8")]
+    InvalidString,
+    /// Encountered when an I/O action fails in some way.
+    #[error("could not perform I/O action: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// All the errors that could occur while reading a book out of NBT data.
+#[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
+#[derive(thiserror::Error, Debug)]
+pub enum BookError {
+    /// Encountered when an item is something other than a book, ex. `minecraft:stone`.
+    #[error("expected a written or writable book, received '{0}'")]
+    NotABook(Box<str>),
+    /// Encountered when a field required to build the book is not present.
+    #[error("missing the field '{0}'")]
+    MissingField(&'static str),
+    /// Encountered when a field is present, but holds the wrong kind of value.
+    #[error("expected the field '{field}' to be {expected}")]
+    UnexpectedType {
+        field: &'static str,
+        expected: &'static str,
+    },
+    /// Encountered when trying to convert invalid syntax in the text of a page.
+    #[error("could not perform conversion: {0}")]
+    Conversion(#[from] ConversionError),
+    /// Encountered when a page is a text component, but not a valid one.
+    #[error("could not read text component: {0}")]
+    Component(#[from] ComponentError),
+}
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Syntax definitions for Minecraft: Java Edition's Named Binary Tag (NBT) data.
+//!
+//! Used as the common representation of item data, regardless of whether it was read from text
+//! (SNBT) or from game files.
+//!
+//! See [`Tag`].
+
+pub use error::{BookError, NbtError};
+
+pub mod binary;
+pub mod book;
+mod error;
+#[cfg(test)]
+pub mod test;
+
+/// A single NBT value.
+///
+/// Names are not stored on the value itself, instead [`Tag::Compound`] stores them alongside the
+/// values it holds.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Box<[i8]>),
+    String(Box<str>),
+    List(Box<[Self]>),
+    /// A set of named values, kept in the order they were read.
+    Compound(Box<[(Box<str>, Self)]>),
+    IntArray(Box<[i32]>),
+    LongArray(Box<[i64]>),
+}
+
+impl Tag {
+    /// If this is a [`Tag::Compound`], return the value associated with `key`.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        self.as_compound()?
+            .iter()
+            .find_map(|(name, value)| (name.as_ref() == key).then_some(value))
+    }
+
+    /// If this is a [`Tag::String`], return the string it holds.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// If this is a [`Tag::List`], return the values it holds.
+    #[must_use]
+    pub fn as_list(&self) -> Option<&[Self]> {
+        match self {
+            Self::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// If this is a [`Tag::Compound`], return the named values it holds.
+    #[must_use]
+    pub fn as_compound(&self) -> Option<&[(Box<str>, Self)]> {
+        match self {
+            Self::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+}
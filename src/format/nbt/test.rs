@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for reading [binary NBT][`super::binary`], and helpers to build it for other tests.
+
+use super::{binary, NbtError, Tag};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Build a [`Tag::Compound`] out of `key => value` pairs.
+macro_rules! compound {
+    ( $( $key:expr => $value:expr ),* $(,)? ) => {
+        crate::format::nbt::Tag::Compound(Box::new([ $( ($key.into(), $value) ),* ]))
+    };
+}
+pub(crate) use compound;
+
+/// Build a [`Tag::String`] out of a string.
+pub fn string(string: &str) -> Tag {
+    Tag::String(string.into())
+}
+
+/// Build a written book item stack in the legacy format, with the given pages.
+pub fn book(title: &str, pages: &[&str]) -> Tag {
+    compound!(
+        "id" => string("minecraft:written_book"),
+        "Count" => Tag::Byte(1),
+        "tag" => compound!(
+            "title" => string(title),
+            "author" => string("RemasteredArch"),
+            "pages" => Tag::List(pages.iter().map(|page| string(page)).collect()),
+        ),
+    )
+}
+
+/// Encode `tag` as an unnamed root tag in uncompressed binary NBT.
+///
+/// Strings are written as plain UTF-8, which is only valid Modified UTF-8 if they do not contain
+/// `'\0'` or characters outside of the Basic Multilingual Plane.
+pub fn encode(tag: &Tag) -> Vec<u8> {
+    let mut output = vec![tag_type(tag)];
+    encode_string(&mut output, "");
+    encode_payload(&mut output, tag);
+
+    output
+}
+
+/// Returns the type ID of a [`Tag`].
+const fn tag_type(tag: &Tag) -> u8 {
+    match tag {
+        Tag::Byte(_) => 1,
+        Tag::Short(_) => 2,
+        Tag::Int(_) => 3,
+        Tag::Long(_) => 4,
+        Tag::Float(_) => 5,
+        Tag::Double(_) => 6,
+        Tag::ByteArray(_) => 7,
+        Tag::String(_) => 8,
+        Tag::List(_) => 9,
+        Tag::Compound(_) => 10,
+        Tag::IntArray(_) => 11,
+        Tag::LongArray(_) => 12,
+    }
+}
+
+fn encode_string(output: &mut Vec<u8>, string: &str) {
+    let length = u16::try_from(string.len()).expect("test strings should be short");
+
+    output.extend(length.to_be_bytes());
+    output.extend(string.as_bytes());
+}
+
+fn encode_length(output: &mut Vec<u8>, length: usize) {
+    let length = i32::try_from(length).expect("test arrays should be short");
+
+    output.extend(length.to_be_bytes());
+}
+
+fn encode_payload(output: &mut Vec<u8>, tag: &Tag) {
+    match tag {
+        Tag::Byte(byte) => output.extend(byte.to_be_bytes()),
+        Tag::Short(short) => output.extend(short.to_be_bytes()),
+        Tag::Int(int) => output.extend(int.to_be_bytes()),
+        Tag::Long(long) => output.extend(long.to_be_bytes()),
+        Tag::Float(float) => output.extend(float.to_be_bytes()),
+        Tag::Double(double) => output.extend(double.to_be_bytes()),
+        Tag::ByteArray(array) => {
+            encode_length(output, array.len());
+            array
+                .iter()
+                .for_each(|byte| output.extend(byte.to_be_bytes()));
+        }
+        Tag::String(string) => encode_string(output, string),
+        Tag::List(list) => {
+            output.push(list.first().map_or(0, tag_type));
+            encode_length(output, list.len());
+            list.iter().for_each(|tag| encode_payload(output, tag));
+        }
+        Tag::Compound(compound) => {
+            for (name, tag) in compound {
+                output.push(tag_type(tag));
+                encode_string(output, name);
+                encode_payload(output, tag);
+            }
+            output.push(0);
+        }
+        Tag::IntArray(array) => {
+            encode_length(output, array.len());
+            array
+                .iter()
+                .for_each(|int| output.extend(int.to_be_bytes()));
+        }
+        Tag::LongArray(array) => {
+            encode_length(output, array.len());
+            array
+                .iter()
+                .for_each(|long| output.extend(long.to_be_bytes()));
+        }
+    }
+}
+
+#[test]
+fn test_round_trip() -> Result {
+    let tag = compound!(
+        "byte" => Tag::Byte(-1),
+        "short" => Tag::Short(300),
+        "int" => Tag::Int(-70_000),
+        "long" => Tag::Long(1 << 40),
+        "float" => Tag::Float(1.5),
+        "double" => Tag::Double(-2.25),
+        "byte array" => Tag::ByteArray(Box::new([1, -2, 3])),
+        "string" => string("crafty_novels §l"),
+        "empty list" => Tag::List(Box::new([])),
+        "list" => Tag::List(Box::new([string("a"), string("b")])),
+        "nested" => compound!("list" => Tag::List(Box::new([compound!(), compound!()]))),
+        "int array" => Tag::IntArray(Box::new([1, 2, 3])),
+        "long array" => Tag::LongArray(Box::new([-1])),
+    );
+
+    assert_eq!(binary::read(encode(&tag).as_slice())?, tag);
+
+    Ok(())
+}
+
+#[test]
+fn test_modified_utf8() -> Result {
+    // "a\0😀" in Modified UTF-8: '\0' is two bytes, and '😀' is two three-byte surrogates
+    let mut bytes = vec![10, 0, 0, 8, 0, 1, b's', 0, 9, b'a', 0xC0, 0x80];
+    bytes.extend([0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80, 0]);
+
+    assert_eq!(
+        binary::read(bytes.as_slice())?,
+        compound!("s" => string("a\0😀"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    /// Assert that reading `$input` fails with an error matching `$pattern`.
+    macro_rules! fails {
+        ( $( $input:expr => $pattern:pat ),+ $(,)? ) => {
+            $(
+                let result = binary::read($input.as_slice());
+                assert!(matches!(result, Err($pattern)), "{:?}: {result:?}", $input);
+            )+
+        };
+    }
+
+    let mut too_deep = vec![10, 0, 0];
+    for _ in 0..=binary::MAX_DEPTH {
+        too_deep.extend([10, 0, 0]);
+    }
+
+    fails!(
+        vec![8, 0, 0, 0, 0] => NbtError::RootNotCompound(8),
+        vec![10, 0, 0, 13, 0, 0] => NbtError::UnknownTagType(13),
+        vec![10, 0, 0, 11, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF] => NbtError::NegativeLength(-1),
+        vec![10, 0, 0, 8, 0, 0, 0, 1, 0xFF, 0] => NbtError::InvalidString,
+        vec![10, 0, 0, 3, 0, 0, 0, 0] => NbtError::Io(_),
+        too_deep => NbtError::TooDeep,
+    );
+}
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Exporting for plain text.
+//!
+//! See [`PlainText`] for more details.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use crafty_novels::{
+//!    export::PlainText,
+//!    syntax::{minecraft::Format, Metadata, Token, TokenList},
+//!    Export,
+//! };
+//!
+//! let input_metadata = Box::new([
+//!     Metadata::Title("crafty_novels".into()),
+//!     Metadata::Author("RemasteredArch".into()),
+//! ]);
+//! let input_tokens = Box::new([
+//!     Token::ThematicBreak,
+//!     Token::Text("Italic:".into()),
+//!     Token::Format(Format::Italic),
+//!     Token::Space,
+//!     Token::Text("text".into()),
+//!     Token::Space,
+//!     Token::Format(Format::Reset),
+//!     Token::Text("reset".into()),
+//!     Token::LineBreak,
+//! ]);
+//! let input = TokenList::new_from_boxed(input_metadata, input_tokens);
+//!
+//! let expected = concat!(
+//!     "crafty_novels\n",
+//!     "by RemasteredArch\n",
+//!     "\n",
+//!     "--- Page 1 ---\n",
+//!     "Italic: text reset\n",
+//! );
+//!
+//! let exporter = PlainText::new().page_separator("--- Page {page} ---");
+//! assert_eq!(exporter.export_to_string(&input).as_ref(), expected);
+//! ```
+
+use crate::{
+    resolve::Untranslated,
+    syntax::{Metadata, Token, TokenList},
+    writer::Utf8Writer,
+    Export,
+};
+use std::io::Write;
+
+#[cfg(test)]
+mod test;
+
+/// Exporting for plain text, keeping only the words and the whitespace between them.
+///
+/// # Format
+///
+/// Opens with a header made from the [metadata][`crate::syntax::Metadata`], followed by a blank
+/// line, unless [`PlainText::header`] is disabled:
+///
+/// ```text
+/// {title}
+/// by {author}
+/// ```
+///
+/// Inside of the contents:
+///
+/// - Text is written as-is
+/// - Spaces, line breaks, and paragraph breaks are written as spaces, line endings, and empty
+///   lines
+/// - Formatting is dropped entirely
+/// - Thematic breaks are written as the [page separator][`PlainText::page_separator`] on a line of
+///   its own, which is a form feed (`'\u{C}'`) by default
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PlainText {
+    page_separator: Box<str>,
+    header: bool,
+}
+
+impl PlainText {
+    /// The placeholder in a [page separator][`Self::page_separator`] that is replaced with the
+    /// number of the page.
+    pub const PAGE_NUMBER: &'static str = "{page}";
+
+    /// Create a new [`PlainText`] exporter with the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            page_separator: "\u{C}".into(),
+            header: true,
+        }
+    }
+
+    /// The text to write for each thematic break, which marks the start of a page.
+    ///
+    /// Any [`Self::PAGE_NUMBER`] (`"{page}"`) in it is replaced with the number of the page that is
+    /// starting, counting from one. Ex. `"--- Page {page} ---"`.
+    ///
+    /// Pages are numbered like in [`TokenList::pages`], so if there are any contents before the
+    /// first thematic break, they are page one, and the first thematic break starts page two.
+    #[must_use]
+    pub fn page_separator(mut self, page_separator: impl Into<Box<str>>) -> Self {
+        self.page_separator = page_separator.into();
+        self
+    }
+
+    /// Whether to start with a header containing the title and author (`true`, the default).
+    #[must_use]
+    pub const fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+impl Default for PlainText {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Export for PlainText {
+    /// Parse a given abstract syntax vector into plain text using these options, then output that
+    /// into a writer, like a [`std::fs::File`].
+    ///
+    /// Guaranteed to only write valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into `output`
+    fn export_to_writer(&self, tokens: &TokenList, output: &mut impl Write) -> std::io::Result<()> {
+        let tokens = &tokens.resolve(&Untranslated).resolve_styles();
+        let mut writer = Utf8Writer::new(output);
+
+        if self.header {
+            start_document(&mut writer, tokens.metadata_as_slice())?;
+        }
+
+        // Match `TokenList::pages`, where contents before the first thematic break are page one
+        let mut page = usize::from(!matches!(
+            tokens.tokens_as_slice().first(),
+            None | Some(Token::ThematicBreak)
+        ));
+        // Whether anything has been written onto the current line
+        let mut line_open = false;
+
+        for token in tokens.tokens_as_slice() {
+            match token {
+                Token::Text(text) => writer.write_str(text)?,
+                Token::Space => writer.write_char(' ')?,
+                // Content is filled in before exporting
+                Token::Format(_)
+                | Token::Click(_)
+                | Token::Hover(_)
+                | Token::Insertion(_)
+                | Token::Content(_) => continue,
+                Token::LineBreak | Token::ParagraphBreak => {
+                    writer.write_char('\n')?;
+                    line_open = false;
+                    continue;
+                }
+                Token::ThematicBreak => {
+                    if line_open {
+                        writer.write_char('\n')?;
+                    }
+
+                    page += 1;
+                    let separator = self
+                        .page_separator
+                        .replace(Self::PAGE_NUMBER, &page.to_string());
+                    writer.write_str(separator)?;
+                    writer.write_char('\n')?;
+
+                    line_open = false;
+                    continue;
+                }
+            }
+
+            line_open = true;
+        }
+
+        if line_open {
+            writer.write_char('\n')?;
+        }
+
+        writer.flush()
+    }
+}
+
+/// Write the title and author from `metadata`, followed by a blank line, if there are any.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+fn start_document(
+    output: &mut Utf8Writer<impl Write>,
+    metadata: &[Metadata],
+) -> std::io::Result<()> {
+    let mut header = false;
+
+    for data in metadata {
+        match data {
+            Metadata::Title(t) => writeln!(output, "{t}")?,
+            Metadata::Author(a) => writeln!(output, "by {a}")?,
+            // Where a book was found has no meaning in the document itself
+            _ => continue,
+        }
+
+        header = true;
+    }
+
+    if header {
+        output.write_char('\n')?;
+    }
+
+    Ok(())
+}
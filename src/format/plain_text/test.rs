@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for exporting to the [plain text][`super::PlainText`] format.
+
+use super::PlainText;
+use crate::{
+    import::Stendhal,
+    syntax::{Token, TokenList},
+    Export, Tokenize,
+};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Insert a [`Token::Format`] with the given variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+const INPUT: &str = "title: crafty_novels
+author: RemasteredArch
+pages:
+#- This is the start of the page
+First line
+#- New Page
+Not a #- new page
+
+
+
+Some §cRED line breaks
+Italic:§o text §rreset
+   lots    of   spaces     
+<div>some HTML</div>";
+
+#[test]
+fn test_default() -> Result {
+    let expected = "crafty_novels
+by RemasteredArch
+
+\u{C}
+This is the start of the page
+First line
+\u{C}
+New Page
+Not a #- new page
+
+
+
+Some RED line breaks
+Italic: text reset
+   lots    of   spaces     
+<div>some HTML</div>
+";
+
+    let text = PlainText::export_token_vector_to_string(Stendhal::tokenize_string(INPUT)?);
+    assert_eq!(text.as_ref(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_options() -> Result {
+    let expected = "--- Page 1 of crafty_novels ---
+This is the start of the page
+First line
+--- Page 2 of crafty_novels ---
+New Page
+";
+
+    let input = Stendhal::tokenize_string(INPUT)?;
+    let text = PlainText::new()
+        .header(false)
+        .page_separator("--- Page {page} of crafty_novels ---")
+        .export_to_string(&input);
+    assert!(text.starts_with(expected), "{text}");
+
+    Ok(())
+}
+
+#[test]
+fn test_tokens() {
+    use Token::{LineBreak, Space, ThematicBreak};
+
+    // No metadata means no header, pages can start in the middle of a line, and contents before
+    // the first page break are a page of their own
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            format!(Bold),
+            text!("one"),
+            format!(Reset),
+            ThematicBreak,
+            text!("two"),
+            Space,
+            ThematicBreak,
+            ThematicBreak,
+            LineBreak,
+            text!("three"),
+        ]),
+    );
+
+    assert_eq!(
+        PlainText::new()
+            .page_separator("[{page}]")
+            .export_to_string(&input)
+            .as_ref(),
+        "one\n[2]\ntwo \n[3]\n[4]\n\nthree\n"
+    );
+    assert_eq!(
+        input.pages().map(|page| page.number()).collect::<Vec<_>>(),
+        [1, 2, 3, 4]
+    );
+}
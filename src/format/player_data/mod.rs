@@ -0,0 +1,253 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Finding written books in Minecraft: Java Edition player data (`playerdata/<uuid>.dat`).
+//! See [`PlayerData`] for more details.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use crafty_novels::{export::Html, import::PlayerData, syntax::Metadata, Export};
+//! # use std::error::Error;
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let found = PlayerData::tokenize_directory("saves/New World/playerdata")?;
+//! for skipped in found.skipped() {
+//!     eprintln!("skipped a book at {:?}: {}", skipped.location(), skipped.error());
+//! }
+//!
+//! for book in found.into_books() {
+//!     for metadata in book.metadata_as_slice() {
+//!         if let Metadata::Owner(uuid) = metadata {
+//!             eprintln!("found a book belonging to {uuid}");
+//!         }
+//!     }
+//!
+//!     println!("{}", Html::export_token_vector_to_string(book));
+//! }
+//! #
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{
+    format::nbt::{
+        binary,
+        book::{self, FoundBooks},
+        Tag,
+    },
+    syntax::Metadata,
+};
+pub use error::TokenizeError;
+use flate2::read::GzDecoder;
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+mod error;
+#[cfg(test)]
+mod test;
+
+/// The ID used as the [`Metadata::Container`] of the player's own inventory and equipment.
+const INVENTORY: &str = "minecraft:player";
+/// The ID used as the [`Metadata::Container`] of the player's ender chest.
+const ENDER_CHEST: &str = "minecraft:ender_chest";
+
+/// The slots of the equipment compound (1.21.5+), numbered the same as they were in the player's
+/// inventory before it was split out.
+const EQUIPMENT: [(&str, i32); 5] = [
+    ("feet", 100),
+    ("legs", 101),
+    ("chest", 102),
+    ("head", 103),
+    ("offhand", -106),
+];
+
+/// Finds written books and writable book drafts in Minecraft: Java Edition player data.
+///
+/// # Expected format
+///
+/// Player data is gzip-compressed NBT, stored in a world's `playerdata` directory as
+/// `<uuid>.dat`, holding:
+///
+/// - `Inventory`, the player's inventory, where each item has a `Slot`
+/// - `EnderItems`, the player's ender chest, where each item has a `Slot`
+/// - `equipment`, the player's armor and offhand (1.21.5+)
+/// - `UUID` (1.16+), or `UUIDMost` and `UUIDLeast`, the player's UUID
+///
+/// Every item is searched for books, including inside of items that hold other items, like
+/// shulker boxes. Each book becomes its own [`TokenList`], tokenized like
+/// [SNBT][`crate::import::Snbt`] books, with the following [`Metadata`] after the title and author
+/// (which drafts do not have):
+///
+/// - [`Owner`][`Metadata::Owner`], the UUID of the player, if it is known
+/// - [`Container`][`Metadata::Container`], either `minecraft:player` for the player's inventory or
+///   `minecraft:ender_chest` for their ender chest
+/// - [`Slot`][`Metadata::Slot`], the slot holding the book (or the item holding the book)
+///
+/// A book that is not valid does not stop the rest from being found. It is
+/// [skipped][`FoundBooks::skipped`] and reported with the same metadata for where it was found.
+pub struct PlayerData;
+
+impl PlayerData {
+    /// Find every book in a `playerdata` directory, reading every `.dat` file in it.
+    ///
+    /// Books are returned in the order of their file name, then their inventory, ender chest, and
+    /// equipment, then their slot.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Io`] if the directory or a file cannot be read
+    /// - Any error from [`Self::tokenize_player`]
+    pub fn tokenize_directory(directory: impl AsRef<Path>) -> Result<FoundBooks, TokenizeError> {
+        let mut entries = fs::read_dir(directory)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<PathBuf>>>()?;
+        entries.sort();
+
+        let mut output = FoundBooks::default();
+
+        // Skips backups, like `<uuid>.dat_old`
+        for path in entries {
+            if path.extension().is_some_and(|extension| extension == "dat") {
+                read_player_file(&mut output, &path)?;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Find every book in a single player data file.
+    ///
+    /// If the data does not hold the player's UUID, the name of the file is used instead.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Io`] if the file cannot be read
+    /// - Any error from [`Self::tokenize_player`]
+    pub fn tokenize_file(path: impl AsRef<Path>) -> Result<FoundBooks, TokenizeError> {
+        let mut output = FoundBooks::default();
+
+        read_player_file(&mut output, path.as_ref())?;
+
+        Ok(output)
+    }
+
+    /// Find every book in player data, read from `input`.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Nbt`] if `input` is not valid gzip-compressed NBT
+    /// - [`TokenizeError::Io`] if `input` cannot be read
+    pub fn tokenize_player(input: impl Read) -> Result<FoundBooks, TokenizeError> {
+        let mut output = FoundBooks::default();
+
+        let player = binary::read(GzDecoder::new(input))?;
+        scan(&mut output, &player, None);
+
+        Ok(output)
+    }
+}
+
+/// Find every book in the player data file at `path`, pushing them into `output`.
+///
+/// # Errors
+///
+/// See [`PlayerData::tokenize_file`].
+fn read_player_file(output: &mut FoundBooks, path: &Path) -> Result<(), TokenizeError> {
+    let player = binary::read(GzDecoder::new(fs::File::open(path)?))?;
+    let file_name = path.file_stem().and_then(|stem| stem.to_str());
+
+    scan(output, &player, file_name);
+    Ok(())
+}
+
+/// Find every book held by `player`, pushing them into `output`.
+///
+/// `fallback_uuid` is used as the owner if the player data does not hold a UUID. Books that are
+/// not valid are skipped with the same location as any other book.
+fn scan(output: &mut FoundBooks, player: &Tag, fallback_uuid: Option<&str>) {
+    let owner: Option<Box<str>> = uuid(player).or_else(|| fallback_uuid.map(Into::into));
+
+    let mut slots: Vec<(&str, i32, &Tag)> = vec![];
+    for (field, container) in [("Inventory", INVENTORY), ("EnderItems", ENDER_CHEST)] {
+        for item in player.get(field).and_then(Tag::as_list).unwrap_or_default() {
+            let slot = match item.get("Slot") {
+                Some(Tag::Byte(slot)) => i32::from(*slot),
+                _ => continue,
+            };
+            slots.push((container, slot, item));
+        }
+    }
+    if let Some(equipment) = player.get("equipment") {
+        for (name, slot) in EQUIPMENT {
+            if let Some(item) = equipment.get(name) {
+                slots.push((INVENTORY, slot, item));
+            }
+        }
+    }
+
+    for (container, slot, item) in slots {
+        let mut books: Vec<&Tag> = vec![];
+        book::find(&mut books, item);
+
+        for found in books {
+            let mut location = vec![];
+            if let Some(owner) = &owner {
+                location.push(Metadata::Owner(owner.clone()));
+            }
+            location.push(Metadata::Container(container.into()));
+            location.push(Metadata::Slot(slot));
+
+            output.push(found, location);
+        }
+    }
+}
+
+/// Returns the player's UUID in its hyphenated form, from either `UUID` (1.16+) or `UUIDMost` and
+/// `UUIDLeast`.
+fn uuid(player: &Tag) -> Option<Box<str>> {
+    #[allow(clippy::cast_sign_loss)] // The bits are what matter, not the sign
+    let uuid: u128 = match (
+        player.get("UUID"),
+        player.get("UUIDMost"),
+        player.get("UUIDLeast"),
+    ) {
+        (Some(Tag::IntArray(ints)), _, _) if ints.len() == 4 => ints
+            .iter()
+            .fold(0, |uuid, &int| (uuid << 32) | u128::from(int as u32)),
+        (_, Some(Tag::Long(most)), Some(Tag::Long(least))) => {
+            (u128::from(*most as u64) << 64) | u128::from(*least as u64)
+        }
+        _ => return None,
+    };
+
+    let hex = format!("{uuid:032x}");
+    Some(
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+        .into(),
+    )
+}
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for finding books in [player data][`super::PlayerData`].
+
+use super::{PlayerData, TokenizeError};
+use crate::{
+    format::nbt::{
+        test::{book, compound, encode, string},
+        BookError, Tag,
+    },
+    syntax::{Metadata, Token, TokenList},
+};
+use flate2::{write::GzEncoder, Compression};
+use std::{fs, io::Write, path::PathBuf};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+/// Compress the binary NBT of `player` like the game does.
+fn gzip(player: &Tag) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder
+        .write_all(&encode(player))
+        .expect("writing to a `Vec` is infallible");
+    encoder.finish().expect("writing to a `Vec` is infallible")
+}
+
+/// Give an item stack a `Slot`, like items in an inventory.
+fn slot(slot: i8, item: Tag) -> Tag {
+    let Tag::Compound(fields) = item else {
+        panic!("item stacks are compounds");
+    };
+
+    let mut fields = fields.into_vec();
+    fields.push(("Slot".into(), Tag::Byte(slot)));
+    Tag::Compound(fields.into())
+}
+
+/// Returns the metadata of every book in `books`.
+fn metadata(books: &[TokenList]) -> Vec<&[Metadata]> {
+    books.iter().map(TokenList::metadata_as_slice).collect()
+}
+
+#[test]
+fn test_player() -> Result {
+    let uuid = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+    let player = compound!(
+        "DataVersion" => Tag::Int(4325),
+        "UUID" => Tag::IntArray(Box::new([0x069a_79f4, 0x44e9_4726, -0x5a41_0357, 0x0e38_aaf5])),
+        "Inventory" => Tag::List(Box::new([
+            slot(0, compound!("id" => string("minecraft:stone"), "count" => Tag::Int(64))),
+            slot(4, book("In the hotbar", &["Page one"])),
+            slot(20, compound!(
+                "id" => string("minecraft:shulker_box"),
+                "count" => Tag::Int(1),
+                "components" => compound!(
+                    "minecraft:container" => Tag::List(Box::new([compound!(
+                        "slot" => Tag::Int(0),
+                        "item" => book("In a shulker box", &["Page one"]),
+                    )])),
+                ),
+            )),
+            // A book and quill that was never written in
+            slot(21, compound!("id" => string("minecraft:writable_book"), "count" => Tag::Int(1))),
+        ])),
+        "EnderItems" => Tag::List(Box::new([slot(26, compound!(
+            "id" => string("minecraft:writable_book"),
+            "Count" => Tag::Byte(1),
+            "tag" => compound!(
+                "pages" => Tag::List(Box::new([string("A draft"), string("")])),
+            ),
+        ))])),
+        "equipment" => compound!(
+            "offhand" => compound!(
+                "id" => string("minecraft:writable_book"),
+                "count" => Tag::Int(1),
+                "components" => compound!(
+                    "minecraft:writable_book_content" => compound!(
+                        "pages" => Tag::List(Box::new([compound!("raw" => string("In the offhand"))])),
+                    ),
+                ),
+            ),
+        ),
+    );
+
+    let books = PlayerData::tokenize_player(gzip(&player).as_slice())?.into_books();
+
+    /// Build the metadata of a book found on the player.
+    macro_rules! found {
+        ( $( $metadata:expr ),* ; $container:expr, $slot:expr ) => {
+            [
+                $( $metadata, )*
+                Metadata::Owner(uuid.into()),
+                Metadata::Container($container.into()),
+                Metadata::Slot($slot),
+            ]
+            .as_slice()
+        };
+    }
+
+    assert_eq!(
+        metadata(&books),
+        [
+            found!(
+                Metadata::Title("In the hotbar".into()),
+                Metadata::Author("RemasteredArch".into());
+                "minecraft:player", 4
+            ),
+            found!(
+                Metadata::Title("In a shulker box".into()),
+                Metadata::Author("RemasteredArch".into());
+                "minecraft:player", 20
+            ),
+            found!(; "minecraft:ender_chest", 26),
+            found!(; "minecraft:player", -106),
+        ]
+    );
+
+    // Drafts are plain text, and keep their empty pages
+    assert_eq!(
+        books[2].tokens_as_slice(),
+        [
+            Token::ThematicBreak,
+            text!("A"),
+            Token::Space,
+            text!("draft"),
+            Token::LineBreak,
+            Token::ThematicBreak,
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_directory() -> Result {
+    let directory: PathBuf =
+        std::env::temp_dir().join(format!("crafty_novels-playerdata-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory)?;
+
+    let legacy = compound!(
+        "UUIDMost" => Tag::Long(0x069a_79f4_44e9_4726),
+        "UUIDLeast" => Tag::Long(-0x5a41_0356_f1c7_550b),
+        "Inventory" => Tag::List(Box::new([slot(-106, book("Legacy", &["Page one"]))])),
+    );
+    // An invalid book does not stop the rest of the directory from being read
+    let anonymous = compound!(
+        "EnderItems" => Tag::List(Box::new([
+            slot(0, book("Anonymous", &["Page one"])),
+            slot(1, book("Invalid", &[r#"{"text":"a","color":"mauve"}"#])),
+        ])),
+    );
+
+    fs::write(
+        directory.join("00000000-0000-0000-0000-000000000000.dat"),
+        gzip(&legacy),
+    )?;
+    fs::write(
+        directory.join("853c80ef-3c37-49fd-aa49-938b674adae6.dat"),
+        gzip(&anonymous),
+    )?;
+    // Backups should be ignored, even if they are not valid
+    fs::write(
+        directory.join("853c80ef-3c37-49fd-aa49-938b674adae6.dat_old"),
+        "",
+    )?;
+
+    let books = PlayerData::tokenize_directory(&directory);
+    fs::remove_dir_all(&directory)?;
+    let found = books?;
+
+    let owners: Vec<&Metadata> = found
+        .books()
+        .iter()
+        .map(|book| &book.metadata_as_slice()[2])
+        .collect();
+    assert_eq!(
+        owners,
+        [
+            // The UUID in the data takes priority over the file name
+            &Metadata::Owner("069a79f4-44e9-4726-a5be-fca90e38aaf5".into()),
+            &Metadata::Owner("853c80ef-3c37-49fd-aa49-938b674adae6".into()),
+        ]
+    );
+
+    // It is reported with where it was found instead
+    let [skipped] = found.skipped() else {
+        panic!("expected one skipped book, found {:?}", found.skipped());
+    };
+    assert_eq!(
+        skipped.location(),
+        [
+            Metadata::Owner("853c80ef-3c37-49fd-aa49-938b674adae6".into()),
+            Metadata::Container("minecraft:ender_chest".into()),
+            Metadata::Slot(1),
+        ]
+    );
+    assert!(matches!(skipped.error(), BookError::Component(_)));
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let not_gzip = encode(&compound!());
+    let result = PlayerData::tokenize_player(not_gzip.as_slice());
+    assert!(matches!(result, Err(TokenizeError::Nbt(_))), "{result:?}");
+
+    // Invalid books are skipped, rather than failing
+    let invalid_book = compound!(
+        "Inventory" => Tag::List(Box::new([slot(0, compound!(
+            "id" => string("minecraft:written_book"),
+            "tag" => compound!("author" => string("RemasteredArch")),
+        ))])),
+    );
+    let found = PlayerData::tokenize_player(gzip(&invalid_book).as_slice())
+        .expect("the player data to be valid");
+    assert!(found.books().is_empty());
+    assert!(matches!(
+        found.skipped()[0].error(),
+        BookError::MissingField("title")
+    ));
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Error definitions for [`super::Snbt`].
+//!
+//! See [`TokenizeError`] and [`SyntaxError`].
+
+use crate::format::nbt::BookError;
+
+/// All the errors that could occur while tokenizing an SNBT item.
+#[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
+#[derive(thiserror::Error, Debug)]
+pub enum TokenizeError {
+    /// Encountered when the input is not valid SNBT.
+    #[error("could not parse SNBT: {0}")]
+    Syntax(#[from] SyntaxError),
+    /// Encountered when the item is valid SNBT, but not a valid book.
+    #[error("could not read book: {0}")]
+    Book(#[from] BookError),
+    /// Encountered when an I/O action fails in some way.
+    #[error("could not perform I/O action: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// All the errors that could occur while parsing SNBT (or JSON, which it is a superset of).
+#[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
+#[derive(thiserror::Error, Debug)]
+pub enum SyntaxError {
+    /// Encountered when the input contains a character where it does not belong.
+    #[error("expected {expected} at byte {offset}, found '{found}'")]
+    UnexpectedCharacter {
+        expected: &'static str,
+        found: char,
+        offset: usize,
+    },
+    /// Encountered when the input ends before the value is complete.
+    #[error("expected {0}, but the input ended")]
+    UnexpectedEnd(&'static str),
+    /// Encountered when a string contains an unknown or malformed escape sequence.
+    #[error("invalid escape sequence at byte {0}")]
+    InvalidEscape(usize),
+    /// Encountered when an element of a typed array (ex. `[I;1,2,3]`) is not of that type.
+    #[error("invalid element in array at byte {0}")]
+    InvalidArrayElement(usize),
+    /// Encountered when there is more input after the end of the value.
+    #[error("unexpected input after the value at byte {0}")]
+    TrailingInput(usize),
+}
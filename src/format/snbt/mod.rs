@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing for written books in Minecraft: Java Edition's stringified NBT (SNBT) format.
+//! See [`Snbt`] for more details.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use crafty_novels::{
+//!     import::Snbt,
+//!     syntax::{minecraft::Format, Metadata, Token, TokenList},
+//!     Tokenize,
+//! };
+//! # use std::error::Error;
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let input = r#"/give @p written_book[written_book_content={
+//!     title: "crafty_novels",
+//!     author: "RemasteredArch",
+//!     pages: ["Italic:§o text §rreset"]
+//! }]"#;
+//!
+//! let expected_metadata = Box::new([
+//!     Metadata::Title("crafty_novels".into()),
+//!     Metadata::Author("RemasteredArch".into()),
+//! ]);
+//! let expected_tokens = Box::new([
+//!     Token::ThematicBreak,
+//!     Token::Text("Italic:".into()),
+//!     Token::Format(Format::Italic),
+//!     Token::Space,
+//!     Token::Text("text".into()),
+//!     Token::Space,
+//!     Token::Format(Format::Reset),
+//!     Token::Text("reset".into()),
+//!     Token::LineBreak,
+//! ]);
+//!
+//! assert_eq!(
+//!     Snbt::tokenize_string(input)?,
+//!     TokenList::new_from_boxed(expected_metadata, expected_tokens)
+//! );
+//! #
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{format::nbt::book, syntax::TokenList, Tokenize};
+pub use error::{SyntaxError, TokenizeError};
+use std::io::Read;
+
+mod error;
+pub mod parse;
+#[cfg(test)]
+mod test;
+
+/// Parses written books stored as stringified NBT (SNBT).
+///
+/// # Expected format
+///
+/// Any one of the following, with arbitrary white space between values:
+///
+/// - A `/give` command, ex. `/give @p written_book{title:"...",author:"...",pages:[...]}`
+///     - The leading `'/'` is optional
+///     - The target can be a player name or a target selector, ex. `@a[limit=1]`
+///     - A trailing item count is allowed
+/// - An item string, the same as a `/give` command but without the `give` and the target
+/// - An item stack compound, ex. `{id:"minecraft:written_book",tag:{...}}`
+/// - The contents of a book alone, ex. `{title:"...",author:"...",pages:[...]}`
+///
+/// The book itself can be stored in either of Minecraft's item formats:
+///
+/// - The legacy format (before 1.20.5), in the `tag` compound: `written_book{...}`
+/// - The data component format (1.20.5+), in the `minecraft:written_book_content` component:
+///   `written_book[written_book_content={...}]`
+///
+/// The book must have a `title` and an `author`, which become
+/// [`Metadata::Title`][`crate::syntax::Metadata::Title`] and
+/// [`Metadata::Author`][`crate::syntax::Metadata::Author`].
+///
+/// Each string in `pages` is the start of a new page, represented in syntax by
+/// [`Token::ThematicBreak`][`crate::syntax::Token::ThematicBreak`]. Inside of a page:
+///
+/// - Each line ending (`'\n'`) ends a line, and empty lines are paragraph breaks
+/// - `'§'`, followed a one of a set of characters makes up a format code, represented in syntax by
+///   [`Format`][`crate::syntax::minecraft::Format`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Snbt;
+
+impl Tokenize for Snbt {
+    type Error = TokenizeError;
+
+    /// Parse a string containing an SNBT written book into an abstract syntax vector.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Syntax`] if `input` is not valid SNBT
+    /// - [`TokenizeError::Book`] if `input` is valid SNBT, but not a valid written book
+    fn tokenize_from_string(&self, input: &str) -> Result<TokenList, Self::Error> {
+        let tag = parse::item(input)?;
+
+        Ok(book::from_tag(&tag)?)
+    }
+
+    /// Parse a file containing an SNBT written book into an abstract syntax vector.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Syntax`] if `input` is not valid SNBT
+    /// - [`TokenizeError::Book`] if `input` is valid SNBT, but not a valid written book
+    /// - [`TokenizeError::Io`] if `input` cannot be read or is not valid UTF-8
+    fn tokenize_from_reader(&self, mut input: impl Read) -> Result<TokenList, Self::Error> {
+        let mut string = String::new();
+        input.read_to_string(&mut string)?;
+
+        self.tokenize_from_string(&string)
+    }
+}
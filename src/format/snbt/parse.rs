@@ -0,0 +1,554 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The actual, under the hood, character-by-character parsing for the [SNBT][`super::Snbt`]
+//! format.
+
+use super::SyntaxError;
+use crate::format::nbt::Tag;
+use std::{iter::Peekable, str::CharIndices};
+
+/// Parse an item in any of the forms accepted by [`super::Snbt`] into a [`Tag`].
+///
+/// Item strings and `/give` commands are converted into an item stack compound, so the result
+/// is either an item stack or the contents of a book.
+///
+/// # Errors
+///
+/// - [`SyntaxError::UnexpectedCharacter`] or [`SyntaxError::UnexpectedEnd`] if the input is
+///   malformed
+/// - [`SyntaxError::InvalidEscape`] if a string contains an invalid escape sequence
+/// - [`SyntaxError::InvalidArrayElement`] if a typed array contains the wrong type
+/// - [`SyntaxError::TrailingInput`] if there is more input after the item
+pub fn item(input: &str) -> Result<Tag, SyntaxError> {
+    let mut parser = Parser::new(input);
+
+    parser.skip_white_space();
+    let tag = if parser.peek() == Some('{') {
+        parser.value()?
+    } else {
+        parser.command()?
+    };
+
+    parser.skip_white_space();
+    parser
+        .peek_offset()
+        .map_or(Ok(tag), |offset| Err(SyntaxError::TrailingInput(offset)))
+}
+
+/// Parse a single SNBT value into a [`Tag`], ex. `{title:"crafty_novels",pages:[]}`.
+///
+/// Because SNBT is (nearly) a superset of JSON, this also parses JSON, with objects becoming
+/// [`Tag::Compound`]s, booleans becoming [`Tag::Byte`]s, and numbers becoming [`Tag::Int`]s or
+/// [`Tag::Double`]s.
+///
+/// # Errors
+///
+/// See [`item`].
+pub fn value(input: &str) -> Result<Tag, SyntaxError> {
+    let mut parser = Parser::new(input);
+
+    parser.skip_white_space();
+    let tag = parser.value()?;
+
+    parser.skip_white_space();
+    parser
+        .peek_offset()
+        .map_or(Ok(tag), |offset| Err(SyntaxError::TrailingInput(offset)))
+}
+
+/// Whether `char` is allowed in an unquoted string, like a compound key or `true`.
+const fn is_unquoted(char: char) -> bool {
+    matches!(char, '0'..='9' | 'A'..='Z' | 'a'..='z' | '_' | '-' | '.' | '+')
+}
+
+/// Whether `char` is allowed in a resource location, like `minecraft:written_book`.
+const fn is_resource_location(char: char) -> bool {
+    matches!(char, '0'..='9' | 'a'..='z' | '_' | '-' | '.' | ':' | '/')
+}
+
+/// Steps through SNBT input, keeping track of the byte offset for errors.
+struct Parser<'s> {
+    input: &'s str,
+    iter: Peekable<CharIndices<'s>>,
+}
+
+impl<'s> Parser<'s> {
+    fn new(input: &'s str) -> Self {
+        Self {
+            input,
+            iter: input.char_indices().peekable(),
+        }
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        self.iter.peek().map(|&(_, char)| char)
+    }
+
+    /// Returns the byte offset of the next character without consuming it.
+    fn peek_offset(&mut self) -> Option<usize> {
+        self.iter.peek().map(|&(offset, _)| offset)
+    }
+
+    /// Returns the byte offset of the next character, or the end of the input.
+    fn offset(&mut self) -> usize {
+        self.peek_offset().unwrap_or(self.input.len())
+    }
+
+    /// Consumes and returns the next character, or an error describing what was `expected`.
+    fn next(&mut self, expected: &'static str) -> Result<char, SyntaxError> {
+        self.iter
+            .next()
+            .map(|(_, char)| char)
+            .ok_or(SyntaxError::UnexpectedEnd(expected))
+    }
+
+    /// Consumes the next character, returning an error if it is not `char`.
+    fn expect(&mut self, char: char, expected: &'static str) -> Result<(), SyntaxError> {
+        match self.iter.next() {
+            Some((_, found)) if found == char => Ok(()),
+            Some((offset, found)) => Err(SyntaxError::UnexpectedCharacter {
+                expected,
+                found,
+                offset,
+            }),
+            None => Err(SyntaxError::UnexpectedEnd(expected)),
+        }
+    }
+
+    /// Return an error for the next character, or for the end of the input.
+    fn unexpected(&mut self, expected: &'static str) -> SyntaxError {
+        match self.iter.peek() {
+            Some(&(offset, found)) => SyntaxError::UnexpectedCharacter {
+                expected,
+                found,
+                offset,
+            },
+            None => SyntaxError::UnexpectedEnd(expected),
+        }
+    }
+
+    fn skip_white_space(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.iter.next();
+        }
+    }
+
+    /// Consumes characters while `predicate` holds, returning them as a slice of the input.
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'s str {
+        let start = self.offset();
+
+        while self.peek().is_some_and(&predicate) {
+            self.iter.next();
+        }
+
+        &self.input[start..self.offset()]
+    }
+
+    /// Parse a `/give` command or an item string into an item stack compound.
+    ///
+    /// - `/give @p written_book{title:"..."}`
+    /// - `give @a[limit=1] minecraft:written_book[written_book_content={...}] 1`
+    /// - `written_book[written_book_content={...}]`
+    fn command(&mut self) -> Result<Tag, SyntaxError> {
+        if self.peek() == Some('/') {
+            self.iter.next();
+        }
+
+        let is_give = self.input[self.offset()..]
+            .strip_prefix("give")
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace));
+        if is_give {
+            self.take_while(|char| !char.is_whitespace());
+            self.skip_white_space();
+            self.target()?;
+            self.skip_white_space();
+        }
+
+        let id = self.take_while(is_resource_location);
+        if id.is_empty() {
+            return Err(self.unexpected("an item ID"));
+        }
+
+        let mut stack: Vec<(Box<str>, Tag)> = vec![("id".into(), Tag::String(id.into()))];
+
+        if self.peek() == Some('[') {
+            stack.push(("components".into(), self.components()?));
+        }
+        if self.peek() == Some('{') {
+            stack.push(("tag".into(), self.compound()?));
+        }
+
+        self.skip_white_space();
+        let count = self.take_while(|char| char.is_ascii_digit());
+        if let Ok(count) = count.parse() {
+            stack.push(("count".into(), Tag::Int(count)));
+        }
+
+        Ok(Tag::Compound(stack.into()))
+    }
+
+    /// Skip over the target of a `/give` command, ex. `@p`, `@a[limit=1]`, or `RemasteredArch`.
+    fn target(&mut self) -> Result<(), SyntaxError> {
+        if self.peek() != Some('@') {
+            if self.take_while(|char| !char.is_whitespace()).is_empty() {
+                return Err(self.unexpected("a target"));
+            }
+            return Ok(());
+        }
+
+        self.iter.next();
+        self.next("a target selector")?;
+
+        if self.peek() != Some('[') {
+            return Ok(());
+        }
+
+        let mut depth = 0_usize;
+        loop {
+            match self.next("the end of the target selector")? {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                quote @ ('"' | '\'') => {
+                    self.quoted_string(quote)?;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Parse the data components of an item string, ex.
+    /// `[written_book_content={...},!minecraft:lore]`, into a compound.
+    ///
+    /// Removed components (prefixed by `'!'`) are skipped.
+    fn components(&mut self) -> Result<Tag, SyntaxError> {
+        self.expect('[', "'['")?;
+
+        let mut components: Vec<(Box<str>, Tag)> = vec![];
+
+        loop {
+            self.skip_white_space();
+            if self.peek() == Some(']') {
+                self.iter.next();
+                break;
+            }
+
+            let removed = self.peek() == Some('!');
+            if removed {
+                self.iter.next();
+            }
+
+            let name = self.take_while(is_resource_location);
+            if name.is_empty() {
+                return Err(self.unexpected("a component name"));
+            }
+
+            self.skip_white_space();
+            if !removed {
+                self.expect('=', "'='")?;
+                self.skip_white_space();
+                components.push((name.into(), self.value()?));
+                self.skip_white_space();
+            }
+
+            if self.separator(']', "',' or ']'")? {
+                break;
+            }
+        }
+
+        Ok(Tag::Compound(components.into()))
+    }
+
+    /// Parse any SNBT value.
+    fn value(&mut self) -> Result<Tag, SyntaxError> {
+        match self.peek() {
+            Some('{') => self.compound(),
+            Some('[') => self.list(),
+            Some(quote @ ('"' | '\'')) => {
+                self.iter.next();
+                Ok(Tag::String(self.quoted_string(quote)?))
+            }
+            Some(char) if is_unquoted(char) => Ok(unquoted(self.take_while(is_unquoted))),
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    /// Parse a compound, ex. `{title:"crafty_novels",'author':RemasteredArch}`.
+    fn compound(&mut self) -> Result<Tag, SyntaxError> {
+        self.expect('{', "'{'")?;
+
+        let mut compound: Vec<(Box<str>, Tag)> = vec![];
+
+        self.skip_white_space();
+        if self.peek() == Some('}') {
+            self.iter.next();
+            return Ok(Tag::Compound(compound.into()));
+        }
+
+        loop {
+            self.skip_white_space();
+            let key: Box<str> = match self.peek() {
+                Some(quote @ ('"' | '\'')) => {
+                    self.iter.next();
+                    self.quoted_string(quote)?
+                }
+                Some(char) if is_unquoted(char) => self.take_while(is_unquoted).into(),
+                _ => return Err(self.unexpected("a key")),
+            };
+
+            self.skip_white_space();
+            self.expect(':', "':'")?;
+            self.skip_white_space();
+            compound.push((key, self.value()?));
+            self.skip_white_space();
+
+            if self.separator('}', "',' or '}'")? {
+                break;
+            }
+        }
+
+        Ok(Tag::Compound(compound.into()))
+    }
+
+    /// Parse a list or a typed array, ex. `['page one', 'page two']` or `[I;1,2,3]`.
+    fn list(&mut self) -> Result<Tag, SyntaxError> {
+        self.expect('[', "'['")?;
+
+        let mut lookahead = self.iter.clone();
+        if let (Some((_, array_type @ ('B' | 'I' | 'L'))), Some((_, ';'))) =
+            (lookahead.next(), lookahead.next())
+        {
+            self.iter = lookahead;
+            return self.array(array_type);
+        }
+
+        let mut list: Vec<Tag> = vec![];
+
+        self.skip_white_space();
+        if self.peek() == Some(']') {
+            self.iter.next();
+            return Ok(Tag::List(list.into()));
+        }
+
+        loop {
+            self.skip_white_space();
+            list.push(self.value()?);
+            self.skip_white_space();
+
+            if self.separator(']', "',' or ']'")? {
+                break;
+            }
+        }
+
+        Ok(Tag::List(list.into()))
+    }
+
+    /// Parse the elements of a typed array, after the `"[B;"`, `"[I;"`, or `"[L;"`.
+    fn array(&mut self, array_type: char) -> Result<Tag, SyntaxError> {
+        let mut elements: Vec<Tag> = vec![];
+
+        self.skip_white_space();
+        if self.peek() == Some(']') {
+            self.iter.next();
+        } else {
+            loop {
+                self.skip_white_space();
+                let offset = self.offset();
+                let element = self.value()?;
+
+                if !matches!(
+                    (array_type, &element),
+                    ('B', Tag::Byte(_)) | ('I', Tag::Int(_)) | ('L', Tag::Long(_))
+                ) {
+                    return Err(SyntaxError::InvalidArrayElement(offset));
+                }
+                elements.push(element);
+                self.skip_white_space();
+
+                if self.separator(']', "',' or ']'")? {
+                    break;
+                }
+            }
+        }
+
+        let elements = elements.into_iter();
+        Ok(match array_type {
+            'B' => Tag::ByteArray(
+                elements
+                    .filter_map(|tag| match tag {
+                        Tag::Byte(byte) => Some(byte),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            'I' => Tag::IntArray(
+                elements
+                    .filter_map(|tag| match tag {
+                        Tag::Int(int) => Some(int),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            _ => Tag::LongArray(
+                elements
+                    .filter_map(|tag| match tag {
+                        Tag::Long(long) => Some(long),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Consume a `','` or the closing character `end` of a list or compound.
+    ///
+    /// Returns whether the closing character was found. A trailing `','` directly before `end`
+    /// is allowed.
+    fn separator(&mut self, end: char, expected: &'static str) -> Result<bool, SyntaxError> {
+        match self.peek() {
+            Some(',') => {
+                self.iter.next();
+                self.skip_white_space();
+
+                if self.peek() == Some(end) {
+                    self.iter.next();
+                    return Ok(true);
+                }
+                Ok(false)
+            }
+            Some(char) if char == end => {
+                self.iter.next();
+                Ok(true)
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    /// Parse the rest of a string after its opening `quote`, consuming the closing `quote`.
+    fn quoted_string(&mut self, quote: char) -> Result<Box<str>, SyntaxError> {
+        let mut string = String::new();
+
+        loop {
+            match self.next("the end of the string")? {
+                '\\' => string.push(self.escape()?),
+                char if char == quote => return Ok(string.into()),
+                char => string.push(char),
+            }
+        }
+    }
+
+    /// Parse an escape sequence after the `'\\'`, ex. `'n'` or `"u00A7"`.
+    ///
+    /// Like in JSON, a character outside of the Basic Multilingual Plane can also be written as a
+    /// UTF-16 surrogate pair, ex. `"uD83D\\uDE00"`.
+    fn escape(&mut self) -> Result<char, SyntaxError> {
+        let offset = self.offset() - 1;
+
+        let digits = match self.next("an escape sequence")? {
+            char @ ('\\' | '\'' | '"' | '/') => return Ok(char),
+            'n' => return Ok('\n'),
+            't' => return Ok('\t'),
+            'r' => return Ok('\r'),
+            'b' => return Ok('\u{8}'),
+            'f' => return Ok('\u{c}'),
+            's' => return Ok(' '),
+            'x' => 2,
+            'u' => 4,
+            'U' => 8,
+            _ => return Err(SyntaxError::InvalidEscape(offset)),
+        };
+
+        let mut code = self.hex_digits(digits, offset)?;
+
+        if digits == 4 && (0xD800..0xDC00).contains(&code) {
+            // The high half of a surrogate pair must be followed by the low half
+            if self.next("an escape sequence")? != '\\' || self.next("an escape sequence")? != 'u' {
+                return Err(SyntaxError::InvalidEscape(offset));
+            }
+
+            let low = self.hex_digits(4, offset)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(SyntaxError::InvalidEscape(offset));
+            }
+
+            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+        }
+
+        char::from_u32(code).ok_or(SyntaxError::InvalidEscape(offset))
+    }
+
+    /// Parse `digits` hexadecimal digits of the escape sequence that starts at `offset`.
+    fn hex_digits(&mut self, digits: usize, offset: usize) -> Result<u32, SyntaxError> {
+        let mut code = 0;
+        for _ in 0..digits {
+            // `u32::from_str_radix` would also accept a leading `'+'`
+            let digit = self
+                .next("an escape sequence")?
+                .to_digit(16)
+                .ok_or(SyntaxError::InvalidEscape(offset))?;
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+}
+
+/// Convert an unquoted string into a number or boolean if it looks like one, otherwise keep it as
+/// a string.
+///
+/// Numbers can carry a type suffix (`b`, `s`, `l`, `f`, `d`), otherwise they are integers or, if
+/// they contain a decimal point or exponent, doubles.
+fn unquoted(string: &str) -> Tag {
+    /// Parse `$number` as `$type` and wrap it in the [`Tag`] variant `$variant`, if possible.
+    macro_rules! number {
+        ($number:expr => $variant:ident) => {
+            $number.parse().ok().map(Tag::$variant)
+        };
+    }
+
+    match string {
+        "true" => return Tag::Byte(1),
+        "false" => return Tag::Byte(0),
+        _ => (),
+    }
+
+    let looks_numeric = string
+        .starts_with(|char: char| matches!(char, '0'..='9' | '-' | '+' | '.'))
+        && string.contains(|char: char| char.is_ascii_digit());
+    if !looks_numeric {
+        return Tag::String(string.into());
+    }
+
+    let (number, suffix) = string.split_at(string.len() - 1);
+    let tag = match suffix {
+        "b" | "B" => number!(number => Byte),
+        "s" | "S" => number!(number => Short),
+        "l" | "L" => number!(number => Long),
+        "f" | "F" => number!(number => Float),
+        "d" | "D" => number!(number => Double),
+        _ if string.contains(['.', 'e', 'E']) => number!(string => Double),
+        _ => number!(string => Int),
+    };
+
+    tag.unwrap_or_else(|| Tag::String(string.into()))
+}
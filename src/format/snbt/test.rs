@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for parsing the [SNBT][`super::Snbt`] format.
+
+use super::{parse, Snbt, SyntaxError, TokenizeError};
+use crate::{
+    format::nbt::{BookError, Tag},
+    syntax::{Metadata, Token, TokenList},
+    Tokenize,
+};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Insert a [`Token::Format`] with the given variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+/// The [`TokenList`] that every book in [`test_book_forms`] is expected to produce.
+fn expected_book() -> TokenList {
+    use Token::{LineBreak, ParagraphBreak, Space, ThematicBreak};
+
+    TokenList::new_from_boxed(
+        Box::new([
+            Metadata::Title("crafty_novels".into()),
+            Metadata::Author("RemasteredArch".into()),
+        ]),
+        Box::new([
+            ThematicBreak,
+            text!("Page"),
+            Space,
+            text!("one"),
+            LineBreak,
+            ParagraphBreak,
+            text!("Italic:"),
+            format!(Italic),
+            Space,
+            text!("text"),
+            Space,
+            format!(Reset),
+            text!("reset"),
+            LineBreak,
+            ThematicBreak,
+            text!("Page"),
+            Space,
+            text!("two"),
+            LineBreak,
+        ]),
+    )
+}
+
+#[test]
+fn test_book_forms() -> Result {
+    let inputs = [
+        // Legacy `/give` command
+        r#"/give @p written_book{title:"crafty_novels",author:"RemasteredArch",pages:['Page one\n\nItalic:§o text §rreset','Page two']} 1"#,
+        // Legacy `/give` command with a target selector and a namespaced ID
+        r#"give @a[name="Remastered Arch",limit=1] minecraft:written_book{title:crafty_novels,author:RemasteredArch,pages:["Page one\n\nItalic:\u00A7o text \u00A7rreset","Page two"]}"#,
+        // Data component `/give` command
+        r#"/give RemasteredArch written_book[written_book_content={title:{raw:"crafty_novels"},author:"RemasteredArch",pages:[{raw:'Page one\n\nItalic:§o text §rreset'},'Page two']}]"#,
+        // Data component item string with a removed component
+        r#"minecraft:written_book[!minecraft:lore, minecraft:written_book_content = {
+            title: "crafty_novels",
+            author: "RemasteredArch",
+            pages: ['Page one\n\nItalic:§o text §rreset', 'Page two',],
+        }]"#,
+        // Legacy `/give` command with JSON text component pages
+        r#"/give @p written_book{title:"crafty_novels",author:"RemasteredArch",pages:['["Page one\\n\\n",{"text":"Italic:"},{"text":" text ","italic":true},"reset"]','"Page two"']}"#,
+        // Data component `/give` command with SNBT text component pages (1.21.5+)
+        r#"/give @p written_book[written_book_content={title:"crafty_novels",author:"RemasteredArch",pages:[{raw:{text:"Page one\n\nItalic:",extra:[{text:" text ",italic:true},"reset"]}},{text:"Page two"}]}]"#,
+        // Legacy item stack
+        r#"{id:"minecraft:written_book",Count:1b,tag:{title:"crafty_novels",author:"RemasteredArch",pages:['Page one\n\nItalic:§o text §rreset','Page two']}}"#,
+        // Data component item stack
+        r#"{id:"minecraft:written_book",count:1,components:{"minecraft:written_book_content":{title:"crafty_novels",author:"RemasteredArch",generation:0,pages:['Page one\n\nItalic:§o text §rreset','Page two']}}}"#,
+        // Book contents
+        r#"{title:"crafty_novels",author:"RemasteredArch",pages:['Page one\n\nItalic:§o text §rreset','Page two']}"#,
+    ];
+
+    for input in inputs {
+        assert_eq!(Snbt::tokenize_string(input)?, expected_book(), "{input}");
+        assert_eq!(
+            Snbt::tokenize_reader(input.as_bytes())?,
+            expected_book(),
+            "{input}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_values() -> Result {
+    /// Compare the item stack parsed out of `$input` with the `$expects`.
+    macro_rules! test {
+        ( $( $input:expr => $expects:expr );+ ; ) => {
+            $(
+                assert_eq!(parse::item($input)?, $expects);
+            )+
+        };
+    }
+
+    /// Build a [`Tag::Compound`] out of `key => value` pairs.
+    macro_rules! compound {
+        ( $( $key:expr => $value:expr ),* $(,)? ) => {
+            Tag::Compound(Box::new([ $( ($key.into(), $value) ),* ]))
+        };
+    }
+
+    test!(
+        "{}" => compound!();
+        "{a:1b,b:2s,c:3,d:4L,e:1.5f,f:2.5d,g:3.5,h:1e3}" => compound!(
+            "a" => Tag::Byte(1),
+            "b" => Tag::Short(2),
+            "c" => Tag::Int(3),
+            "d" => Tag::Long(4),
+            "e" => Tag::Float(1.5),
+            "f" => Tag::Double(2.5),
+            "g" => Tag::Double(3.5),
+            "h" => Tag::Double(1000.0),
+        );
+        "{a:true,b:false,c:stone,d:-5,e:300b}" => compound!(
+            "a" => Tag::Byte(1),
+            "b" => Tag::Byte(0),
+            "c" => Tag::String("stone".into()),
+            "d" => Tag::Int(-5),
+            "e" => Tag::String("300b".into()),
+        );
+        r#"{"quoted key":'it\'s',b:"say \"hi\"",c:"\\",d:"\u00A7l"}"# => compound!(
+            "quoted key" => Tag::String("it's".into()),
+            "b" => Tag::String(r#"say "hi""#.into()),
+            "c" => Tag::String(r"\".into()),
+            "d" => Tag::String("§l".into()),
+        );
+        r#"{a:"a\/b",b:'\uD83D\uDE00',c:"\U0001F600"}"# => compound!(
+            "a" => Tag::String("a/b".into()),
+            "b" => Tag::String("😀".into()),
+            "c" => Tag::String("😀".into()),
+        );
+        "{ a : [ 1 , 2 , ] , b:[B;1b,2b], c:[I;], d:[L;5L] }" => compound!(
+            "a" => Tag::List(Box::new([Tag::Int(1), Tag::Int(2)])),
+            "b" => Tag::ByteArray(Box::new([1, 2])),
+            "c" => Tag::IntArray(Box::new([])),
+            "d" => Tag::LongArray(Box::new([5])),
+        );
+        "stone 64" => compound!(
+            "id" => Tag::String("stone".into()),
+            "count" => Tag::Int(64),
+        );
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    /// Assert that tokenizing `$input` fails with an error matching `$pattern`.
+    macro_rules! fails {
+        ( $( $input:expr => $pattern:pat ),+ $(,)? ) => {
+            $(
+                let result = Snbt::tokenize_string($input);
+                assert!(matches!(result, Err($pattern)), "{}: {result:?}", $input);
+            )+
+        };
+    }
+
+    fails!(
+        "{title:\"a\",author:\"b\"" => TokenizeError::Syntax(SyntaxError::UnexpectedEnd(_)),
+        "{title \"a\"}" => TokenizeError::Syntax(SyntaxError::UnexpectedCharacter { found: '"', offset: 7, .. }),
+        "{title:'a\\q'}" => TokenizeError::Syntax(SyntaxError::InvalidEscape(9)),
+        // Surrogates must come in pairs
+        "{title:'\\uD83D'}" => TokenizeError::Syntax(SyntaxError::InvalidEscape(8)),
+        "{title:'\\uD83D\\u0041'}" => TokenizeError::Syntax(SyntaxError::InvalidEscape(8)),
+        "{title:'\\uDE00'}" => TokenizeError::Syntax(SyntaxError::InvalidEscape(8)),
+        // Only hexadecimal digits, without a sign
+        "{title:'\\u+041'}" => TokenizeError::Syntax(SyntaxError::InvalidEscape(8)),
+        "{title:'\\U+001F600'}" => TokenizeError::Syntax(SyntaxError::InvalidEscape(8)),
+        "{a:[I;1,2b]}" => TokenizeError::Syntax(SyntaxError::InvalidArrayElement(8)),
+        "{title:a,author:b} extra" => TokenizeError::Syntax(SyntaxError::TrailingInput(19)),
+        "/give @p" => TokenizeError::Syntax(SyntaxError::UnexpectedEnd(_)),
+        "/give @p stone" => TokenizeError::Book(BookError::NotABook(_)),
+        "/give @p written_book" => TokenizeError::Book(BookError::MissingField(_)),
+        "{author:b,pages:[]}" => TokenizeError::Book(BookError::MissingField("title")),
+        "{title:a,author:b,pages:'page'}" => TokenizeError::Book(BookError::UnexpectedType { field: "pages", .. }),
+        "{title:a,author:b,pages:['§']}" => TokenizeError::Book(BookError::Conversion(_)),
+    );
+}
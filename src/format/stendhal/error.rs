@@ -19,22 +19,51 @@
 //!
 //! See [`TokenizeError`].
 
-use crate::syntax::ConversionError;
+use crate::syntax::{ConversionError, SourceSpan};
 
 /// All the errors that could occur while tokenizing a Stendhal document.
 #[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
 #[derive(thiserror::Error, Debug)]
 pub enum TokenizeError {
-    /// Encountered when trying to convert invalid syntax.
-    #[error("could not perform conversion: {0}")]
-    Conversion(#[from] ConversionError),
+    /// Encountered when trying to convert invalid syntax, ex. an unknown format code.
+    #[error("{span}: could not perform conversion: {error}")]
+    Conversion {
+        error: ConversionError,
+        /// Where the invalid syntax is in the input.
+        span: SourceSpan,
+    },
     /// Encountered when trying to parse an frontmatter that is incomplete or entirely missing.
     #[error("frontmatter is not present or incomplete")]
     IncompleteOrMissingFrontmatter,
     /// Encountered when an iterator ends before its consumer is finished.
     #[error("expected document to be longer")]
     UnexpectedEndOfDocument,
-    /// Encoutered when an I/O action fails in some way.
+    /// Encountered when an I/O action fails in some way.
     #[error("could not perform I/O action: {0}")]
     Io(#[from] std::io::Error),
 }
+
+impl TokenizeError {
+    /// Where in the input the error is, if it belongs to one place.
+    #[must_use]
+    pub const fn span(&self) -> Option<SourceSpan> {
+        match self {
+            Self::Conversion { span, .. } => Some(*span),
+            Self::IncompleteOrMissingFrontmatter | Self::UnexpectedEndOfDocument | Self::Io(_) => {
+                None
+            }
+        }
+    }
+
+    /// Renders the error for a person to read, showing where it is in `source` if it belongs to
+    /// one place (see [`SourceSpan::render`]).
+    ///
+    /// `source` should be the input that failed to tokenize.
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        match self {
+            Self::Conversion { error, span } => span.render(source, &error.to_string()),
+            error => format!("error: {error}\n"),
+        }
+    }
+}
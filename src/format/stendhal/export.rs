@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The actual, under the hood, token-by-token writing for the [Stendhal][`super::Stendhal`]
+//! format.
+//!
+//! The inverse of [`super::parse`].
+
+use crate::{
+    syntax::{minecraft::Format, Metadata, Token},
+    writer::Utf8Writer,
+};
+use std::io::{Result, Write};
+
+/// Write the frontmatter of a work into the output.
+///
+/// The title, author, and [custom fields][`Metadata::Custom`] are written in the order they
+/// appear in. If the title or author are missing, empty fields are written for them first, as the
+/// Stendhal mod expects them. Other metadata cannot be represented in the frontmatter, and is
+/// ignored.
+///
+/// # Errors
+///
+/// - [`std::io::Error`] if it cannot write into `output`
+pub fn frontmatter<W: Write>(output: &mut Utf8Writer<W>, metadata: &[Metadata]) -> Result<()> {
+    if !metadata.iter().any(|m| matches!(m, Metadata::Title(_))) {
+        output.write_str("title: \n")?;
+    }
+    if !metadata.iter().any(|m| matches!(m, Metadata::Author(_))) {
+        output.write_str("author: \n")?;
+    }
+
+    for metadata in metadata {
+        match metadata {
+            Metadata::Title(t) => writeln!(output, "title: {t}")?,
+            Metadata::Author(a) => writeln!(output, "author: {a}")?,
+            Metadata::Custom { key, value } => writeln!(output, "{key}: {value}")?,
+            _ => (),
+        }
+    }
+
+    output.write_str("pages:\n")
+}
+
+/// Writes the lines of a work, tracking where the parser would implicitly insert tokens.
+pub struct Lines<'w, W: Write> {
+    output: &'w mut Utf8Writer<W>,
+    /// Whether a [`Token::ThematicBreak`] has been seen, but its `"#- "` has not been written.
+    page_start: bool,
+    /// Whether anything has been written onto the current line.
+    line_open: bool,
+    /// Whether this line has a formatting code yet to be reset, mirroring [`super::parse`].
+    trailing_formatting: bool,
+}
+
+impl<'w, W: Write> Lines<'w, W> {
+    /// Create a new [`Lines`] that writes into `output`.
+    pub const fn new(output: &'w mut Utf8Writer<W>) -> Self {
+        Self {
+            output,
+            page_start: false,
+            line_open: false,
+            trailing_formatting: false,
+        }
+    }
+
+    /// Write a token into the output.
+    ///
+    /// `next` is the token following `token`, if any, which is used to leave out the
+    /// [`Format::Reset`] that the parser inserts at the end of lines on its own.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn token(&mut self, token: &Token, next: Option<&Token>) -> Result<()> {
+        match token {
+            Token::Text(text) => {
+                self.open_line()?;
+                self.output.write_str(text)?;
+            }
+            Token::Space => {
+                self.open_line()?;
+                self.output.write_char(' ')?;
+            }
+            Token::Format(Format::Reset)
+                if self.trailing_formatting && matches!(next, None | Some(Token::LineBreak)) =>
+            {
+                self.trailing_formatting = false;
+            }
+            Token::Format(format) => {
+                // Stendhal has no syntax for fonts or shadow colors
+                let Ok(code) = char::try_from(*format) else {
+                    return Ok(());
+                };
+
+                self.open_line()?;
+                self.output.write_char('§')?;
+                self.output.write_char(code)?;
+                self.trailing_formatting = *format != Format::Reset;
+            }
+            // Stendhal has no syntax for events, and content is filled in before exporting
+            Token::Click(_) | Token::Hover(_) | Token::Insertion(_) | Token::Content(_) => (),
+            Token::LineBreak => {
+                self.open_line()?;
+                self.close_line()?;
+            }
+            Token::ParagraphBreak => {
+                self.end_line()?;
+                self.output.write_char('\n')?;
+            }
+            Token::ThematicBreak => {
+                self.end_line()?;
+                self.page_start = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finish the last line, if it has not been finished already.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into the output
+    pub fn finish(mut self) -> Result<()> {
+        self.end_line()?;
+        self.output.flush()
+    }
+
+    /// Prepare to write onto the current line, writing the `"#- "` of a new page if necessary.
+    fn open_line(&mut self) -> Result<()> {
+        if self.page_start {
+            self.output.write_str("#- ")?;
+            self.page_start = false;
+        }
+        self.line_open = true;
+
+        Ok(())
+    }
+
+    /// End the current line.
+    fn close_line(&mut self) -> Result<()> {
+        self.output.write_char('\n')?;
+        self.line_open = false;
+        self.trailing_formatting = false;
+
+        Ok(())
+    }
+
+    /// End the current line if anything is waiting to be written onto it, like the start of a
+    /// page.
+    fn end_line(&mut self) -> Result<()> {
+        if self.page_start || self.line_open {
+            self.open_line()?;
+            self.close_line()?;
+        }
+
+        Ok(())
+    }
+}
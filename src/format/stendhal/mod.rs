@@ -15,7 +15,7 @@
 // You should have received a copy of the GNU Affero General Public License along with
 // crafty_novels. If not, see <https://www.gnu.org/licenses/>.
 
-//! Parsing for [Stendhal] format.
+//! Parsing and exporting for [Stendhal] format.
 //! See [`Stendhal`] for more details.
 //!
 //! [Stendhal]: https://modrinth.com/mod/stendhal
@@ -62,14 +62,17 @@
 //! ```
 
 use crate::{
-    syntax::{Token, TokenList},
-    Tokenize,
+    resolve::Untranslated,
+    syntax::{SourceSpan, Token, TokenList},
+    writer::Utf8Writer,
+    Export, Tokenize,
 };
 pub use error::TokenizeError;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufReader, Read, Write};
 
 mod error;
-mod parse;
+mod export;
+pub(super) mod parse;
 #[cfg(test)]
 mod test;
 
@@ -79,11 +82,12 @@ mod test;
 ///
 /// *Convention: `"a string"` `'a single character'` (the `"` or `'` are not necessarily present).*
 ///
-/// The first three lines make up the frontmatter:
-/// 1. Starts with `"title: "`, the rest is considered the title of the book
-/// 2. Starts with `"author: "`, the rest is considered the author's name, which is probably
-///    whoever exported the book
-/// 3. Starts and ends with `"pages:"`
+/// The first lines make up the frontmatter, a block of `"key: value"` fields in any order:
+/// - `"title: "`, the rest is considered the title of the book
+/// - `"author: "`, the rest is considered the author's name, which is probably whoever exported
+///   the book
+/// - Any other key is kept as a [custom field][`crate::syntax::Metadata::Custom`]
+/// - `"pages:"` ends the frontmatter
 ///
 /// For the rest of the book:
 /// - Any line that starts with `"#- "` is considered the start of a new page, and the text
@@ -93,8 +97,73 @@ mod test;
 ///     - The resulting format continues until the next line ending or
 ///       [reset][`crate::syntax::minecraft::Format::Reset`] format code
 ///
+/// # Exporting
+///
+/// Exporting writes the same format back out, so that books edited outside of the game can be
+/// imported again with the Stendhal mod. For any [`TokenList`] produced by
+/// [`Stendhal::tokenize_string`] from a frontmatter with a title and author, exporting it and
+/// tokenizing the result gives back the same [`TokenList`].
+///
+/// - Only the [title][`crate::syntax::Metadata::Title`],
+///   [author][`crate::syntax::Metadata::Author`], and
+///   [custom fields][`crate::syntax::Metadata::Custom`] are written into the frontmatter, and an
+///   empty title or author is written if either is missing
+/// - [Resets][`crate::syntax::minecraft::Format::Reset`] that the parser would insert at the end
+///   of a line on its own are left out
+/// - Text is written as-is, so text containing `'§'` or line endings will not be tokenized the same
+///
 /// [Stendhal]: https://modrinth.com/mod/stendhal
-pub struct Stendhal;
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Stendhal {
+    lenient: bool,
+    format_codes: FormatCodeRecovery,
+    spans: bool,
+}
+
+impl Stendhal {
+    /// Create a new [`Stendhal`] parser with the default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            lenient: false,
+            format_codes: FormatCodeRecovery::Keep,
+            spans: false,
+        }
+    }
+
+    /// Whether to recover from any problems instead of failing (`false`, the default).
+    ///
+    /// When set, [`Tokenize`] and [`Stendhal::tokenize_with_spans`] parse like
+    /// [`Stendhal::tokenize_lenient`] and leave out its warnings, so they only fail if the input
+    /// cannot be read.
+    #[must_use]
+    pub const fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// What to do with an invalid or missing format code when [lenient][`Self::lenient`]
+    /// ([`FormatCodeRecovery::Keep`] by default).
+    #[must_use]
+    pub const fn format_code_recovery(mut self, format_codes: FormatCodeRecovery) -> Self {
+        self.format_codes = format_codes;
+        self
+    }
+
+    /// Whether [`Stendhal::tokenize_lenient`] keeps the [`SourceSpan`] of each token, returned by
+    /// [`Recovered::spans`] (`false`, the default).
+    #[must_use]
+    pub const fn spans(mut self, spans: bool) -> Self {
+        self.spans = spans;
+        self
+    }
+}
+
+impl Default for Stendhal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Tokenize for Stendhal {
     type Error = TokenizeError;
@@ -103,59 +172,293 @@ impl Tokenize for Stendhal {
     ///
     /// # Errors
     ///
-    /// - [`crate::syntax::ConversionError::MissingFormatCode`] if it encounters a `'§'` that isn't
-    ///   followed by another character
-    /// - [`crate::syntax::ConversionError::NoSuchFormatCode`] if it encounters a `'§'` isn't
-    ///   followed by a valid [`Format`][`crate::syntax::minecraft::Format`] character
+    /// Unless [lenient][`Stendhal::lenient`]:
+    ///
+    /// - [`TokenizeError::Conversion`] holding the location of the error, and:
+    ///     - [`crate::syntax::ConversionError::MissingFormatCode`] if it encounters a `'§'` that
+    ///       isn't followed by another character
+    ///     - [`crate::syntax::ConversionError::NoSuchFormatCode`] if it encounters a `'§'` isn't
+    ///       followed by a valid [`Format`][`crate::syntax::minecraft::Format`] character
+    /// - [`TokenizeError::IncompleteOrMissingFrontmatter`] if `input` ends before the frontmatter
+    ///   parsing is finished, or a line before `"pages:"` is not a `"key: value"` field
+    fn tokenize_from_string(&self, input: &str) -> Result<TokenList, Self::Error> {
+        self.tokenize_with_spans(input).map(|(tokens, _)| tokens)
+    }
+
+    /// Parse a file in the Stendhal format into an abstract syntax vector.
+    ///
+    /// # Errors
+    ///
+    /// Unless [lenient][`Stendhal::lenient`]:
+    ///
+    /// - [`TokenizeError::Conversion`] holding the location of the error, and:
+    ///     - [`crate::syntax::ConversionError::MissingFormatCode`] if it encounters a `'§'` that
+    ///       isn't followed by another character
+    ///     - [`crate::syntax::ConversionError::NoSuchFormatCode`] if it encounters a `'§'` isn't
+    ///       followed by a valid [`Format`][`crate::syntax::minecraft::Format`] character
     /// - [`TokenizeError::IncompleteOrMissingFrontmatter`] if `input` ends before the frontmatter
-    ///   parsing is finished
-    fn tokenize_string(input: &str) -> Result<TokenList, Self::Error> {
-        let mut input = input.lines();
+    ///   parsing is finished, or a line before `"pages:"` is not a `"key: value"` field
+    ///
+    /// Always:
+    ///
+    /// - [`TokenizeError::Io`] if `input` cannot be read, or is not valid UTF-8
+    fn tokenize_from_reader(&self, input: impl Read) -> Result<TokenList, Self::Error> {
+        let mut string = String::new();
+        BufReader::new(input).read_to_string(&mut string)?;
+
+        self.tokenize_from_string(&string)
+    }
+}
+
+impl Stendhal {
+    /// Parse a string in the Stendhal format into an abstract syntax vector, like
+    /// [`Stendhal::tokenize_string`], alongside the [`SourceSpan`] of `input` that each token
+    /// came from.
+    ///
+    /// The spans are in the same order as the tokens. Tokens that the parser inserts on its own,
+    /// like the [`Token::LineBreak`] at the end of each line, have empty spans.
+    ///
+    /// # Errors
+    ///
+    /// See [`Stendhal::tokenize_string`]. [`TokenizeError::span`] returns where the error is, and
+    /// [`TokenizeError::render`] shows it for a person to read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crafty_novels::import::Stendhal;
+    ///
+    /// let input = "title: crafty_novels
+    /// author: RemasteredArch
+    /// pages:
+    /// #- Some §zRED text";
+    ///
+    /// let error = Stendhal::new().tokenize_with_spans(input).unwrap_err();
+    /// let span = error.span().unwrap();
+    ///
+    /// assert_eq!((span.line(), span.column()), (4, 9));
+    /// assert_eq!(&input[span.range()], "§z");
+    /// assert_eq!(
+    ///     error.render(input),
+    ///     concat!(
+    ///         "error: no such format code 'z'\n",
+    ///         " --> line 4, column 9\n",
+    ///         "  |\n",
+    ///         "4 | #- Some §zRED text\n",
+    ///         "  |         ^^\n",
+    ///     )
+    /// );
+    /// ```
+    pub fn tokenize_with_spans(
+        &self,
+        input: &str,
+    ) -> Result<(TokenList, Box<[SourceSpan]>), TokenizeError> {
+        if self.lenient {
+            let (tokens, spans, _) = self.recover(input);
+            return Ok((tokens, spans.into()));
+        }
+
+        let mut lines = lines(input);
         let mut tokens: Vec<Token> = vec![];
+        let mut spans: Vec<SourceSpan> = vec![];
 
         // Could be recovered by capturing the state of `input` before calling, then reverting on
         // certain errors.
-        let metadata = parse::frontmatter(&mut input)?;
+        let metadata = parse::frontmatter(&mut lines.by_ref().map(|(line, _)| line))?;
 
-        for line in input {
-            parse::line(&mut tokens, line)?;
+        for (line, start) in lines {
+            parse::line(&mut tokens, &mut spans, line, start, None)?;
         }
 
-        Ok(TokenList::new_from_boxed(metadata, tokens.into()))
+        Ok((
+            TokenList::new_from_boxed(metadata, tokens.into()),
+            spans.into(),
+        ))
     }
+}
 
-    /// Parse a file in the Stendhal format into an abstract syntax vector.
+impl Stendhal {
+    /// Parse a string in the Stendhal format into an abstract syntax vector, recovering from any
+    /// problems instead of failing.
     ///
-    /// # Errors
+    /// - Invalid or missing format codes are kept as text or dropped, as the
+    ///   [format code recovery][`Self::format_code_recovery`] says
+    /// - If the frontmatter does not end with `"pages:"`, the fields before the first line that is
+    ///   not a `"key: value"` field are kept, and the rest is read as part of the book
     ///
-    /// - [`crate::syntax::ConversionError::MissingFormatCode`] if it encounters a `'§'` that isn't
-    ///   followed by another character
-    /// - [`crate::syntax::ConversionError::NoSuchFormatCode`] if it encounters a `'§'` isn't
-    ///   followed by a valid [`Format`][`crate::syntax::minecraft::Format`] character
-    /// - [`TokenizeError::IncompleteOrMissingFrontmatter`] if `input` ends before the frontmatter
-    ///   parsing is finished
-    /// - [`TokenizeError::Io`] if the a line from `input` is an I/O error of some kind
-    fn tokenize_reader(input: impl Read) -> Result<TokenList, Self::Error> {
-        /// Get a refrence to the next element in `$iter` or return [`Error::UnexpectedEndOfIter`]
-        /// or the encapsulated [`Error::Io`].
-        macro_rules! next {
-            ($iter:expr) => {
-                &$iter
-                    .next()
-                    .ok_or(Self::Error::IncompleteOrMissingFrontmatter)??
-            };
+    /// Every problem is returned alongside the tokens, as the error that
+    /// [`Stendhal::tokenize_string`] would have failed with.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crafty_novels::{import::Stendhal, syntax::Token};
+    ///
+    /// let input = "#- Price: 5§";
+    ///
+    /// let recovered = Stendhal::new().tokenize_lenient(input);
+    /// assert_eq!(
+    ///     recovered.tokens().tokens_as_slice(),
+    ///     [
+    ///         Token::ThematicBreak,
+    ///         Token::Text("Price:".into()),
+    ///         Token::Space,
+    ///         Token::Text("5§".into()),
+    ///         Token::LineBreak,
+    ///     ]
+    /// );
+    ///
+    /// // The frontmatter is missing, and so is the format code
+    /// assert_eq!(recovered.warnings().len(), 2);
+    /// ```
+    #[must_use]
+    pub fn tokenize_lenient(&self, input: &str) -> Recovered {
+        let (tokens, spans, warnings) = self.recover(input);
+
+        Recovered {
+            tokens,
+            spans: if self.spans {
+                spans.into()
+            } else {
+                Box::new([])
+            },
+            warnings: warnings.into(),
         }
+    }
 
-        let mut iter = BufReader::new(input).lines();
+    /// Parse `input` like [`Stendhal::tokenize_lenient`], returning the tokens, their spans, and
+    /// every problem.
+    fn recover(self, input: &str) -> (TokenList, Vec<SourceSpan>, Vec<TokenizeError>) {
+        let mut lines = lines(input).peekable();
         let mut tokens: Vec<Token> = vec![];
+        let mut spans: Vec<SourceSpan> = vec![];
+        let mut warnings = vec![];
+
+        let metadata = parse::lenient_frontmatter(&mut lines, &mut warnings);
+
+        let mut recover = parse::Recover {
+            format_codes: self.format_codes,
+            warnings: &mut warnings,
+        };
+        for (line, start) in lines {
+            // Recovering parses do not fail, but if one does, it is just another problem
+            if let Err(error) =
+                parse::line(&mut tokens, &mut spans, line, start, Some(&mut recover))
+            {
+                recover.warnings.push(error);
+            }
+        }
+
+        (
+            TokenList::new_from_boxed(metadata, tokens.into()),
+            spans,
+            warnings,
+        )
+    }
+}
+
+/// What a [lenient][`Stendhal::lenient`] [`Stendhal`] parser does with an invalid or missing
+/// format code.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FormatCodeRecovery {
+    /// Keep the `'§'` and whatever follows it as text, ex. `"§z"`.
+    #[default]
+    Keep,
+    /// Leave out the `'§'` and whatever follows it, like Minecraft does when showing the text.
+    Drop,
+}
+
+/// A best effort [`TokenList`], and the problems found while parsing it.
+///
+/// See [`Stendhal::tokenize_lenient`].
+#[derive(Debug)]
+pub struct Recovered {
+    /// The parsed work.
+    tokens: TokenList,
+    /// The [`SourceSpan`] of each token, if [`Stendhal::spans`] was set.
+    spans: Box<[SourceSpan]>,
+    /// Every problem, in the order they were found.
+    warnings: Box<[TokenizeError]>,
+}
+
+impl Recovered {
+    /// The parsed work.
+    #[must_use]
+    pub const fn tokens(&self) -> &TokenList {
+        &self.tokens
+    }
+
+    /// Returns the parsed work.
+    #[must_use]
+    pub fn into_tokens(self) -> TokenList {
+        self.tokens
+    }
+
+    /// The [`SourceSpan`] of each token, in the same order as the tokens, like
+    /// [`Stendhal::tokenize_with_spans`].
+    ///
+    /// Empty unless the parser was set to keep [spans][`Stendhal::spans`].
+    #[must_use]
+    pub const fn spans(&self) -> &[SourceSpan] {
+        &self.spans
+    }
+
+    /// Every problem, in the order they were found, as the error that
+    /// [`Stendhal::tokenize_string`] would have failed with.
+    ///
+    /// Use [`TokenizeError::render`] to show them to a person.
+    #[must_use]
+    pub const fn warnings(&self) -> &[TokenizeError] {
+        &self.warnings
+    }
+
+    /// Whether the work parsed without any problems.
+    #[must_use]
+    pub const fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+/// Returns an iterator over the lines of `input`, without their line endings, alongside where
+/// each starts.
+fn lines(input: &str) -> impl Iterator<Item = (&str, SourceSpan)> {
+    input
+        .split_inclusive('\n')
+        .enumerate()
+        .scan(0, |offset, (index, line)| {
+            let start = SourceSpan::new(*offset, 0, index + 1, 1);
+            *offset += line.len();
+
+            let line = line
+                .strip_suffix('\n')
+                .map_or(line, |line| line.strip_suffix('\r').unwrap_or(line));
+            Some((line, start))
+        })
+}
+
+impl Export for Stendhal {
+    /// Parse a given abstract syntax vector into the Stendhal format, then output that into a
+    /// writer, like a [`std::fs::File`].
+    ///
+    /// Guaranteed to only write valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into `output`
+    fn export_to_writer(&self, tokens: &TokenList, output: &mut impl Write) -> std::io::Result<()> {
+        // Format codes already follow Minecraft's rules, so styles are written as they are, without
+        // `TokenList::resolve_styles`
+        let tokens = tokens.resolve(&Untranslated);
+        let mut writer = Utf8Writer::new(output);
 
-        let chunk: [&str; 3] = [next!(iter), next!(iter), next!(iter)];
-        let metadata = parse::frontmatter(&mut chunk.into_iter())?;
+        export::frontmatter(&mut writer, tokens.metadata_as_slice())?;
 
-        for line in iter {
-            parse::line(&mut tokens, &line?)?;
+        let mut lines = export::Lines::new(&mut writer);
+        let mut iter = tokens.tokens_as_slice().iter().peekable();
+        while let Some(token) = iter.next() {
+            lines.token(token, iter.peek().copied())?;
         }
 
-        Ok(TokenList::new_from_boxed(metadata, tokens.into()))
+        lines.finish()
     }
 }
@@ -17,74 +17,218 @@
 
 //! The actual, under the hood, line-by-line parsing for the [Stendhal][`super::Stendhal`] format.
 
-use super::TokenizeError;
-use crate::syntax::{minecraft::Format, ConversionError, Metadata, Token};
+use super::{FormatCodeRecovery, TokenizeError};
+use crate::syntax::{minecraft::Format, ConversionError, Metadata, SourceSpan, Token};
+
+/// How a lenient parse recovers from invalid format codes, and where it collects them.
+pub struct Recover<'w> {
+    /// What to do with an invalid or missing format code.
+    pub format_codes: FormatCodeRecovery,
+    /// Every problem found so far.
+    pub warnings: &'w mut Vec<TokenizeError>,
+}
 
 /// Parse a line in the Stendhal format into an abstract syntax vector.
 ///
 /// If a line is empty, it is considered a paragraph break.
 ///
+/// `start` is where the line starts in the input, and the [`SourceSpan`] of each token is pushed
+/// into `spans`. Tokens that the parser inserts on its own have empty spans.
+///
+/// If `recover` is present, invalid format codes are recovered from as it describes, and this
+/// never fails.
+///
 /// # Errors
 ///
-/// - [`ConversionError::MissingFormatCode`] if `'§'` isn't followed by another character
-/// - [`ConversionError::NoSuchFormatCode`] if `'§'` isn't followed by a valid [`Format`] character
-pub fn line(output: &mut Vec<Token>, line: &str) -> Result<(), ConversionError> {
-    /// Flush the current word stack into a text node.
-    fn flush(output: &mut Vec<Token>, word_stack: &mut Vec<char>) {
-        if !word_stack.is_empty() {
-            output.push((word_stack).into());
-        }
-    }
-
+/// - [`TokenizeError::Conversion`], holding:
+///     - [`ConversionError::MissingFormatCode`] if `'§'` isn't followed by another character
+///     - [`ConversionError::NoSuchFormatCode`] if `'§'` isn't followed by a valid [`Format`]
+///       character
+pub fn line(
+    output: &mut Vec<Token>,
+    spans: &mut Vec<SourceSpan>,
+    line: &str,
+    start: SourceSpan,
+    recover: Option<&mut Recover<'_>>,
+) -> Result<(), TokenizeError> {
     if line.is_empty() {
         output.push(Token::ParagraphBreak);
+        spans.push(start);
         return Ok(());
     }
 
-    let line = start_of_page(output, line);
+    let (line, start) = start_of_page(output, spans, line, start);
+
+    formatted(output, spans, line, start, recover)
+        .map_err(|(error, span)| TokenizeError::Conversion { error, span })
+}
+
+/// Parse a line of text containing `'§'` format codes into an abstract syntax vector, ending it
+/// with a [`Token::LineBreak`].
+///
+/// Unlike [`line`], this does not look for page starts or paragraph breaks, so it can be used for
+/// any text that uses Minecraft's format codes.
+///
+/// # Errors
+///
+/// - [`ConversionError::MissingFormatCode`] if `'§'` isn't followed by another character
+/// - [`ConversionError::NoSuchFormatCode`] if `'§'` isn't followed by a valid [`Format`] character
+pub fn formatted_line(output: &mut Vec<Token>, line: &str) -> Result<(), ConversionError> {
+    formatted(output, &mut vec![], line, SourceSpan::new(0, 0, 1, 1), None)
+        .map_err(|(error, _)| error)
+}
+
+/// Parse a line of text containing `'§'` format codes, like [`formatted_line`], pushing the
+/// [`SourceSpan`] of each token into `spans`.
+///
+/// `start` is where the line starts in the input. If `recover` is present, invalid format codes
+/// are recovered from as it describes, and this never fails.
+///
+/// # Errors
+///
+/// Returns the error and the span of the format code it is about, see [`formatted_line`].
+fn formatted(
+    output: &mut Vec<Token>,
+    spans: &mut Vec<SourceSpan>,
+    line: &str,
+    start: SourceSpan,
+    mut recover: Option<&mut Recover<'_>>,
+) -> Result<(), (ConversionError, SourceSpan)> {
+    // Returns the span of `line[from..to]`, which starts at the character `column` of the line
+    let span = |from: usize, to: usize, column: usize| {
+        SourceSpan::new(
+            start.offset() + from,
+            to - from,
+            start.line(),
+            start.column() + column,
+        )
+    };
+
+    // Builds a word out of consectutive characters, alongside the byte and character it starts at
+    let mut word_start: Option<(usize, usize)> = None;
 
-    // Builds a word out of consectutive characters
-    let mut word_stack: Vec<char> = vec![];
+    // Flush the current word into a text node, ending at the byte `end`
+    let flush = |output: &mut Vec<Token>,
+                 spans: &mut Vec<SourceSpan>,
+                 word_start: &mut Option<(usize, usize)>,
+                 end: usize| {
+        if let Some((from, column)) = word_start.take() {
+            output.push(Token::Text(line[from..end].into()));
+            spans.push(span(from, end, column));
+        }
+    };
 
     // Whether or not this line has a formatting code yet to be reset
     let mut trailing_formatting = false;
 
-    let mut iter = line.chars();
+    let mut iter = line.char_indices().enumerate().peekable();
 
-    while let Some(char) = iter.next() {
+    while let Some((column, (index, char))) = iter.next() {
         match char {
             // Flush current word and insert a space
             ' ' => {
-                flush(output, &mut word_stack);
+                flush(output, spans, &mut word_start, index);
                 output.push(Token::Space);
+                spans.push(span(index, index + 1, column));
             }
             // Flush current word and insert new formatting code
             '§' => {
-                flush(output, &mut word_stack);
+                let format = match iter.peek() {
+                    Some(&(_, (code_index, code))) => {
+                        let end = code_index + code.len_utf8();
+                        Format::try_from(code).map_err(|error| (error, span(index, end, column)))
+                    }
+                    None => Err((
+                        ConversionError::MissingFormatCode,
+                        span(index, line.len(), column),
+                    )),
+                };
+
+                let format = match (format, recover.as_deref_mut()) {
+                    (Ok(format), _) => format,
+                    (Err(error), None) => return Err(error),
+                    (Err((error, span)), Some(recover)) => {
+                        recover
+                            .warnings
+                            .push(TokenizeError::Conversion { error, span });
 
-                let code: char = iter.next().ok_or(ConversionError::MissingFormatCode)?;
-                let code: Token = Token::Format(Format::try_from(code)?);
+                        match recover.format_codes {
+                            // The code is read as text on its own, after the `'§'`
+                            FormatCodeRecovery::Keep => {
+                                word_start.get_or_insert((index, column));
+                            }
+                            // The word is split around the code, so that the code is not part
+                            // of the text before or after it
+                            FormatCodeRecovery::Drop => {
+                                flush(output, spans, &mut word_start, index);
+                                iter.next();
+                            }
+                        }
+                        continue;
+                    }
+                };
 
-                trailing_formatting = !matches!(code, Token::Format(Format::Reset));
-                output.push(code);
+                flush(output, spans, &mut word_start, index);
+
+                let (_, (code_index, code)) = iter.next().expect("the format code to be valid");
+                trailing_formatting = format != Format::Reset;
+                output.push(Token::Format(format));
+                spans.push(span(index, code_index + code.len_utf8(), column));
             }
             // Add a new character onto the current word
-            _ => word_stack.push(char),
+            _ => {
+                word_start.get_or_insert((index, column));
+            }
         }
     }
 
-    flush(output, &mut word_stack);
+    flush(output, spans, &mut word_start, line.len());
 
+    let end = span(line.len(), line.len(), line.chars().count());
     if trailing_formatting {
         output.push(Token::Format(Format::Reset));
+        spans.push(end);
     }
     output.push(Token::LineBreak);
+    spans.push(end);
 
     Ok(())
 }
 
+/// A line of the frontmatter.
+enum Field {
+    /// The `"pages:"` line that ends the frontmatter.
+    Pages,
+    /// Any other field.
+    Metadata(Metadata),
+}
+
+/// Parse a `"key: value"` line of the frontmatter, or return [`None`] if it is not one.
+///
+/// The `"title"` and `"author"` keys become [`Metadata::Title`] and [`Metadata::Author`], any
+/// other key becomes [`Metadata::Custom`].
+fn field(line: &str) -> Option<Field> {
+    let (key, value) = line.split_once(':')?;
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    let value = value.strip_prefix(' ').unwrap_or(value);
+
+    Some(match key {
+        "pages" => Field::Pages,
+        "title" => Field::Metadata(Metadata::Title(value.into())),
+        "author" => Field::Metadata(Metadata::Author(value.into())),
+        _ => Field::Metadata(Metadata::Custom {
+            key: key.into(),
+            value: value.into(),
+        }),
+    })
+}
+
 /// Parses the metadata about a work into the output.
 ///
+/// The frontmatter is a block of `"key: value"` lines in any order, ending with `"pages:"`.
+///
 /// # Side effects
 ///
 /// - Pushes data into `output`
@@ -92,43 +236,70 @@ pub fn line(output: &mut Vec<Token>, line: &str) -> Result<(), ConversionError>
 ///
 /// # Errors
 ///
-/// - [`TokenizeError::IncompleteOrMissingFrontmatter`] if, before it finishes parsing the
-///   frontmatter, the iterator empties or a line does not have the expected field
+/// - [`TokenizeError::IncompleteOrMissingFrontmatter`] if, before it reaches `"pages:"`, the
+///   iterator empties or a line is not a `"key: value"` field
 pub fn frontmatter<'s>(
     iter: &mut impl Iterator<Item = &'s str>,
 ) -> Result<Box<[Metadata]>, TokenizeError> {
-    /// Strip the prefix from the next line and return it or an error.
-    fn get_field<'s>(
-        iter: &mut impl Iterator<Item = &'s str>,
-        field: &str,
-    ) -> Result<&'s str, TokenizeError> {
-        iter.next()
-            .ok_or(TokenizeError::IncompleteOrMissingFrontmatter)?
-            .strip_prefix(field)
-            .ok_or(TokenizeError::IncompleteOrMissingFrontmatter)
+    let mut output: Vec<Metadata> = vec![];
+
+    loop {
+        let line = iter
+            .next()
+            .ok_or(TokenizeError::IncompleteOrMissingFrontmatter)?;
+
+        match field(line).ok_or(TokenizeError::IncompleteOrMissingFrontmatter)? {
+            Field::Pages => return Ok(output.into()),
+            Field::Metadata(metadata) => output.push(metadata),
+        }
     }
+}
 
+/// Parses the metadata about a work into the output, like [`frontmatter`], but keeps whatever
+/// fields it finds if the frontmatter does not end with `"pages:"`.
+///
+/// The first line that is not a `"key: value"` field is left in `iter` to be parsed as part of the
+/// book, and a [`TokenizeError::IncompleteOrMissingFrontmatter`] is pushed into `warnings`.
+pub fn lenient_frontmatter<'s, I>(
+    iter: &mut std::iter::Peekable<I>,
+    warnings: &mut Vec<TokenizeError>,
+) -> Box<[Metadata]>
+where
+    I: Iterator<Item = (&'s str, SourceSpan)>,
+{
     let mut output: Vec<Metadata> = vec![];
 
-    /// Parse a frontmatter field from `iter` and push the token to `output`, or return an error.
-    macro_rules! parse_field {
-        ($field:ident, $field_str:expr) => {
-            output.push(Metadata::$field(get_field(iter, $field_str)?.into()));
-        };
-    }
+    while let Some(field) = iter.peek().and_then(|(line, _)| field(line)) {
+        iter.next();
 
-    parse_field!(Title, "title: ");
-    parse_field!(Author, "author: ");
-    get_field(iter, "pages:")?; // Should just be an empty string, just need to make sure it's there
+        match field {
+            Field::Pages => return output.into(),
+            Field::Metadata(metadata) => output.push(metadata),
+        }
+    }
 
-    Ok(output.into())
+    warnings.push(TokenizeError::IncompleteOrMissingFrontmatter);
+    output.into()
 }
 
 /// If a line starts with `"#- "`, push a [`Token::ThematicBreak`] into the output.
-/// Returns the line without the `"#- "`.
-fn start_of_page<'s>(output: &mut Vec<Token>, line: &'s str) -> &'s str {
-    line.strip_prefix("#- ").map_or(line, |stripped| {
+/// Returns the line without the `"#- "`, and where it starts.
+fn start_of_page<'s>(
+    output: &mut Vec<Token>,
+    spans: &mut Vec<SourceSpan>,
+    line: &'s str,
+    start: SourceSpan,
+) -> (&'s str, SourceSpan) {
+    line.strip_prefix("#- ").map_or((line, start), |stripped| {
         output.push(Token::ThematicBreak);
-        stripped
+        spans.push(SourceSpan::new(
+            start.offset(),
+            3,
+            start.line(),
+            start.column(),
+        ));
+
+        let start = SourceSpan::new(start.offset() + 3, 0, start.line(), start.column() + 3);
+        (stripped, start)
     })
 }
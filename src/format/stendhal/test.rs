@@ -17,8 +17,14 @@
 
 //! Tests for parsing the [Stendhal][`super::Stendhal`] format.
 
-use super::parse;
-use crate::syntax::{Metadata, Token};
+use super::{parse, FormatCodeRecovery, Stendhal, TokenizeError};
+use crate::{
+    syntax::{
+        minecraft::{Font, Format, Rgb, ShadowColor},
+        ConversionError, Metadata, SourceSpan, Token, TokenList,
+    },
+    Export, Tokenize,
+};
 
 type Result = std::result::Result<(), Box<dyn std::error::Error>>;
 
@@ -46,6 +52,42 @@ pages:
     );
     assert_eq!(metadata, expected_metadata);
 
+    // Fields can be in any order, and unknown keys are kept
+    let mut lines = "author: RemasteredArch
+generator: stendhal
+title: crafty_novels
+empty:
+pages:
+#- The text of the book"
+        .lines();
+    let expected_metadata: Box<[Metadata]> = [
+        Metadata::Author("RemasteredArch".into()),
+        Metadata::Custom {
+            key: "generator".into(),
+            value: "stendhal".into(),
+        },
+        Metadata::Title("crafty_novels".into()),
+        Metadata::Custom {
+            key: "empty".into(),
+            value: "".into(),
+        },
+    ]
+    .into();
+
+    assert_eq!(parse::frontmatter(&mut lines)?, expected_metadata);
+    assert_eq!(lines.next(), Some(expected_line));
+
+    // The frontmatter must end with `pages:`, and have nothing but fields before it
+    for input in [
+        "title: crafty_novels",
+        "title: crafty_novels\nnot a field\npages:",
+    ] {
+        assert!(matches!(
+            parse::frontmatter(&mut input.lines()),
+            Err(TokenizeError::IncompleteOrMissingFrontmatter)
+        ));
+    }
+
     Ok(())
 }
 
@@ -56,9 +98,11 @@ fn test_line() -> Result {
         ( $( $input:expr => $expects:expr );+ ; ) => {
             $({
                 let mut output: Vec<Token> = vec![];
-                parse::line(&mut output, $input)?;
+                let mut spans: Vec<SourceSpan> = vec![];
+                parse::line(&mut output, &mut spans, $input, SourceSpan::new(0, 0, 1, 1), None)?;
 
                 assert_eq!(output, $expects);
+                assert_eq!(spans.len(), output.len());
             })+
         };
     }
@@ -74,7 +118,7 @@ fn test_line() -> Result {
     macro_rules! color {
         ($color:ident) => {
             crate::syntax::Token::Format(crate::syntax::minecraft::Format::Color(
-                crate::syntax::minecraft::Color::$color,
+                crate::syntax::minecraft::Color::$color.into(),
             ))
         };
     }
@@ -153,3 +197,404 @@ fn test_line() -> Result {
 
     Ok(())
 }
+
+#[test]
+fn test_spans() -> Result {
+    use Token::{LineBreak, ParagraphBreak, Space, ThematicBreak};
+
+    let input = "title: crafty_novels\r\nauthor: RemasteredArch\npages:\n#- é §lb\n\nc";
+    let (tokens, spans) = Stendhal::new().tokenize_with_spans(input)?;
+
+    let expected = [
+        (ThematicBreak, "#- ", (4, 1)),
+        (Token::Text("é".into()), "é", (4, 4)),
+        (Space, " ", (4, 5)),
+        (Token::Format(Format::Bold), "§l", (4, 6)),
+        (Token::Text("b".into()), "b", (4, 8)),
+        // Inserted by the parser
+        (Token::Format(Format::Reset), "", (4, 9)),
+        (LineBreak, "", (4, 9)),
+        (ParagraphBreak, "", (5, 1)),
+        (Token::Text("c".into()), "c", (6, 1)),
+        (LineBreak, "", (6, 2)),
+    ];
+
+    assert_eq!(tokens.tokens_as_slice().len(), expected.len());
+    for ((token, span), (expected_token, text, (line, column))) in tokens
+        .tokens_as_slice()
+        .iter()
+        .zip(spans.iter())
+        .zip(expected)
+    {
+        assert_eq!(token, &expected_token);
+        assert_eq!(&input[span.range()], text, "{token:?}");
+        assert_eq!((span.line(), span.column()), (line, column), "{token:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let input = "title: crafty_novels\nauthor: RemasteredArch\npages:\n#- fine\nends with §";
+    let error = Stendhal::tokenize_string(input).expect_err("the format code to be missing");
+
+    assert!(matches!(
+        error,
+        TokenizeError::Conversion {
+            error: ConversionError::MissingFormatCode,
+            ..
+        }
+    ));
+    assert_eq!(error.span(), Some(SourceSpan::new(69, 2, 5, 11)));
+    assert_eq!(
+        error.render(input),
+        concat!(
+            "error: expected a format code after '§'\n",
+            " --> line 5, column 11\n",
+            "  |\n",
+            "5 | ends with §\n",
+            "  |           ^\n",
+        )
+    );
+    assert_eq!(
+        error.to_string(),
+        "line 5, column 11: could not perform conversion: expected a format code after '§'"
+    );
+
+    // Errors that do not belong to one place are rendered on their own
+    let error = Stendhal::tokenize_string("title: crafty_novels").expect_err("no frontmatter");
+    assert_eq!(error.span(), None);
+    assert_eq!(
+        error.render(""),
+        "error: frontmatter is not present or incomplete\n"
+    );
+}
+
+#[test]
+fn test_lenient() {
+    use Token::{Format as F, LineBreak, Space, Text, ThematicBreak};
+
+    let input =
+        "title: crafty_novels\nauthor: RemasteredArch\npages:\n#- §zodd §lbold\nends with §";
+
+    let kept = Stendhal::new()
+        .format_code_recovery(FormatCodeRecovery::Keep)
+        .tokenize_lenient(input);
+    assert_eq!(
+        kept.tokens().tokens_as_slice(),
+        [
+            ThematicBreak,
+            Text("§zodd".into()),
+            Space,
+            F(Format::Bold),
+            Text("bold".into()),
+            F(Format::Reset),
+            LineBreak,
+            Text("ends".into()),
+            Space,
+            Text("with".into()),
+            Space,
+            Text("§".into()),
+            LineBreak,
+        ]
+    );
+
+    let dropped = Stendhal::new()
+        .format_code_recovery(FormatCodeRecovery::Drop)
+        .tokenize_lenient(input);
+    assert_eq!(
+        dropped.tokens().tokens_as_slice(),
+        [
+            ThematicBreak,
+            Text("odd".into()),
+            Space,
+            F(Format::Bold),
+            Text("bold".into()),
+            F(Format::Reset),
+            LineBreak,
+            Text("ends".into()),
+            Space,
+            Text("with".into()),
+            Space,
+            LineBreak,
+        ]
+    );
+
+    // Both keep the metadata, and report the same problems
+    for recovered in [&kept, &dropped] {
+        assert_eq!(
+            recovered.tokens().metadata_as_slice(),
+            [
+                Metadata::Title("crafty_novels".into()),
+                Metadata::Author("RemasteredArch".into()),
+            ]
+        );
+        assert!(!recovered.is_clean());
+
+        let spans: Vec<_> = recovered
+            .warnings()
+            .iter()
+            .map(TokenizeError::span)
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                Some(SourceSpan::new(54, 3, 4, 4)),
+                Some(SourceSpan::new(79, 2, 5, 11)),
+            ]
+        );
+        assert!(matches!(
+            recovered.warnings()[0],
+            TokenizeError::Conversion {
+                error: ConversionError::NoSuchFormatCode('z'),
+                ..
+            }
+        ));
+    }
+
+    // Clean input has no warnings, and parses just like the strict parser
+    let input = "title: crafty_novels\nauthor: RemasteredArch\npages:\n#- fine";
+    let recovered = Stendhal::new()
+        .format_code_recovery(FormatCodeRecovery::Drop)
+        .tokenize_lenient(input);
+    assert!(recovered.is_clean());
+    assert_eq!(
+        recovered.into_tokens(),
+        Stendhal::tokenize_string(input).expect("the input to be valid")
+    );
+}
+
+#[test]
+fn test_lenient_drop() {
+    use Token::{LineBreak, Space, Text, ThematicBreak};
+
+    // Codes in the middle and at the end of a word are dropped too, not only at the start
+    let input = "pages:\n#- ab§zcd ef§";
+    let recovered = Stendhal::new()
+        .format_code_recovery(FormatCodeRecovery::Drop)
+        .spans(true)
+        .tokenize_lenient(input);
+    assert_eq!(
+        recovered.tokens().tokens_as_slice(),
+        [
+            ThematicBreak,
+            Text("ab".into()),
+            Text("cd".into()),
+            Space,
+            Text("ef".into()),
+            LineBreak,
+        ]
+    );
+    assert_eq!(recovered.warnings().len(), 2);
+
+    let text: Vec<&str> = recovered
+        .spans()
+        .iter()
+        .map(|span| &input[span.range()])
+        .collect();
+    assert_eq!(text, ["#- ", "ab", "cd", " ", "ef", ""]);
+}
+
+#[test]
+fn test_lenient_frontmatter() {
+    use Token::{LineBreak, Space, Text, ThematicBreak};
+
+    // The fields before a missing `pages:` are kept, and the body is still parsed
+    let input = "title: crafty_novels\n#- text";
+    let recovered = Stendhal::new()
+        .format_code_recovery(FormatCodeRecovery::Keep)
+        .tokenize_lenient(input);
+    assert_eq!(
+        recovered.tokens().metadata_as_slice(),
+        [Metadata::Title("crafty_novels".into())]
+    );
+    assert_eq!(
+        recovered.tokens().tokens_as_slice(),
+        [ThematicBreak, Text("text".into()), LineBreak]
+    );
+    assert!(matches!(
+        recovered.warnings(),
+        [TokenizeError::IncompleteOrMissingFrontmatter]
+    ));
+
+    // Without frontmatter, every line is part of the book
+    let recovered = Stendhal::new()
+        .format_code_recovery(FormatCodeRecovery::Keep)
+        .tokenize_lenient("no frontmatter");
+    assert!(recovered.tokens().metadata_as_slice().is_empty());
+    assert_eq!(
+        recovered.tokens().tokens_as_slice(),
+        [
+            Text("no".into()),
+            Space,
+            Text("frontmatter".into()),
+            LineBreak
+        ]
+    );
+    assert_eq!(recovered.warnings().len(), 1);
+}
+
+#[test]
+fn test_options() -> Result {
+    let input = "title: crafty_novels\nauthor: RemasteredArch\npages:\n#- §zodd";
+
+    // Strict by default
+    assert!(Stendhal::new().tokenize_from_string(input).is_err());
+    assert!(Stendhal::tokenize_string(input).is_err());
+
+    // Lenient parsers recover through `Tokenize` too, using their format code recovery
+    let lenient = Stendhal::new()
+        .lenient(true)
+        .format_code_recovery(FormatCodeRecovery::Drop);
+    let tokens = lenient.tokenize_from_string(input)?;
+    assert_eq!(tokens, lenient.tokenize_lenient(input).into_tokens(),);
+    assert_eq!(
+        tokens.tokens_as_slice(),
+        [
+            Token::ThematicBreak,
+            Token::Text("odd".into()),
+            Token::LineBreak
+        ]
+    );
+    assert_eq!(lenient.tokenize_from_reader(input.as_bytes())?, tokens);
+
+    let (_, spans) = lenient.tokenize_with_spans(input)?;
+    assert_eq!(spans.len(), tokens.tokens_as_slice().len());
+
+    // Spans are only kept when asked for
+    assert!(lenient.tokenize_lenient(input).spans().is_empty());
+    let recovered = lenient.spans(true).tokenize_lenient(input);
+    assert_eq!(recovered.spans(), spans.as_ref());
+    assert_eq!(&input[recovered.spans()[0].range()], "#- ");
+
+    Ok(())
+}
+
+#[test]
+fn test_export_frontmatter() {
+    // Fields keep their order, and a missing author is written first
+    let tokens = TokenList::new_from_boxed(
+        Box::new([
+            Metadata::Custom {
+                key: "generator".into(),
+                value: "stendhal".into(),
+            },
+            Metadata::Title("crafty_novels".into()),
+        ]),
+        Box::new([]),
+    );
+    assert_eq!(
+        Stendhal::export_token_vector_to_string(tokens).as_ref(),
+        "author: \ngenerator: stendhal\ntitle: crafty_novels\npages:\n"
+    );
+}
+
+#[test]
+fn test_round_trip() -> Result {
+    /// Wrap lines of a book in frontmatter.
+    macro_rules! book {
+        ( $( $line:expr ),* $(,)? ) => {
+            concat!("title: crafty_novels\nauthor: RemasteredArch\npages:\n", $( $line, "\n" ),*)
+        };
+    }
+
+    let inputs = [
+        // The inputs of `test_line`
+        book!("#- page start"),
+        book!("Plain line"),
+        book!("Not #- new page"),
+        book!(" #- not new page"),
+        book!(""),
+        book!("Some §cRED text"),
+        book!("Italic:§o text §rreset"),
+        book!("   lots    of   spaces     "),
+        book!("one space "),
+        book!("<div>HTML &gt; & &amp;</div>"),
+        // The input of the crate-level example
+        book!("#- Page one", "Italic:§o text §rreset"),
+        // Custom fields
+        "title: crafty_novels\nauthor: RemasteredArch\ngenerator: stendhal\npages:\n#- text\n",
+        // The input of the CLI
+        book!(
+            "#- This is the start of the page",
+            "First line",
+            "#- New Page",
+            "Not a #- new page",
+            " #- also not a new page",
+            "",
+            "",
+            "",
+            "Lots of paragraph breaks",
+            "Some §cRED line breaks",
+            "Some §l BOLD line breaks (2)",
+            "Italic:§o text §rreset",
+            "   lots    of   spaces     ",
+            "just one space ",
+            "<div>some HTML</div>",
+            "&gt; <== not an <",
+            "& ampersands &",
+            "last line",
+        ),
+        // Formatting that is reset by hand, empty pages, and a page that starts empty
+        book!(
+            "§l§nBold§r underline",
+            "#- ",
+            "#- ",
+            "",
+            "§r§kObfuscated",
+            "#- §6"
+        ),
+        // Frontmatter without any pages
+        book!(),
+    ];
+
+    for input in inputs {
+        let tokens = Stendhal::tokenize_string(input)?;
+        let exported = Stendhal::export_token_vector_to_string(tokens.clone());
+
+        assert_eq!(exported.as_ref(), input);
+        assert_eq!(Stendhal::tokenize_string(&exported)?, tokens, "{input}");
+    }
+
+    // A reset at the end of a line is inserted by the parser anyway, so it is left out
+    let tokens = Stendhal::tokenize_string(book!("§lBold§r"))?;
+    let exported = Stendhal::export_token_vector_to_string(tokens.clone());
+    assert_eq!(exported.as_ref(), book!("§lBold"));
+    assert_eq!(Stendhal::tokenize_string(&exported)?, tokens);
+
+    // Format codes only exist for legacy colors, so RGB colors are written as the nearest one
+    let tokens = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            Token::ThematicBreak,
+            Token::Format(Format::Color(Rgb::new(250, 160, 10).into())),
+            Token::Text("gold".into()),
+            Token::LineBreak,
+        ]),
+    );
+    assert_eq!(
+        Stendhal::export_token_vector_to_string(tokens).as_ref(),
+        "title: \nauthor: \npages:\n#- §6gold\n"
+    );
+
+    // Fonts, shadow colors, and insertions have no format codes, so they are left out
+    let tokens = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            Token::ThematicBreak,
+            Token::Format(Format::Font(Font::Alt)),
+            Token::Format(Format::ShadowColor(ShadowColor::from_argb(0xFF00_0000))),
+            Token::Insertion("/say hi".into()),
+            Token::Format(Format::Bold),
+            Token::Text("runes".into()),
+            Token::LineBreak,
+        ]),
+    );
+    assert_eq!(
+        Stendhal::export_token_vector_to_string(tokens).as_ref(),
+        "title: \nauthor: \npages:\n#- §lrunes\n"
+    );
+
+    Ok(())
+}
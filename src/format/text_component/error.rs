@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Error definitions for [`super::TextComponent`].
+//!
+//! See [`TokenizeError`] and [`ComponentError`].
+
+use crate::{format::snbt::SyntaxError, syntax::ConversionError};
+
+/// All the errors that could occur while tokenizing a JSON text component.
+#[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
+#[derive(thiserror::Error, Debug)]
+pub enum TokenizeError {
+    /// Encountered when the input is not valid JSON.
+    #[error("could not parse JSON: {0}")]
+    Syntax(#[from] SyntaxError),
+    /// Encountered when the input is valid JSON, but not a valid text component.
+    #[error("could not read text component: {0}")]
+    Component(#[from] ComponentError),
+    /// Encountered when an I/O action fails in some way.
+    #[error("could not perform I/O action: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// All the errors that could occur while flattening a text component into tokens.
+#[allow(clippy::module_name_repetitions)] // This will be exported outside of `error`
+#[derive(thiserror::Error, Debug)]
+pub enum ComponentError {
+    /// Encountered when a field holds the wrong kind of value, ex. `{"bold":"yes"}`.
+    #[error("expected the field '{field}' to be {expected}")]
+    UnexpectedType {
+        field: &'static str,
+        expected: &'static str,
+    },
+    /// Encountered when trying to convert invalid syntax, like an unknown color name or format
+    /// code.
+    #[error("could not perform conversion: {0}")]
+    Conversion(#[from] ConversionError),
+}
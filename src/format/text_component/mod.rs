@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing for Minecraft: Java Edition's JSON text components.
+//! See [`TextComponent`] for more details.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use crafty_novels::{
+//!     import::TextComponent,
+//!     syntax::{
+//!         minecraft::{Color, Format},
+//!         Token, TokenList,
+//!     },
+//!     Tokenize,
+//! };
+//! # use std::error::Error;
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let input = r#"["Some ", {"text": "RED", "color": "red", "extra": [{"text": " text", "bold": true}]}]"#;
+//!
+//! let expected_tokens = Box::new([
+//!     Token::Text("Some".into()),
+//!     Token::Space,
+//!     Token::Format(Format::Color(Color::Red.into())),
+//!     Token::Text("RED".into()),
+//!     Token::Format(Format::Bold),
+//!     Token::Space,
+//!     Token::Text("text".into()),
+//!     Token::Format(Format::Reset),
+//!     Token::LineBreak,
+//! ]);
+//!
+//! assert_eq!(
+//!     TextComponent::tokenize_string(input)?,
+//!     TokenList::new_from_boxed(Box::new([]), expected_tokens)
+//! );
+//! #
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{
+    format::snbt,
+    syntax::{Token, TokenList},
+    Tokenize,
+};
+pub use error::{ComponentError, TokenizeError};
+use std::io::Read;
+
+mod error;
+pub mod parse;
+#[cfg(test)]
+mod test;
+
+/// Parses Minecraft: Java Edition's JSON text components, as used for the pages of written books.
+///
+/// # Expected format
+///
+/// A single text component, which is any of:
+///
+/// - A string, ex. `"plain text"`, which is plain text
+/// - An array, ex. `["a", {"text": "b"}]`, where the first element is the parent of the rest of
+///   the elements, which inherit its style
+/// - An object, ex. `{"text": "a", "bold": true, "extra": ["b"]}`, where:
+///     - `text` is plain text
+///     - Without `text`, `translate`, `keybind`, `score`, or `selector` is
+///       [content][`crate::syntax::minecraft::Content`] that is filled in as it is exported, ex.
+///       `{"translate": "multiplayer.player.joined", "with": ["Steve"]}`
+///     - `color` is the name of a [`Color`][`crate::syntax::minecraft::Color`], ex. `"dark_aqua"`,
+///       or a hex value, ex. `"#12AB34"`
+///     - `bold`, `italic`, `underlined`, `strikethrough`, and `obfuscated` are booleans that turn
+///       formats on or off
+///     - `font` is the resource location of a [`Font`][`crate::syntax::minecraft::Font`], ex.
+///       `"minecraft:alt"`, where fonts from resource packs fall back to the default font
+///     - `shadow_color` is an ARGB integer, ex. `-16777216` for opaque black, or a list of four
+///       numbers from zero to one, ex. `[0.0, 0.0, 0.0, 1.0]`
+///     - `clickEvent` is an object with an `action` and a `value`, ex.
+///       `{"action": "change_page", "value": "2"}`, where the action is any of `change_page`,
+///       `open_url`, `copy_to_clipboard`, `run_command`, or `suggest_command`
+///     - `hoverEvent` is an object with an `action` of `show_text` and a text component in
+///       `contents` (or `value`), ex. `{"action": "show_text", "contents": "tooltip"}`
+///     - Both are also read in their 1.21.5+ forms (`click_event` and `hover_event`), and
+///       unsupported actions are ignored
+///     - `insertion` is a string inserted into the chat box when the text is shift-clicked
+///     - `extra` is an array of child components, which inherit the object's style
+///
+/// Inside of text:
+///
+/// - Each line ending (`'\n'`) ends a line, and empty lines are paragraph breaks
+/// - `'§'` format codes apply on top of the component's style until the end of the text
+///
+/// Because a text component has no metadata, the resulting [`TokenList`] has no
+/// [`Metadata`][`crate::syntax::Metadata`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TextComponent;
+
+impl Tokenize for TextComponent {
+    type Error = TokenizeError;
+
+    /// Parse a string containing a JSON text component into an abstract syntax vector.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Syntax`] if `input` is not valid JSON
+    /// - [`TokenizeError::Component`] if `input` is valid JSON, but not a valid text component
+    fn tokenize_from_string(&self, input: &str) -> Result<TokenList, Self::Error> {
+        let component = snbt::parse::value(input)?;
+
+        let mut tokens: Vec<Token> = vec![];
+        parse::component(&mut tokens, &component)?;
+
+        Ok(TokenList::new_from_boxed(Box::new([]), tokens.into()))
+    }
+
+    /// Parse a file containing a JSON text component into an abstract syntax vector.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenizeError::Syntax`] if `input` is not valid JSON
+    /// - [`TokenizeError::Component`] if `input` is valid JSON, but not a valid text component
+    /// - [`TokenizeError::Io`] if `input` cannot be read or is not valid UTF-8
+    fn tokenize_from_reader(&self, mut input: impl Read) -> Result<TokenList, Self::Error> {
+        let mut string = String::new();
+        input.read_to_string(&mut string)?;
+
+        self.tokenize_from_string(&string)
+    }
+}
@@ -0,0 +1,627 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The actual, under the hood, flattening of [text components][`super::TextComponent`] into
+//! tokens.
+
+use super::ComponentError;
+use crate::{
+    format::nbt::Tag,
+    syntax::{
+        minecraft::{ClickEvent, Content, Font, Format, HoverEvent, Rgb, ShadowColor, TextColor},
+        ConversionError, Token,
+    },
+};
+
+/// Flatten a text component into an abstract syntax vector.
+///
+/// `component` is the component as parsed from JSON or SNBT: a string, a list, or a compound.
+///
+/// - Strings are plain text
+/// - Lists are the first element, with the rest of the elements as its `extra`, so they inherit
+///   the style of the first element
+/// - Compounds hold `text` (or the [`Content`] of `translate`, `keybind`, `score`, or
+///   `selector`), optionally styled by `color`, `bold`, `italic`, `underlined`,
+///   `strikethrough`, `obfuscated`, `font`, and `shadow_color`, and made interactive by
+///   `click_event`, `hover_event` (`clickEvent` and `hoverEvent` before 1.21.5), and
+///   `insertion`, with the children in `extra` inheriting that style
+///
+/// Changes in style are written as [`Token::Format`]s, [`Token::Click`]s, [`Token::Hover`]s, and
+/// [`Token::Insertion`]s, adding to the style where possible and inserting [`Format::Reset`] when
+/// something needs to be removed. Like
+/// [Stendhal][`crate::import::Stendhal`], the output ends with a [`Format::Reset`] if any
+/// formatting is left over, and with a [`Token::LineBreak`] if the last line is not empty.
+///
+/// # Errors
+///
+/// - [`ComponentError::UnexpectedType`] if a component or one of its fields is of the wrong type
+/// - [`ComponentError::Conversion`] if a color name or `'§'` format code is invalid
+pub fn component(output: &mut Vec<Token>, component: &Tag) -> Result<(), ComponentError> {
+    let mut flattener = Flattener {
+        output,
+        word_stack: vec![],
+        active: Style::default(),
+        line_is_empty: true,
+    };
+
+    flattener.component(component, Style::default())?;
+    flattener.finish();
+
+    Ok(())
+}
+
+/// The formatting applied to a span of text.
+#[allow(clippy::struct_excessive_bools)] // Mirrors the fields of a text component
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+struct Style {
+    color: Option<TextColor>,
+    obfuscated: bool,
+    bold: bool,
+    strikethrough: bool,
+    underline: bool,
+    italic: bool,
+    font: Option<Font>,
+    shadow_color: Option<ShadowColor>,
+    click: Option<ClickEvent>,
+    hover: Option<HoverEvent>,
+    insertion: Option<Box<str>>,
+}
+
+impl Style {
+    /// Returns the style with `format` applied on top of it.
+    ///
+    /// Like in Minecraft, a color clears the formatting before it, and a [`Format::Reset`] goes
+    /// back to `base`, the style of the component that holds the text.
+    fn apply(mut self, format: Format, base: &Self) -> Self {
+        match format {
+            Format::Color(color) => {
+                return Self {
+                    color: Some(color),
+                    font: self.font,
+                    shadow_color: self.shadow_color,
+                    click: self.click,
+                    hover: self.hover,
+                    insertion: self.insertion,
+                    ..Self::default()
+                }
+            }
+            Format::Obfuscated => self.obfuscated = true,
+            Format::Bold => self.bold = true,
+            Format::Strikethrough => self.strikethrough = true,
+            Format::Underline => self.underline = true,
+            Format::Italic => self.italic = true,
+            Format::Font(font) => self.font = Some(font),
+            Format::ShadowColor(shadow_color) => self.shadow_color = Some(shadow_color),
+            Format::Reset => return base.clone(),
+        }
+
+        self
+    }
+
+    /// Returns the [`Format`]s that make up the style, colors first.
+    fn formats(&self) -> impl Iterator<Item = Format> {
+        [
+            self.color.map(Format::Color),
+            self.obfuscated.then_some(Format::Obfuscated),
+            self.bold.then_some(Format::Bold),
+            self.strikethrough.then_some(Format::Strikethrough),
+            self.underline.then_some(Format::Underline),
+            self.italic.then_some(Format::Italic),
+            self.font.map(Format::Font),
+            self.shadow_color.map(Format::ShadowColor),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Whether every format in `other` is also in `self`, and every event in `other` is kept or
+    /// replaced in `self`, such that `self` can be reached by only adding to `other`.
+    ///
+    /// Adding a color clears the formatting before it, so `other` cannot have any formatting if
+    /// `self` adds a color to it.
+    fn contains(&self, other: &Self) -> bool {
+        other.color.is_none_or(|color| self.color == Some(color))
+            && (self.color == other.color || !other.is_decorated())
+            && (other.click.is_none() || self.click.is_some())
+            && (other.hover.is_none() || self.hover.is_some())
+            && (other.insertion.is_none() || self.insertion.is_some())
+            && other
+                .formats()
+                .all(|format| self.formats().any(|f| f == format))
+    }
+
+    /// Whether any formatting that a color clears (bold, italic, etc.) applies.
+    const fn is_decorated(&self) -> bool {
+        self.obfuscated || self.bold || self.strikethrough || self.underline || self.italic
+    }
+
+    /// Returns the style of `compound`, inheriting anything it does not specify from `self`.
+    fn inherit(mut self, compound: &Tag) -> Result<Self, ComponentError> {
+        /// If `$field` is present in `compound`, set `self.$style` to its value as a boolean.
+        macro_rules! bool_field {
+            ( $( $field:literal => $style:ident ),+ $(,)? ) => {
+                $(
+                    if let Some(value) = compound.get($field) {
+                        self.$style = boolean(value).ok_or(ComponentError::UnexpectedType {
+                            field: $field,
+                            expected: "a boolean",
+                        })?;
+                    }
+                )+
+            };
+        }
+
+        if let Some(color) = compound.get("color") {
+            let color = color.as_str().ok_or(ComponentError::UnexpectedType {
+                field: "color",
+                expected: "a string",
+            })?;
+
+            self.color = match color {
+                "reset" => None,
+                color => Some(color.parse()?),
+            };
+        }
+
+        bool_field!(
+            "bold" => bold,
+            "italic" => italic,
+            "underlined" => underline,
+            "strikethrough" => strikethrough,
+            "obfuscated" => obfuscated,
+        );
+
+        if let Some(font) = compound.get("font") {
+            let font = font.as_str().ok_or(ComponentError::UnexpectedType {
+                field: "font",
+                expected: "a string",
+            })?;
+
+            // Fonts from resource packs cannot be shown anywhere else, so use the default instead
+            self.font = font.parse().ok();
+        }
+
+        if let Some(shadow_color) = compound.get("shadow_color") {
+            self.shadow_color = Some(shadow_color_field(shadow_color)?);
+        }
+
+        if let Some(event) = compound
+            .get("click_event")
+            .or_else(|| compound.get("clickEvent"))
+        {
+            self.click = click_event(event)?;
+        }
+
+        if let Some(event) = compound
+            .get("hover_event")
+            .or_else(|| compound.get("hoverEvent"))
+        {
+            self.hover = hover_event(event)?;
+        }
+
+        if let Some(insertion) = compound.get("insertion") {
+            self.insertion = Some(insertion.as_str().map(Box::from).ok_or(
+                ComponentError::UnexpectedType {
+                    field: "insertion",
+                    expected: "a string",
+                },
+            )?);
+        }
+
+        Ok(self)
+    }
+}
+
+/// Read a shadow color, either an ARGB integer (ex. `-16777216` for opaque black) or a list of
+/// the red, green, blue, and alpha channels from zero to one (ex. `[0.0, 0.0, 0.0, 1.0]`).
+fn shadow_color_field(tag: &Tag) -> Result<ShadowColor, ComponentError> {
+    const INVALID: ComponentError = ComponentError::UnexpectedType {
+        field: "shadow_color",
+        expected: "an integer or a list of four numbers",
+    };
+
+    /// Scale a channel from zero to one up to a byte.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to `u8`'s range
+    fn channel(tag: &Tag) -> Option<u8> {
+        let channel = match tag {
+            Tag::Float(channel) => f64::from(*channel),
+            Tag::Double(channel) => *channel,
+            Tag::Int(channel) => f64::from(*channel),
+            _ => return None,
+        };
+
+        Some((channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    match tag {
+        // Stored as a signed integer, so opaque colors are negative
+        Tag::Int(argb) => Ok(ShadowColor::from_argb(u32::from_be_bytes(
+            argb.to_be_bytes(),
+        ))),
+        Tag::Long(argb) => u32::try_from(*argb)
+            .map(ShadowColor::from_argb)
+            .map_err(|_| INVALID),
+        Tag::List(channels) => {
+            match channels
+                .iter()
+                .map(channel)
+                .collect::<Option<Vec<_>>>()
+                .as_deref()
+            {
+                Some(&[red, green, blue, alpha]) => {
+                    Ok(ShadowColor::new(Rgb::new(red, green, blue), alpha))
+                }
+                _ => Err(INVALID),
+            }
+        }
+        _ => Err(INVALID),
+    }
+}
+
+/// Returns the action of a click or hover event, ex. `"change_page"`.
+fn action(event: &Tag) -> Result<&str, ComponentError> {
+    event
+        .get("action")
+        .and_then(Tag::as_str)
+        .ok_or(ComponentError::UnexpectedType {
+            field: "action",
+            expected: "a string",
+        })
+}
+
+/// Read a click event, ex. `{action:"change_page",page:2}` (1.21.5+) or
+/// `{"action":"change_page","value":"2"}` (before 1.21.5).
+///
+/// Returns [`None`] for actions that are not supported in books (ex. `open_file`).
+fn click_event(event: &Tag) -> Result<Option<ClickEvent>, ComponentError> {
+    /// Returns the string in `$field`, or in `value` before 1.21.5.
+    macro_rules! string {
+        ($field:literal) => {
+            event
+                .get($field)
+                .or_else(|| event.get("value"))
+                .and_then(Tag::as_str)
+                .map(Box::from)
+                .ok_or(ComponentError::UnexpectedType {
+                    field: $field,
+                    expected: "a string",
+                })?
+        };
+    }
+
+    Ok(Some(match action(event)? {
+        "change_page" => {
+            let page = event.get("page").or_else(|| event.get("value"));
+            let page = match page {
+                Some(Tag::Byte(page)) => usize::try_from(*page).ok(),
+                Some(Tag::Short(page)) => usize::try_from(*page).ok(),
+                Some(Tag::Int(page)) => usize::try_from(*page).ok(),
+                Some(Tag::Long(page)) => usize::try_from(*page).ok(),
+                Some(Tag::String(page)) => page.parse().ok(),
+                _ => None,
+            };
+
+            ClickEvent::ChangePage(page.filter(|&page| page > 0).ok_or(
+                ComponentError::UnexpectedType {
+                    field: "page",
+                    expected: "a positive integer",
+                },
+            )?)
+        }
+        "open_url" => ClickEvent::OpenUrl(string!("url")),
+        "copy_to_clipboard" => ClickEvent::CopyToClipboard(string!("value")),
+        "run_command" => ClickEvent::RunCommand(string!("command")),
+        "suggest_command" => ClickEvent::SuggestCommand(string!("command")),
+        _ => return Ok(None),
+    }))
+}
+
+/// Read a hover event, ex. `{action:"show_text",value:"..."}` (1.21.5+) or
+/// `{"action":"show_text","contents":"..."}` (before 1.21.5).
+///
+/// The text is flattened on its own, without its trailing [`Format::Reset`] or
+/// [`Token::LineBreak`]. Returns [`None`] for actions other than `show_text`, which show items or
+/// entities.
+fn hover_event(event: &Tag) -> Result<Option<HoverEvent>, ComponentError> {
+    if action(event)? != "show_text" {
+        return Ok(None);
+    }
+
+    let text = event.get("value").or_else(|| event.get("contents")).ok_or(
+        ComponentError::UnexpectedType {
+            field: "value",
+            expected: "a text component",
+        },
+    )?;
+
+    let mut tokens = nested(text)?;
+    if tokens.last() == Some(&Token::Format(Format::Reset)) {
+        tokens.pop();
+    }
+
+    Ok(Some(HoverEvent::ShowText(tokens.into())))
+}
+
+/// Read the [`Content`] of a component that has no `text`, if it has any.
+///
+/// - `translate` is a translation key, with an optional `fallback`, and arguments in `with`
+/// - `keybind` is the name of a control, ex. `"key.jump"`
+/// - `score` holds the `name` of a player or entity and the `objective` to show the score of
+/// - `selector` is a selector, with an optional `separator` between the names it matches
+fn content(component: &Tag) -> Result<Option<Content>, ComponentError> {
+    /// Returns the string in `$field` of `$tag`.
+    macro_rules! string {
+        ($tag:expr, $field:literal) => {
+            $tag.get($field)
+                .map(|value| {
+                    value
+                        .as_str()
+                        .map(Box::from)
+                        .ok_or(ComponentError::UnexpectedType {
+                            field: $field,
+                            expected: "a string",
+                        })
+                })
+                .transpose()?
+        };
+    }
+
+    if let Some(key) = string!(component, "translate") {
+        let with = match component.get("with") {
+            Some(with) => with
+                .as_list()
+                .ok_or(ComponentError::UnexpectedType {
+                    field: "with",
+                    expected: "a list",
+                })?
+                .iter()
+                .map(|argument| nested(argument).map(Vec::into_boxed_slice))
+                .collect::<Result<_, _>>()?,
+            None => Box::new([]) as Box<[_]>,
+        };
+
+        return Ok(Some(Content::Translate {
+            key,
+            fallback: string!(component, "fallback"),
+            with,
+        }));
+    }
+
+    if let Some(keybind) = string!(component, "keybind") {
+        return Ok(Some(Content::Keybind(keybind)));
+    }
+
+    if let Some(score) = component.get("score") {
+        let missing = |field| ComponentError::UnexpectedType {
+            field,
+            expected: "a string",
+        };
+
+        return Ok(Some(Content::Score {
+            name: string!(score, "name").ok_or_else(|| missing("name"))?,
+            objective: string!(score, "objective").ok_or_else(|| missing("objective"))?,
+        }));
+    }
+
+    if let Some(pattern) = string!(component, "selector") {
+        let separator = component
+            .get("separator")
+            .map(|separator| nested(separator).map(Vec::into_boxed_slice))
+            .transpose()?;
+
+        return Ok(Some(Content::Selector { pattern, separator }));
+    }
+
+    Ok(None)
+}
+
+/// Flatten a component that is part of another, like an argument of a translation, on its own.
+///
+/// Any formatting is still [reset][`Format::Reset`] at the end, but the trailing
+/// [`Token::LineBreak`] is left out.
+fn nested(component: &Tag) -> Result<Vec<Token>, ComponentError> {
+    let mut tokens: Vec<Token> = vec![];
+    self::component(&mut tokens, component)?;
+
+    if tokens.last() == Some(&Token::LineBreak) {
+        tokens.pop();
+    }
+
+    Ok(tokens)
+}
+
+/// Interpret a [`Tag`] as a boolean, as JSON `true` or SNBT `1b`.
+fn boolean(tag: &Tag) -> Option<bool> {
+    match tag {
+        Tag::Byte(byte) => Some(*byte != 0),
+        Tag::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Holds the state necessary to turn a tree of components into a flat list of tokens.
+struct Flattener<'o> {
+    output: &'o mut Vec<Token>,
+    /// Builds a word out of consecutive characters of the same style.
+    word_stack: Vec<char>,
+    /// The style that the [`Token::Format`]s written so far add up to.
+    active: Style,
+    /// Whether anything has been written since the last line break.
+    line_is_empty: bool,
+}
+
+impl Flattener<'_> {
+    /// Flatten `component`, which inherits `parent`'s style.
+    ///
+    /// Returns the style of `component`, for the rest of a list to inherit.
+    fn component(&mut self, component: &Tag, parent: Style) -> Result<Style, ComponentError> {
+        match component {
+            Tag::String(text) => {
+                self.text(text, &parent)?;
+                Ok(parent)
+            }
+            Tag::List(list) => {
+                let Some((first, rest)) = list.split_first() else {
+                    return Ok(parent);
+                };
+
+                let style = self.component(first, parent)?;
+                for child in rest {
+                    self.component(child, style.clone())?;
+                }
+
+                Ok(style)
+            }
+            Tag::Compound(_) => {
+                let style = parent.inherit(component)?;
+
+                if let Some(text) = component.get("text") {
+                    self.component(text, style.clone())?;
+                } else if let Some(content) = content(component)? {
+                    self.flush();
+                    self.transition(&style);
+                    self.output.push(Token::Content(content));
+                    self.line_is_empty = false;
+                }
+
+                if let Some(extra) = component.get("extra") {
+                    let extra = extra.as_list().ok_or(ComponentError::UnexpectedType {
+                        field: "extra",
+                        expected: "a list",
+                    })?;
+
+                    for child in extra {
+                        self.component(child, style.clone())?;
+                    }
+                }
+
+                Ok(style)
+            }
+            Tag::Byte(number) => self.number(number, parent),
+            Tag::Short(number) => self.number(number, parent),
+            Tag::Int(number) => self.number(number, parent),
+            Tag::Long(number) => self.number(number, parent),
+            Tag::Float(number) => self.number(number, parent),
+            Tag::Double(number) => self.number(number, parent),
+            _ => Err(ComponentError::UnexpectedType {
+                field: "text",
+                expected: "a string, list, compound, or number",
+            }),
+        }
+    }
+
+    /// Write a number as plain text.
+    fn number(&mut self, number: &impl ToString, style: Style) -> Result<Style, ComponentError> {
+        self.text(&number.to_string(), &style)?;
+        Ok(style)
+    }
+
+    /// Write a string of text in the `base` style.
+    ///
+    /// `'§'` format codes inside of the text apply on top of `base` until the end of the text, and
+    /// `"§r"` goes back to `base`.
+    fn text(&mut self, text: &str, base: &Style) -> Result<(), ComponentError> {
+        let mut iter = text.chars();
+        let mut style = base.clone();
+
+        while let Some(char) = iter.next() {
+            match char {
+                '§' => {
+                    let code = iter.next().ok_or(ConversionError::MissingFormatCode)?;
+                    style = style.apply(Format::try_from(code)?, base);
+                }
+                '\n' => {
+                    self.flush();
+                    self.output.push(if self.line_is_empty {
+                        Token::ParagraphBreak
+                    } else {
+                        Token::LineBreak
+                    });
+                    self.line_is_empty = true;
+                }
+                ' ' => {
+                    self.flush();
+                    self.transition(&style);
+                    self.output.push(Token::Space);
+                    self.line_is_empty = false;
+                }
+                _ => {
+                    if style != self.active {
+                        self.flush();
+                        self.transition(&style);
+                    }
+                    self.word_stack.push(char);
+                    self.line_is_empty = false;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the tokens necessary to change the active style to `style`.
+    fn transition(&mut self, style: &Style) {
+        if *style == self.active {
+            return;
+        }
+
+        if !style.contains(&self.active) {
+            self.output.push(Token::Format(Format::Reset));
+            self.active = Style::default();
+        }
+
+        let active = &self.active;
+        self.output.extend(
+            style
+                .formats()
+                .filter(|&format| !active.formats().any(|f| f == format))
+                .map(Token::Format),
+        );
+
+        if style.click != active.click {
+            self.output.extend(style.click.clone().map(Token::Click));
+        }
+        if style.hover != active.hover {
+            self.output.extend(style.hover.clone().map(Token::Hover));
+        }
+        if style.insertion != active.insertion {
+            self.output
+                .extend(style.insertion.clone().map(Token::Insertion));
+        }
+
+        self.active = style.clone();
+    }
+
+    /// Flush the current word stack into a text node.
+    fn flush(&mut self) {
+        if !self.word_stack.is_empty() {
+            self.output.push((&mut self.word_stack).into());
+        }
+    }
+
+    /// Close any remaining formatting and end the last line.
+    fn finish(&mut self) {
+        self.flush();
+
+        if self.active != Style::default() {
+            self.output.push(Token::Format(Format::Reset));
+        }
+        if !self.line_is_empty {
+            self.output.push(Token::LineBreak);
+        }
+    }
+}
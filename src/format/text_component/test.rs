@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for parsing [text components][`super::TextComponent`].
+
+use super::{ComponentError, TextComponent, TokenizeError};
+use crate::{
+    syntax::{
+        minecraft::{ClickEvent, Content, Font, Format, HoverEvent, Rgb, ShadowColor},
+        ConversionError, Token,
+    },
+    Tokenize,
+};
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+#[allow(clippy::too_many_lines)]
+#[test]
+fn test_component() -> Result {
+    /// Compare the tokens of the component in `$input` and the expected output.
+    macro_rules! test {
+        ( $( $input:expr => $expects:expr );+ ; ) => {
+            $(
+                assert_eq!(
+                    TextComponent::tokenize_string($input)?.tokens_as_slice(),
+                    $expects,
+                    "{}",
+                    $input
+                );
+            )+
+        };
+    }
+
+    /// Insert a [`Token::Format`] with the given variant.
+    macro_rules! format {
+        ($format:ident) => {
+            crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+        };
+    }
+
+    /// Insert a [`Token::Format`] with the given color.
+    macro_rules! color {
+        ($color:ident) => {
+            crate::syntax::Token::Format(crate::syntax::minecraft::Format::Color(
+                crate::syntax::minecraft::Color::$color.into(),
+            ))
+        };
+    }
+
+    /// Insert a [`Token::Text`] with the given string.
+    macro_rules! text {
+        ($text:expr) => {
+            crate::syntax::Token::Text($text.into())
+        };
+    }
+
+    use Token::{LineBreak, ParagraphBreak, Space};
+
+    test!(
+        r#""Plain line""# => [
+            text!("Plain"), Space,
+            text!("line"), LineBreak,
+        ];
+        r#"{"text":"Plain line"}"# => [
+            text!("Plain"), Space,
+            text!("line"), LineBreak,
+        ];
+        r#"["Plain", " ", "line"]"# => [
+            text!("Plain"), Space,
+            text!("line"), LineBreak,
+        ];
+        r#"{"text":"Hel","extra":["lo"]}"# => [
+            text!("Hello"), LineBreak,
+        ];
+        r#""""# => [];
+        r#"{"text":"first\n\nsecond\n"}"# => [
+            text!("first"), LineBreak,
+            ParagraphBreak,
+            text!("second"), LineBreak,
+        ];
+        r#"["Some ",{"text":"RED text","color":"red"}]"# => [
+            text!("Some"), Space,
+            color!(Red),
+            text!("RED"), Space,
+            text!("text"),
+            format!(Reset), LineBreak,
+        ];
+        r#"[{"text":"Italic:"},{"text":" text ","italic":true},"reset"]"# => [
+            text!("Italic:"),
+            format!(Italic), Space,
+            text!("text"), Space,
+            format!(Reset),
+            text!("reset"), LineBreak,
+        ];
+        // Array elements inherit the style of the first element
+        r#"[{"text":"a","bold":true},"b",{"text":"c","bold":false}]"# => [
+            format!(Bold),
+            text!("ab"),
+            format!(Reset),
+            text!("c"), LineBreak,
+        ];
+        // Children inherit and override the style of their parent
+        r#"{"text":"a","color":"gold","bold":true,"extra":[{"text":"b","color":"aqua"},{"text":"c","italic":true,"extra":["d"]},"e"]}"# => [
+            color!(Gold), format!(Bold),
+            text!("a"),
+            format!(Reset), color!(Aqua), format!(Bold),
+            text!("b"),
+            format!(Reset), color!(Gold), format!(Bold), format!(Italic),
+            text!("cd"),
+            format!(Reset), color!(Gold), format!(Bold),
+            text!("e"),
+            format!(Reset), LineBreak,
+        ];
+        // Adding a color would clear the formatting, so it is reset and applied again after it
+        r#"{"text":"a","bold":true,"font":"minecraft:alt","extra":[{"text":"b","color":"red"}]}"# => [
+            format!(Bold), Token::Format(Format::Font(Font::Alt)),
+            text!("a"),
+            format!(Reset), color!(Red), format!(Bold), Token::Format(Format::Font(Font::Alt)),
+            text!("b"),
+            format!(Reset), LineBreak,
+        ];
+        r#"{"text":"a","bold":true,"extra":[{"text":"b","color":"red"}]}"# => [
+            format!(Bold),
+            text!("a"),
+            format!(Reset), color!(Red), format!(Bold),
+            text!("b"),
+            format!(Reset), LineBreak,
+        ];
+        r#"{"text":"a","underlined":true,"strikethrough":true,"obfuscated":true,"color":"reset"}"# => [
+            format!(Obfuscated), format!(Strikethrough), format!(Underline),
+            text!("a"),
+            format!(Reset), LineBreak,
+        ];
+        // Format codes apply until the end of the text
+        r#"["§lbold§r plain",{"text":" §cred"}," plain"]"# => [
+            format!(Bold),
+            text!("bold"),
+            format!(Reset), Space,
+            text!("plain"), Space,
+            color!(Red),
+            text!("red"), format!(Reset), Space,
+            text!("plain"), LineBreak,
+        ];
+        // A reset code goes back to the style of the component, not to no style at all
+        r#"{"text":"a§lb§rc","color":"red"}"# => [
+            color!(Red),
+            text!("a"),
+            format!(Bold),
+            text!("b"),
+            format!(Reset), color!(Red),
+            text!("c"),
+            format!(Reset), LineBreak,
+        ];
+        // Like in Minecraft, a color code clears the formatting before it, but not a color field
+        r#"[{"text":"§la§cb","italic":true},{"text":"c","bold":true,"color":"gold"}]"# => [
+            format!(Bold), format!(Italic),
+            text!("a"),
+            format!(Reset), color!(Red),
+            text!("b"),
+            format!(Reset), color!(Gold), format!(Bold), format!(Italic),
+            text!("c"),
+            format!(Reset), LineBreak,
+        ];
+        // Hex colors, since 1.16
+        r##"{"text":"a","color":"#12AB34"}"## => [
+            Token::Format(Format::Color(Rgb::new(0x12, 0xAB, 0x34).into())),
+            text!("a"),
+            format!(Reset), LineBreak,
+        ];
+        // Events apply like formatting, and survive format codes
+        r#"["",{"text":"Chapter 2","clickEvent":{"action":"change_page","value":"3"}}," §lend"]"# => [
+            Token::Click(ClickEvent::ChangePage(3)),
+            text!("Chapter"), Space,
+            text!("2"),
+            format!(Reset), Space,
+            format!(Bold),
+            text!("end"),
+            format!(Reset), LineBreak,
+        ];
+        r#"{"text":"a§lb","hoverEvent":{"action":"show_text","contents":{"text":"tip","italic":true}}}"# => [
+            Token::Hover(HoverEvent::ShowText(Box::new([format!(Italic), text!("tip")]))),
+            text!("a"),
+            format!(Bold),
+            text!("b"),
+            format!(Reset), LineBreak,
+        ];
+        // A child adding an event does not need a reset, but removing one does
+        r#"{"text":"a","extra":[{"text":"b","clickEvent":{"action":"open_url","value":"https://example.com"}},"c"]}"# => [
+            text!("a"),
+            Token::Click(ClickEvent::OpenUrl("https://example.com".into())),
+            text!("b"),
+            format!(Reset),
+            text!("c"), LineBreak,
+        ];
+        // Unsupported actions are left out
+        r#"{"text":"a","clickEvent":{"action":"open_file","value":"book.txt"},"hoverEvent":{"action":"show_item","contents":{"id":"minecraft:book"}}}"# => [
+            text!("a"), LineBreak,
+        ];
+        // SNBT-style components, as stored in 1.21.5+
+        "{text:'a',click_event:{action:'change_page',page:2},hover_event:{action:'show_text',value:'b'}}" => [
+            Token::Click(ClickEvent::ChangePage(2)),
+            Token::Hover(HoverEvent::ShowText(Box::new([text!("b")]))),
+            text!("a"),
+            format!(Reset), LineBreak,
+        ];
+        "[{text:'a',click_event:{action:'run_command',command:'/say hi'}},{text:'b',click_event:{action:'suggest_command',command:'/help'}},{text:'c',click_event:{action:'copy_to_clipboard',value:'copied'}}]" => [
+            Token::Click(ClickEvent::RunCommand("/say hi".into())),
+            text!("a"),
+            Token::Click(ClickEvent::SuggestCommand("/help".into())),
+            text!("b"),
+            Token::Click(ClickEvent::CopyToClipboard("copied".into())),
+            text!("c"),
+            format!(Reset), LineBreak,
+        ];
+        "{text:'a',bold:1b,extra:[5]}" => [
+            format!(Bold),
+            text!("a5"),
+            format!(Reset), LineBreak,
+        ];
+        // Fonts from resource packs fall back to the default font
+        r#"{"text":"a","font":"minecraft:alt","extra":[{"text":"b","font":"illageralt"},{"text":"c","font":"pack:fancy"}]}"# => [
+            Token::Format(Format::Font(Font::Alt)),
+            text!("a"),
+            format!(Reset),
+            Token::Format(Format::Font(Font::IllagerAlt)),
+            text!("b"),
+            format!(Reset),
+            text!("c"), LineBreak,
+        ];
+        r#"{"text":"a","shadow_color":-16777216,"extra":[{"text":"b","shadow_color":[1.0,0.0,0.5,0.5]}]}"# => [
+            Token::Format(Format::ShadowColor(ShadowColor::new(Rgb::new(0, 0, 0), 255))),
+            text!("a"),
+            format!(Reset),
+            Token::Format(Format::ShadowColor(ShadowColor::new(Rgb::new(255, 0, 128), 128))),
+            text!("b"),
+            format!(Reset), LineBreak,
+        ];
+        // Content is kept as is, in the style of its component
+        r#"["Hi ",{"translate":"chat.type.text","fallback":"<%s> %s","with":["Steve",{"text":"hey","bold":true}],"italic":true}]"# => [
+            text!("Hi"), Space,
+            format!(Italic),
+            Token::Content(Content::Translate {
+                key: "chat.type.text".into(),
+                fallback: Some("<%s> %s".into()),
+                with: Box::new([
+                    Box::new([text!("Steve")]),
+                    Box::new([format!(Bold), text!("hey"), format!(Reset)]),
+                ]),
+            }),
+            format!(Reset), LineBreak,
+        ];
+        "[{keybind:'key.jump'},' ',{score:{name:'@s',objective:'deaths'}},' ',{selector:'@a',separator:{text:'|'}}]" => [
+            Token::Content(Content::Keybind("key.jump".into())), Space,
+            Token::Content(Content::Score {
+                name: "@s".into(),
+                objective: "deaths".into(),
+            }),
+            Space,
+            Token::Content(Content::Selector {
+                pattern: "@a".into(),
+                separator: Some(Box::new([text!("|")])),
+            }),
+            LineBreak,
+        ];
+        // Like events, an insertion is kept through a `'§'` reset
+        r#"{"text":"a §lb§rc","insertion":"/say hi"}"# => [
+            Token::Insertion("/say hi".into()),
+            text!("a"), Space,
+            format!(Bold),
+            text!("b"),
+            format!(Reset),
+            Token::Insertion("/say hi".into()),
+            text!("c"),
+            format!(Reset), LineBreak,
+        ];
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    /// Assert that tokenizing `$input` fails with an error matching `$pattern`.
+    macro_rules! fails {
+        ( $( $input:expr => $pattern:pat ),+ $(,)? ) => {
+            $(
+                let result = TextComponent::tokenize_string($input);
+                assert!(matches!(result, Err($pattern)), "{}: {result:?}", $input);
+            )+
+        };
+    }
+
+    fails!(
+        r#"{"text":"a""# => TokenizeError::Syntax(_),
+        r#"{"text":"a"} {}"# => TokenizeError::Syntax(_),
+        r#"{"text":"a","color":"orange"}"# => TokenizeError::Component(ComponentError::Conversion(
+            ConversionError::NoSuchColor(_)
+        )),
+        r##"{"text":"a","color":"#12AB3"}"## => TokenizeError::Component(
+            ComponentError::Conversion(ConversionError::InvalidHexColor(_))
+        ),
+        r#"{"text":"a","clickEvent":{"value":"2"}}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "action", .. }
+        ),
+        r#"{"text":"a","clickEvent":{"action":"change_page","value":"zero"}}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "page", .. }
+        ),
+        "{text:'a',click_event:{action:'change_page',page:0}}" => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "page", .. }
+        ),
+        r#"{"text":"a","hoverEvent":{"action":"show_text"}}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "value", .. }
+        ),
+        r#"{"text":"a","color":5}"# => TokenizeError::Component(ComponentError::UnexpectedType {
+            field: "color",
+            ..
+        }),
+        r#"{"text":"a","bold":"yes"}"# => TokenizeError::Component(ComponentError::UnexpectedType {
+            field: "bold",
+            ..
+        }),
+        r#"{"text":"a","extra":"b"}"# => TokenizeError::Component(ComponentError::UnexpectedType {
+            field: "extra",
+            ..
+        }),
+        r#"{"text":"a","font":5}"# => TokenizeError::Component(ComponentError::UnexpectedType {
+            field: "font",
+            ..
+        }),
+        r#"{"text":"a","shadow_color":"black"}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "shadow_color", .. }
+        ),
+        r#"{"text":"a","shadow_color":[0.0,0.0,0.0]}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "shadow_color", .. }
+        ),
+        r#"{"text":"a","insertion":1}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "insertion", .. }
+        ),
+        r#"{"translate":"a","with":"b"}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "with", .. }
+        ),
+        r#"{"score":{"name":"@s"}}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "objective", .. }
+        ),
+        r#"{"keybind":5}"# => TokenizeError::Component(
+            ComponentError::UnexpectedType { field: "keybind", .. }
+        ),
+        r#"{"text":"a§"}"# => TokenizeError::Component(ComponentError::Conversion(
+            ConversionError::MissingFormatCode
+        )),
+        r#"{"text":"§za"}"# => TokenizeError::Component(ComponentError::Conversion(
+            ConversionError::NoSuchFormatCode('z')
+        )),
+    );
+}
@@ -17,5 +17,21 @@
 
 //! Implementations of [`Tokenize`][`crate::Tokenize`].
 
+pub use crate::format::anvil::Anvil;
+pub use crate::format::anvil::TokenizeError as AnvilTokenizeError;
+pub use crate::format::nbt::book::FoundBooks;
+pub use crate::format::nbt::book::SkippedBook;
+pub use crate::format::nbt::BookError;
+pub use crate::format::nbt::NbtError;
+pub use crate::format::player_data::PlayerData;
+pub use crate::format::player_data::TokenizeError as PlayerDataTokenizeError;
+pub use crate::format::snbt::Snbt;
+pub use crate::format::snbt::SyntaxError as SnbtSyntaxError;
+pub use crate::format::snbt::TokenizeError as SnbtTokenizeError;
+pub use crate::format::stendhal::FormatCodeRecovery;
+pub use crate::format::stendhal::Recovered;
 pub use crate::format::stendhal::Stendhal;
 pub use crate::format::stendhal::TokenizeError as StendhalTokenizeError;
+pub use crate::format::text_component::ComponentError;
+pub use crate::format::text_component::TextComponent;
+pub use crate::format::text_component::TokenizeError as TextComponentTokenizeError;
@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Laying out text like the book GUI of Minecraft: Java Edition.
+//!
+//! See [`Layout`], and [`repaginate`] to split a work into pages that fit.
+
+use crate::{
+    resolve::Untranslated,
+    syntax::{minecraft::Style, Token, TokenList},
+};
+pub use paginate::{repaginate, Repaginated, MAX_PAGES};
+pub use widths::{advance, is_renderable, width};
+
+mod paginate;
+#[cfg(test)]
+mod test;
+mod widths;
+
+/// The width of a line of a book, in pixels.
+pub const LINE_WIDTH: u32 = 114;
+
+/// The number of lines shown on each page of a book.
+pub const LINES_PER_PAGE: usize = 14;
+
+/// A work, wrapped into lines like the book GUI wraps it.
+///
+/// Each [`Page`][`crate::syntax::Page`] is wrapped on its own, breaking lines after the last space
+/// that fits within [`LINE_WIDTH`] (or in the middle of a word that does not fit on a line of its
+/// own), and at every line break. Lines past [`LINES_PER_PAGE`] are not shown in game.
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::{
+///     import::Stendhal,
+///     layout::{Layout, LINES_PER_PAGE},
+///     Tokenize,
+/// };
+///
+/// let input = "title: crafty_novels
+/// author: RemasteredArch
+/// pages:
+/// #- A line that is too long to fit on a single line of a book";
+///
+/// let layout = Layout::new(&Stendhal::tokenize_string(input).unwrap());
+/// let lines: Vec<String> = layout.pages()[0].lines().iter().map(|line| line.text()).collect();
+///
+/// assert_eq!(lines, ["A line that is too long", "to fit on a single line", "of a book"]);
+/// assert!(!layout.pages()[0].overflows());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Layout {
+    /// The layout of each page.
+    pages: Box<[PageLayout]>,
+}
+
+impl Layout {
+    /// Wraps every page of `tokens`.
+    ///
+    /// Content is filled in with [`Untranslated`] first, see [`crate::resolve`].
+    #[must_use]
+    pub fn new(tokens: &TokenList) -> Self {
+        let tokens = tokens.resolve(&Untranslated);
+
+        Self {
+            pages: tokens
+                .pages()
+                .map(|page| PageLayout {
+                    number: page.number(),
+                    lines: wrap(page.tokens()).into(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the layout of every page.
+    #[must_use]
+    pub const fn pages(&self) -> &[PageLayout] {
+        &self.pages
+    }
+
+    /// Returns the layout of the page with the given number, counting from one.
+    #[must_use]
+    pub fn page(&self, number: usize) -> Option<&PageLayout> {
+        self.pages.get(number.checked_sub(1)?)
+    }
+}
+
+/// A single page of a [`Layout`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PageLayout {
+    /// The number of this page in the work, counting from one.
+    number: usize,
+    /// Every line of this page, including those that are not shown.
+    lines: Box<[Line]>,
+}
+
+impl PageLayout {
+    /// The number of this page in the work, counting from one.
+    #[must_use]
+    pub const fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Every line of this page, including those past [`LINES_PER_PAGE`] that are not shown.
+    #[must_use]
+    pub const fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// The lines of this page that are shown in game.
+    #[must_use]
+    pub fn visible_lines(&self) -> &[Line] {
+        &self.lines[..self.lines.len().min(LINES_PER_PAGE)]
+    }
+
+    /// Whether this page has more lines than are shown in game.
+    #[must_use]
+    pub const fn overflows(&self) -> bool {
+        self.lines.len() > LINES_PER_PAGE
+    }
+}
+
+/// A single line of a [`PageLayout`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Line {
+    /// The style that applies at the start of the line.
+    style: Style,
+    /// The contents of the line, without the space or line break that ends it.
+    tokens: Box<[Token]>,
+    /// The space or line break that ends the line, if any.
+    end: Option<Token>,
+    /// The width of the line, in pixels.
+    width: u32,
+}
+
+impl Line {
+    /// The style that applies at the start of the line, carried over from the lines before it.
+    #[must_use]
+    pub const fn style(&self) -> &Style {
+        &self.style
+    }
+
+    /// The contents of the line, without the space or line break that ends it.
+    ///
+    /// Every space is a [`Token::Space`], and hidden tokens are kept where they apply.
+    #[must_use]
+    pub const fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// The token that ends the line: the line break it ends at, or the space it was wrapped at.
+    ///
+    /// [`None`] if the line was wrapped in the middle of a word, or if it is the last line of its
+    /// page and does not end with a line break.
+    #[must_use]
+    pub const fn end(&self) -> Option<&Token> {
+        self.end.as_ref()
+    }
+
+    /// Whether the line has no text, like the empty line that a [`Token::ParagraphBreak`] is.
+    #[must_use]
+    pub fn is_blank(&self) -> bool {
+        !self
+            .tokens
+            .iter()
+            .any(|token| matches!(token, Token::Text(_) | Token::Space))
+    }
+
+    /// The width of the line, in pixels.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The text of the line, without any formatting.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Text(text) => Some(text.as_ref()),
+                Token::Space => Some(" "),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A piece of a page, as the book GUI reads it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Item<'t> {
+    /// A character and its advance, in pixels.
+    Char(char, u32),
+    /// A hidden token.
+    Hidden(&'t Token),
+    /// A line break.
+    Break(&'t Token),
+}
+
+/// Breaks `tokens` into lines like the book GUI does.
+fn wrap(tokens: &[Token]) -> Vec<Line> {
+    let items = items(tokens);
+
+    let mut lines = vec![];
+    let mut style = Style::new();
+    let mut start = 0;
+
+    while start < items.len() {
+        let (end, next) = line_end(&items[start..]);
+        let line = line(
+            &items[start..start + end],
+            items.get(start + end),
+            style.clone(),
+        );
+
+        for item in &items[start..start + end] {
+            if let Item::Hidden(token) = item {
+                style.apply(token);
+            }
+        }
+
+        lines.push(line);
+        start += next;
+    }
+
+    lines
+}
+
+/// Returns the [`Item`]s of `tokens`, measured in the style that applies to them.
+fn items(tokens: &[Token]) -> Vec<Item<'_>> {
+    let mut style = Style::new();
+    let mut items = vec![];
+
+    for token in tokens {
+        match token {
+            Token::Text(text) => {
+                items.extend(
+                    text.chars()
+                        .map(|char| Item::Char(char, advance(char, style.is_bold()))),
+                );
+            }
+            Token::Space => items.push(Item::Char(' ', advance(' ', style.is_bold()))),
+            Token::LineBreak | Token::ParagraphBreak => items.push(Item::Break(token)),
+            Token::Format(_) | Token::Click(_) | Token::Hover(_) | Token::Insertion(_) => {
+                style.apply(token);
+                items.push(Item::Hidden(token));
+            }
+            // Content is filled in before laying out, and pages are laid out one at a time
+            Token::Content(_) | Token::ThematicBreak => (),
+        }
+    }
+
+    items
+}
+
+/// Returns where the line at the start of `items` ends, and where the next line starts.
+///
+/// Like the book GUI, a line ends at a line break, or once it is wider than [`LINE_WIDTH`]. An
+/// overlong line is broken at its last space, or before the character that made it too wide if it
+/// has no spaces, but always keeps at least one character. The space or line break that a line is
+/// broken at is left out of both lines.
+fn line_end(items: &[Item<'_>]) -> (usize, usize) {
+    let mut width = 0;
+    let mut last_space = None;
+    let mut has_width = false;
+
+    for (index, item) in items.iter().enumerate() {
+        match *item {
+            Item::Break(_) => return (index, index + 1),
+            Item::Char(char, advance) => {
+                if char == ' ' {
+                    last_space = Some(index);
+                }
+
+                width += advance;
+                if has_width && width > LINE_WIDTH {
+                    return last_space.map_or((index, index), |space| (space, space + 1));
+                }
+
+                has_width |= advance != 0;
+            }
+            Item::Hidden(_) => (),
+        }
+    }
+
+    (items.len(), items.len())
+}
+
+/// Builds a [`Line`] from its [`Item`]s, starting in `style` and ended by `end`.
+fn line(items: &[Item<'_>], end: Option<&Item<'_>>, style: Style) -> Line {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut width = 0;
+
+    for item in items {
+        match *item {
+            Item::Char(' ', advance) => {
+                push_text(&mut tokens, &mut text);
+                tokens.push(Token::Space);
+                width += advance;
+            }
+            Item::Char(char, advance) => {
+                text.push(char);
+                width += advance;
+            }
+            Item::Hidden(token) => {
+                push_text(&mut tokens, &mut text);
+                tokens.push(token.clone());
+            }
+            Item::Break(_) => (),
+        }
+    }
+    push_text(&mut tokens, &mut text);
+
+    let end = match end {
+        Some(Item::Break(token)) => Some((*token).clone()),
+        Some(Item::Char(' ', _)) => Some(Token::Space),
+        _ => None,
+    };
+
+    Line {
+        style,
+        tokens: tokens.into(),
+        end,
+        width,
+    }
+}
+
+/// Empty `text` into a [`Token::Text`] at the end of `tokens`, if it holds anything.
+fn push_text(tokens: &mut Vec<Token>, text: &mut String) {
+    if !text.is_empty() {
+        tokens.push(Token::Text(std::mem::take(text).into()));
+    }
+}
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Splitting a work into pages that fit the book GUI.
+//!
+//! See [`repaginate`].
+
+use super::{wrap, Line, LINES_PER_PAGE};
+use crate::{
+    resolve::Untranslated,
+    syntax::{Token, TokenList},
+};
+
+/// The most pages a signed book can have.
+pub const MAX_PAGES: usize = 100;
+
+/// The fewest lines a page is filled with before it may end early at a paragraph.
+const MIN_LINES: usize = LINES_PER_PAGE / 2;
+
+/// Splits every page of `tokens` into as many pages as it takes to fit it in the book GUI.
+///
+/// Pages that already fit are kept as they are, and a page that does not is split:
+///
+/// - At the last empty line that leaves the page at least half full, which is left out
+/// - Otherwise, at the last line break that leaves the page at least half full
+/// - Otherwise, after the last line that fits on the page, which was wrapped at a space unless a
+///   single word did not fit on a line
+///
+/// The formatting that applies where a page is split is applied again at the start of the next
+/// page, because formatting ends with the page in game. Content is filled in with
+/// [`Untranslated`] first, see [`crate::resolve`].
+///
+/// The result may have more pages than a book can hold, see [`Repaginated::exceeds_page_limit`].
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::{
+///     layout::{repaginate, Layout},
+///     syntax::{minecraft::Format, Token, TokenList},
+/// };
+///
+/// let mut tokens = vec![Token::Format(Format::Italic)];
+/// for _ in 0..40 {
+///     tokens.extend([Token::Text("line".into()), Token::LineBreak]);
+/// }
+///
+/// let repaginated = repaginate(&TokenList::new_from_boxed(Box::new([]), tokens.into()));
+/// assert_eq!(repaginated.page_count(), 3);
+/// assert!(!repaginated.exceeds_page_limit());
+///
+/// // Italics carry over onto each page
+/// let layout = Layout::new(repaginated.tokens());
+/// assert_eq!(layout.pages()[2].lines()[0].tokens()[0], Token::Format(Format::Italic));
+/// assert!(layout.pages().iter().all(|page| !page.overflows()));
+/// ```
+#[must_use]
+pub fn repaginate(tokens: &TokenList) -> Repaginated {
+    let tokens = tokens.resolve(&Untranslated);
+
+    let mut pages = vec![];
+    for page in tokens.pages() {
+        let lines = wrap(page.tokens());
+
+        if lines.len() <= LINES_PER_PAGE {
+            pages.push(page.tokens().to_vec());
+        } else {
+            split(&lines, &mut pages);
+        }
+    }
+
+    Repaginated {
+        page_count: pages.len(),
+        tokens: TokenList::new_from_pages(tokens.metadata_as_slice().into(), pages),
+    }
+}
+
+/// A work split into pages by [`repaginate`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Repaginated {
+    /// The work, starting with a [`Token::ThematicBreak`] for each page.
+    tokens: TokenList,
+    /// The number of pages in `tokens`.
+    page_count: usize,
+}
+
+impl Repaginated {
+    /// The work, split into pages.
+    #[must_use]
+    pub const fn tokens(&self) -> &TokenList {
+        &self.tokens
+    }
+
+    /// Returns the work, split into pages.
+    #[must_use]
+    pub fn into_tokens(self) -> TokenList {
+        self.tokens
+    }
+
+    /// The number of pages in the work.
+    #[must_use]
+    pub const fn page_count(&self) -> usize {
+        self.page_count
+    }
+
+    /// Whether the work has more than [`MAX_PAGES`] pages, which a book cannot hold.
+    #[must_use]
+    pub const fn exceeds_page_limit(&self) -> bool {
+        self.page_count > MAX_PAGES
+    }
+}
+
+/// Split `lines` into pages, pushing the tokens of each into `pages`.
+fn split(lines: &[Line], pages: &mut Vec<Vec<Token>>) {
+    let mut start = 0;
+
+    while start < lines.len() {
+        let rest = &lines[start..];
+        let (end, next) = page_end(rest);
+
+        let mut page: Vec<Token> = rest[0].style().tokens().collect();
+        for (index, line) in rest[..end].iter().enumerate() {
+            page.extend(line.tokens().iter().cloned());
+
+            match line.end() {
+                // The space a page is split at is left out
+                Some(Token::Space) if index + 1 == end => (),
+                end => page.extend(end.cloned()),
+            }
+        }
+        pages.push(page);
+
+        start += next;
+        // A page should not start with an empty line
+        while lines.get(start).is_some_and(Line::is_blank) {
+            start += 1;
+        }
+    }
+}
+
+/// Returns how many of `lines` fit on the next page, and where the page after it starts.
+fn page_end(lines: &[Line]) -> (usize, usize) {
+    if lines.len() <= LINES_PER_PAGE {
+        return (lines.len(), lines.len());
+    }
+
+    let candidates = MIN_LINES..=LINES_PER_PAGE;
+
+    // Before an empty line, which is left out
+    if let Some(end) = candidates.clone().rev().find(|&end| lines[end].is_blank()) {
+        return (end, end + 1);
+    }
+
+    // After a line break
+    if let Some(end) = candidates
+        .rev()
+        .find(|&end| matches!(lines[end - 1].end(), Some(Token::LineBreak)))
+    {
+        return (end, end);
+    }
+
+    (LINES_PER_PAGE, LINES_PER_PAGE)
+}
@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for laying out text like the book GUI.
+
+use super::{advance, repaginate, width, Layout, LINES_PER_PAGE, LINE_WIDTH, MAX_PAGES};
+use crate::syntax::{minecraft::Style, Token, TokenList};
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+/// Insert a [`Token::Format`] with the given [`Format`][`crate::syntax::minecraft::Format`]
+/// variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Lays out a single page holding `tokens`.
+fn lay_out(tokens: impl Into<Box<[Token]>>) -> Layout {
+    Layout::new(&TokenList::new_from_boxed(Box::new([]), tokens.into()))
+}
+
+/// Returns the text of every line of the first page of `layout`.
+fn lines(layout: &Layout) -> Vec<String> {
+    layout.pages()[0]
+        .lines()
+        .iter()
+        .map(super::Line::text)
+        .collect()
+}
+
+#[test]
+fn test_widths() {
+    assert_eq!(width("Hello", false), 6 + 6 + 3 + 3 + 6);
+    assert_eq!(width("Hello", true), width("Hello", false) + 5);
+    assert_eq!(width("i l!", false), 2 + 4 + 3 + 2);
+    assert_eq!(advance('\u{4E2D}', false), 9);
+    assert_eq!(advance('é', false), 6);
+}
+
+#[test]
+fn test_wrapping() {
+    use Token::{LineBreak, ParagraphBreak, Space};
+
+    // Exactly as wide as a line
+    let full = "a".repeat(19);
+    assert_eq!(width(&full, false), LINE_WIDTH);
+    assert_eq!(lines(&lay_out([text!(full.as_str())])), [full.as_str()]);
+
+    // A word that does not fit on a line of its own is broken before the character that does not
+    // fit
+    let layout = lay_out([text!("a".repeat(20))]);
+    assert_eq!(lines(&layout), [full.as_str(), "a"]);
+    assert_eq!(layout.pages()[0].lines()[0].width(), LINE_WIDTH);
+
+    // Otherwise, lines are broken at the last space, which is left out
+    let words = [
+        text!("a".repeat(10)),
+        Space,
+        text!("a".repeat(10)),
+        Space,
+        text!("b"),
+    ];
+    assert_eq!(
+        lines(&lay_out(words)),
+        ["a".repeat(10), "a".repeat(10) + " b"]
+    );
+
+    // Bold text is wider
+    assert_eq!(
+        lines(&lay_out([format!(Bold), text!("a".repeat(17))])),
+        ["a".repeat(16), "a".to_string()]
+    );
+
+    // Every line ends with a line break, and an empty line is a paragraph break
+    let layout = lay_out([
+        text!("one"),
+        LineBreak,
+        ParagraphBreak,
+        ParagraphBreak,
+        text!("two"),
+        LineBreak,
+    ]);
+    assert_eq!(lines(&layout), ["one", "", "", "two"]);
+
+    // Formatting carries over onto the next line
+    let layout = lay_out([
+        format!(Italic),
+        text!("a".repeat(20)),
+        format!(Reset),
+        text!("b"),
+    ]);
+    let [first, second] = layout.pages()[0].lines() else {
+        panic!("expected two lines");
+    };
+    assert!(first.style().is_plain());
+    assert!(second.style().is_italic());
+    assert_eq!(second.tokens(), [text!("a"), format!(Reset), text!("b")]);
+}
+
+#[test]
+fn test_pages() {
+    use Token::{LineBreak, ThematicBreak};
+
+    let mut tokens = vec![ThematicBreak];
+    for _ in 0..=LINES_PER_PAGE {
+        tokens.extend([text!("line"), LineBreak]);
+    }
+    tokens.extend([ThematicBreak, ThematicBreak, format!(Bold), text!("last")]);
+
+    let layout = lay_out(tokens);
+    let [first, empty, last] = layout.pages() else {
+        panic!("expected three pages, found {:?}", layout.pages());
+    };
+
+    assert_eq!(first.lines().len(), LINES_PER_PAGE + 1);
+    assert_eq!(first.visible_lines().len(), LINES_PER_PAGE);
+    assert!(first.overflows());
+
+    assert!(empty.lines().is_empty());
+    assert!(!empty.overflows());
+
+    // Formatting ends with the page
+    assert_eq!(last.number(), 3);
+    assert_eq!(last.lines()[0].style(), &Style::new());
+    assert_eq!(last.lines()[0].width(), width("last", true));
+    assert_eq!(layout.page(3), Some(last));
+    assert_eq!(layout.page(0), None);
+}
+
+#[test]
+fn test_repaginate() {
+    use Token::{LineBreak, ParagraphBreak, Space, ThematicBreak};
+
+    // Pages are split at the last empty line that leaves them at least half full, and then at
+    // line breaks
+    let mut tokens = vec![];
+    for _ in 0..10 {
+        tokens.extend([text!("a"), LineBreak]);
+    }
+    tokens.push(ParagraphBreak);
+    for _ in 0..20 {
+        tokens.extend([text!("b"), LineBreak]);
+    }
+    let repaginated = repaginate(&TokenList::new_from_boxed(Box::new([]), tokens.into()));
+    let layout = Layout::new(repaginated.tokens());
+
+    assert_eq!(repaginated.page_count(), 3);
+    assert_eq!(
+        layout
+            .pages()
+            .iter()
+            .map(|page| page.lines().len())
+            .collect::<Vec<_>>(),
+        [10, 14, 6]
+    );
+
+    // A long paragraph is split between words, and formatting carries onto the next page
+    let mut tokens = vec![format!(Bold)];
+    for _ in 0..300 {
+        tokens.extend([text!("word"), Space]);
+    }
+    let repaginated = repaginate(&TokenList::new_from_boxed(Box::new([]), tokens.into()));
+    let layout = Layout::new(repaginated.tokens());
+
+    assert!(layout.pages().len() > 1);
+    assert!(layout.pages().iter().all(|page| !page.overflows()));
+    assert!(layout
+        .pages()
+        .iter()
+        .all(|page| page.lines()[0].tokens()[0] == format!(Bold)));
+    let words: usize = layout
+        .pages()
+        .iter()
+        .flat_map(super::PageLayout::lines)
+        .map(|line| line.text().split_whitespace().count())
+        .sum();
+    assert_eq!(words, 300);
+
+    // Pages that fit are kept as they are
+    let tokens = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([ThematicBreak, text!("one"), ThematicBreak, ThematicBreak]),
+    );
+    let repaginated = repaginate(&tokens);
+    assert_eq!(repaginated.tokens(), &tokens);
+    assert_eq!(repaginated.page_count(), 3);
+
+    // Too long for a book
+    let mut tokens = vec![];
+    for _ in 0..=MAX_PAGES * LINES_PER_PAGE {
+        tokens.extend([text!("line"), LineBreak]);
+    }
+    let repaginated = repaginate(&TokenList::new_from_boxed(Box::new([]), tokens.into()));
+    assert_eq!(repaginated.page_count(), MAX_PAGES + 1);
+    assert!(repaginated.exceeds_page_limit());
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The widths of the characters of Minecraft: Java Edition's default font.
+//!
+//! See [`advance`].
+
+/// The advance of each printable ASCII character (`' '` through `'~'`) in the default font, in
+/// pixels.
+///
+/// Each is the width of the glyph in `ascii.png`, plus the pixel of space that follows it.
+#[rustfmt::skip]
+const ASCII: [u8; 95] = [
+    // ' ' ! " # $ % & ' ( ) * + , - . /
+    4, 2, 4, 6, 6, 6, 6, 2, 4, 4, 4, 6, 2, 6, 2, 6,
+    // 0-9
+    6, 6, 6, 6, 6, 6, 6, 6, 6, 6,
+    // : ; < = > ? @
+    2, 2, 5, 6, 5, 6, 7,
+    // A-Z
+    6, 6, 6, 6, 6, 6, 6, 6, 4, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6,
+    // [ \ ] ^ _ `
+    4, 6, 4, 6, 6, 3,
+    // a-z
+    6, 6, 6, 6, 6, 5, 6, 6, 2, 6, 5, 3, 6, 6, 6, 6, 6, 6, 6, 4, 6, 6, 6, 6, 6, 6,
+    // { | } ~
+    4, 2, 4, 7,
+];
+
+/// The advance of most characters outside of ASCII, which are drawn from bitmaps of the same size.
+const DEFAULT: u32 = 6;
+
+/// The advance of wide characters (ex. Chinese, Japanese, and Korean), which are drawn at half
+/// the size of their glyph in GNU Unifont.
+const WIDE: u32 = 9;
+
+/// Returns how far the cursor moves after drawing `char` in the default font, in pixels.
+///
+/// Bold text is drawn twice, one pixel apart, so it is one pixel wider. Advances are exact for
+/// printable ASCII; other characters are estimated by whether they are wide.
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::layout::advance;
+///
+/// assert_eq!(advance('a', false), 6);
+/// assert_eq!(advance('i', false), 2);
+/// assert_eq!(advance('i', true), 3);
+/// ```
+#[must_use]
+pub const fn advance(char: char, bold: bool) -> u32 {
+    let advance = match char {
+        ' '..='~' => ASCII[char as usize - ' ' as usize] as u32,
+        '\0'..='\u{1F}' | '\u{7F}'..='\u{9F}' | '\u{200B}'..='\u{200F}' => return 0,
+        char if is_wide(char) => WIDE,
+        _ => DEFAULT,
+    };
+
+    if bold {
+        advance + 1
+    } else {
+        advance
+    }
+}
+
+/// Returns the sum of the [`advance`]s of every character of `text`.
+#[must_use]
+pub fn width(text: &str, bold: bool) -> u32 {
+    text.chars().map(|char| advance(char, bold)).sum()
+}
+
+/// Whether Minecraft: Java Edition's default font has a glyph for `char`.
+///
+/// The default font falls back to GNU Unifont, which covers nearly every assigned character, so
+/// only control characters, private use characters, and noncharacters cannot be rendered.
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::layout::is_renderable;
+///
+/// assert!(is_renderable('a'));
+/// assert!(is_renderable('\u{4E2D}'));
+/// assert!(!is_renderable('\u{7}'));
+/// assert!(!is_renderable('\u{E000}'));
+/// ```
+#[must_use]
+pub const fn is_renderable(char: char) -> bool {
+    !matches!(
+        char,
+        '\0'..='\u{1F}'
+            | '\u{7F}'..='\u{9F}'
+            | '\u{E000}'..='\u{F8FF}'
+            | '\u{FDD0}'..='\u{FDEF}'
+            | '\u{FFF0}'..='\u{FFF8}'
+            | '\u{F0000}'..='\u{10FFFF}'
+    ) && (char as u32) & 0xFFFE != 0xFFFE
+}
+
+/// Whether `char` takes up the space of two characters, like Chinese, Japanese, and Korean
+/// characters.
+const fn is_wide(char: char) -> bool {
+    matches!(
+        char,
+        '\u{1100}'..='\u{115F}'
+            | '\u{2E80}'..='\u{303E}'
+            | '\u{3041}'..='\u{33FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{A000}'..='\u{A4CF}'
+            | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FF60}'
+            | '\u{FFE0}'..='\u{FFE6}'
+            | '\u{20000}'..='\u{3FFFD}'
+    )
+}
@@ -26,8 +26,12 @@
 //! [`TokenList`].
 //! Structs that implement [`Export`] take that [`TokenList`], convert it to their format, and
 //! write that to the output.
+//! Both are configured instances, built with builder-style options, and have associated
+//! functions that use the default options.
 //!
-//! Built-in implementations can be found in [`import`] and [`export`].
+//! Built-in implementations can be found in [`import`] and [`export`]. To see how a work wraps
+//! in the book GUI of Minecraft: Java Edition, see [`layout`], and to check it against the limits
+//! of books, see [`validate`].
 //!
 //! # Examples
 //!
@@ -52,9 +56,14 @@
 //! );
 //!
 //! let token_list = Stendhal::tokenize_string(input)?;
-//! let html = Html::export_token_vector_to_string(token_list);
+//! let html = Html::export_token_vector_to_string(token_list.clone());
 //!
 //! assert_eq!(html.as_ref(), expects);
+//!
+//! // Options are set on an instance of the exporter
+//! let html = Html::new().language("en-GB").export_to_string(&token_list);
+//!
+//! assert!(html.starts_with(r#"<!DOCTYPE html><html lang="en-GB" dir="ltr">"#));
 //! #
 //! #     Ok(())
 //! # }
@@ -79,25 +88,66 @@ use syntax::TokenList;
 pub mod export;
 mod format;
 pub mod import;
+pub mod layout;
+pub mod resolve;
 pub mod syntax;
+pub mod validate;
 mod writer;
 
 /// Methods for exporting [`TokenList`]s into other document formats.
 ///
+/// Exporters are configured instances, usually built with a `new` function followed by
+/// builder-style options, ex. `Html::new().language("fr")`. For the default options,
+/// [`Self::export_token_vector_to_string`] and [`Self::export_token_vector_to_writer`] can be used
+/// without creating one.
+///
 /// # Implementation
 ///
-/// Behavior should be exactly the same between the two methods, so it is suggested that
-/// [`Self::export_token_vector_to_string`] simply pass a [`Vec<u8>`] to
-/// [`Self::export_token_vector_to_writer`].
+/// Only [`Self::export_to_writer`] needs to be implemented. [`Self::export_to_string`] passes it a
+/// [`Vec<u8>`], which is infallible as long as the exporter only writes UTF-8, ex. by writing
+/// through a UTF-8 wrapper over the output.
 ///
-/// Implementing it this way can still be infallible:
-/// As of Rust 1.80.1, `.write_all` is infallible for [`Vec<u8>`], and a UTF-8 wrapper over a
-/// [`std::io::BufWriter`] can render [`String::from_utf8`] infallible.
+/// Options should be added as builder methods on the exporter, rather than as new traits, and the
+/// exporter should implement [`Default`] to get the convenience methods.
 pub trait Export {
-    /// Parse a given abstract syntax vector into a certain format, then output that as a string.
-    fn export_token_vector_to_string(tokens: TokenList) -> Box<str>;
+    /// Parse a given abstract syntax vector into a certain format using these options, then output
+    /// that as a string.
+    #[must_use]
+    fn export_to_string(&self, tokens: &TokenList) -> Box<str> {
+        let mut bytes: Vec<u8> = vec![];
+
+        self.export_to_writer(tokens, &mut bytes)
+            // https://github.com/rust-lang/rust/blob/1.80.1/library/std/src/io/impls.rs#L433-L437
+            // https://github.com/rust-lang/rust/blob/1.80.1/library/alloc/src/vec/mod.rs#L2569-L2592
+            .expect(
+                "the `std::io::Write` implementations for `Vec<u8>` are infallible (as of 1.80.1)",
+            );
+
+        String::from_utf8(bytes)
+            .expect("exporters only write UTF-8 encoded types")
+            .into_boxed_str()
+    }
+
+    /// Parse a given abstract syntax vector into a certain format using these options, writing
+    /// the result into `output`.
+    ///
+    /// # Errors
+    ///
+    /// - [`std::io::Error`] if it cannot write into `output`
+    fn export_to_writer(&self, tokens: &TokenList, output: &mut impl Write) -> std::io::Result<()>;
+
+    /// Parse a given abstract syntax vector into a certain format with the default options, then
+    /// output that as a string.
+    #[must_use]
+    fn export_token_vector_to_string(tokens: TokenList) -> Box<str>
+    where
+        Self: Default,
+    {
+        Self::default().export_to_string(&tokens)
+    }
 
-    /// Parse a given abstract syntax vector into a certain format, writing the result into `output`.
+    /// Parse a given abstract syntax vector into a certain format with the default options,
+    /// writing the result into `output`.
     ///
     /// # Errors
     ///
@@ -105,33 +155,67 @@ pub trait Export {
     fn export_token_vector_to_writer(
         tokens: TokenList,
         output: &mut impl Write,
-    ) -> std::io::Result<()>;
+    ) -> std::io::Result<()>
+    where
+        Self: Default,
+    {
+        Self::default().export_to_writer(&tokens, output)
+    }
 }
 
 /// Methods for importing documents into [`TokenList`]s.
 ///
-/// # Implementation
+/// Like [`Export`], importers are configured instances, and [`Self::tokenize_string`] and
+/// [`Self::tokenize_reader`] use the default options.
 ///
-/////////////////
+/// # Implementation
 ///
-/// -- TODO --
+/// Both [`Self::tokenize_from_string`] and [`Self::tokenize_from_reader`] need to be implemented,
+/// and should give the same result for the same input. For text formats,
+/// [`Self::tokenize_from_reader`] can simply read its input into a [`String`] and pass it to
+/// [`Self::tokenize_from_string`].
 ///
-/////////////////
+/// Options should be added as builder methods on the importer, rather than as new traits, and the
+/// importer should implement [`Default`] to get the convenience methods.
 pub trait Tokenize {
     /// All the errors that could occur while tokenizing input.
     type Error: std::error::Error;
 
-    /// Parse a string into an abstract syntax vector.
+    /// Parse a string into an abstract syntax vector using these options.
+    ///
+    /// # Errors
+    ///
+    /// Typical errors involve incorrect, malformed, or misplaced syntax.
+    fn tokenize_from_string(&self, input: &str) -> Result<TokenList, Self::Error>;
+
+    /// Parse a file into an abstract syntax vector using these options.
+    ///
+    /// # Errors
+    ///
+    /// Typical errors include I/O errors and incorrect, malformed, or misplaced syntax.
+    fn tokenize_from_reader(&self, input: impl Read) -> Result<TokenList, Self::Error>;
+
+    /// Parse a string into an abstract syntax vector with the default options.
     ///
     /// # Errors
     ///
     /// Typical errors involve incorrect, malformed, or misplaced syntax.
-    fn tokenize_string(input: &str) -> Result<TokenList, Self::Error>;
+    fn tokenize_string(input: &str) -> Result<TokenList, Self::Error>
+    where
+        Self: Default,
+    {
+        Self::default().tokenize_from_string(input)
+    }
 
-    /// Parse a file into an abstract syntax vector.
+    /// Parse a file into an abstract syntax vector with the default options.
     ///
     /// # Errors
     ///
     /// Typical errors include I/O errors and incorrect, malformed, or misplaced syntax.
-    fn tokenize_reader(input: impl Read) -> Result<TokenList, Self::Error>;
+    fn tokenize_reader(input: impl Read) -> Result<TokenList, Self::Error>
+    where
+        Self: Default,
+    {
+        Self::default().tokenize_from_reader(input)
+    }
 }
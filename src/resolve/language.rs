@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Translating with Minecraft: Java Edition's language files.
+//!
+//! See [`Language`].
+
+use super::{LanguageError, Resolver};
+use crate::format::{nbt::Tag, snbt};
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+/// A [`Resolver`] that translates with one of Minecraft: Java Edition's language files, ex.
+/// `en_us.json`.
+///
+/// Language files are a single JSON object of translation keys to translations, ex.
+/// `{"multiplayer.player.joined": "%s joined the game"}`. The game's own are found at
+/// `assets/minecraft/lang/` inside of the client's `.jar` (for `en_us.json`) or its assets (for
+/// every other language).
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Language {
+    translations: HashMap<Box<str>, Box<str>>,
+}
+
+impl Language {
+    /// Read a language file from a string.
+    ///
+    /// # Errors
+    ///
+    /// - [`LanguageError::Syntax`] if `json` is not valid JSON
+    /// - [`LanguageError::UnexpectedType`] if `json` is not an object of strings
+    pub fn from_json(json: &str) -> Result<Self, LanguageError> {
+        let object = snbt::parse::value(json)?;
+        let entries = object
+            .as_compound()
+            .ok_or(LanguageError::UnexpectedType("an object"))?;
+
+        let translations = entries
+            .iter()
+            .map(|(key, value)| match value {
+                Tag::String(translation) => Ok((key.clone(), translation.clone())),
+                _ => Err(LanguageError::UnexpectedType("a string")),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { translations })
+    }
+
+    /// Read a language file from a reader.
+    ///
+    /// # Errors
+    ///
+    /// - [`LanguageError::Syntax`] if the input is not valid JSON
+    /// - [`LanguageError::UnexpectedType`] if the input is not an object of strings
+    /// - [`LanguageError::Io`] if the input cannot be read or is not valid UTF-8
+    pub fn from_reader(mut input: impl Read) -> Result<Self, LanguageError> {
+        let mut string = String::new();
+        input.read_to_string(&mut string)?;
+
+        Self::from_json(&string)
+    }
+
+    /// Read a language file from disk, ex. `en_us.json`.
+    ///
+    /// # Errors
+    ///
+    /// - [`LanguageError::Syntax`] if the file is not valid JSON
+    /// - [`LanguageError::UnexpectedType`] if the file is not an object of strings
+    /// - [`LanguageError::Io`] if the file cannot be opened or read, or is not valid UTF-8
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, LanguageError> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Returns the translation of `key`, if it has one.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.translations.get(key).map(AsRef::as_ref)
+    }
+
+    /// Returns the number of translations.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.translations.len()
+    }
+
+    /// Whether there are no translations.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.translations.is_empty()
+    }
+}
+
+impl Resolver for Language {
+    fn translate(&self, key: &str) -> Option<&str> {
+        self.get(key)
+    }
+}
@@ -0,0 +1,380 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Filling in [`Content`], the parts of Minecraft: Java Edition text that are not stored as text.
+//!
+//! Exporters fill in any content with [`Untranslated`]. To use another [`Resolver`], like a
+//! [`Language`], call [`TokenList::resolve`][`crate::syntax::TokenList::resolve`] before
+//! exporting. They also apply Minecraft's rules for formatting, with
+//! [`TokenList::resolve_styles`][`crate::syntax::TokenList::resolve_styles`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use crafty_novels::{
+//!     export::PlainText,
+//!     resolve::Language,
+//!     syntax::{minecraft::Content, Token, TokenList},
+//!     Export,
+//! };
+//!
+//! let language = Language::from_json(r#"{"key.keyboard.space": "Space"}"#).unwrap();
+//!
+//! let tokens = TokenList::new_from_boxed(
+//!     Box::new([]),
+//!     Box::new([
+//!         Token::Text("Press".into()),
+//!         Token::Space,
+//!         Token::Content(Content::Keybind("key.jump".into())),
+//!     ]),
+//! );
+//!
+//! assert_eq!(
+//!     PlainText::export_token_vector_to_string(tokens.resolve(&language)).as_ref(),
+//!     "Press Space\n"
+//! );
+//! assert_eq!(
+//!     PlainText::export_token_vector_to_string(tokens).as_ref(),
+//!     "Press key.keyboard.space\n"
+//! );
+//! ```
+
+use crate::syntax::{
+    minecraft::{Content, Format, HoverEvent},
+    Token,
+};
+
+pub use error::LanguageError;
+pub use language::Language;
+pub(crate) use style::resolve_styles;
+
+mod error;
+mod language;
+mod style;
+#[cfg(test)]
+mod test;
+
+/// Fills in [`Content`] as it is exported.
+///
+/// Every method returns [`None`] by default, in which case:
+///
+/// - A translation is its fallback, or the key itself, like in Minecraft
+/// - A keybind is the control itself, ex. `"key.jump"`
+/// - A score is left out
+/// - A selector is written as is, ex. `"@p"`
+pub trait Resolver {
+    /// Returns the translation of `key`, ex. `"%s joined the game"` for
+    /// `"multiplayer.player.joined"`.
+    fn translate(&self, _key: &str) -> Option<&str> {
+        None
+    }
+
+    /// Returns the name of the key bound to the control `keybind`, ex. `"Space"` for
+    /// `"key.jump"`.
+    ///
+    /// By default, this is the key that Minecraft binds the control to by default, named by its
+    /// [translation][`Resolver::translate`] (ex. `"key.keyboard.space"`). Keys without a
+    /// translation are named by their character if they have one (ex. `"W"`), or by their key
+    /// otherwise.
+    fn keybind(&self, keybind: &str) -> Option<String> {
+        let (_, key) = DEFAULT_KEYS
+            .iter()
+            .find(|(control, _)| *control == keybind)?;
+
+        Some(self.translate(key).map_or_else(
+            || {
+                let name = key.rsplit('.').next().unwrap_or(key);
+
+                if name.chars().count() == 1 {
+                    name.to_uppercase()
+                } else {
+                    (*key).to_string()
+                }
+            },
+            str::to_string,
+        ))
+    }
+
+    /// Returns the score of `name` in the scoreboard objective `objective`.
+    fn score(&self, _name: &str, _objective: &str) -> Option<String> {
+        None
+    }
+
+    /// Returns the names of the players or entities that `pattern` matches.
+    fn selector(&self, _pattern: &str) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// A [`Resolver`] that knows of nothing, so that everything is filled in by the defaults
+/// described there.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Untranslated;
+
+impl Resolver for Untranslated {}
+
+/// The keys that Minecraft: Java Edition binds each control to by default.
+const DEFAULT_KEYS: &[(&str, &str)] = &[
+    ("key.attack", "key.mouse.left"),
+    ("key.use", "key.mouse.right"),
+    ("key.pickItem", "key.mouse.middle"),
+    ("key.forward", "key.keyboard.w"),
+    ("key.left", "key.keyboard.a"),
+    ("key.back", "key.keyboard.s"),
+    ("key.right", "key.keyboard.d"),
+    ("key.jump", "key.keyboard.space"),
+    ("key.sneak", "key.keyboard.left.shift"),
+    ("key.sprint", "key.keyboard.left.control"),
+    ("key.drop", "key.keyboard.q"),
+    ("key.inventory", "key.keyboard.e"),
+    ("key.swapOffhand", "key.keyboard.f"),
+    ("key.chat", "key.keyboard.t"),
+    ("key.command", "key.keyboard.slash"),
+    ("key.playerlist", "key.keyboard.tab"),
+    ("key.socialInteractions", "key.keyboard.p"),
+    ("key.advancements", "key.keyboard.l"),
+    ("key.screenshot", "key.keyboard.f2"),
+    ("key.togglePerspective", "key.keyboard.f5"),
+    ("key.fullscreen", "key.keyboard.f11"),
+    ("key.saveToolbarActivator", "key.keyboard.c"),
+    ("key.loadToolbarActivator", "key.keyboard.x"),
+    ("key.hotbar.1", "key.keyboard.1"),
+    ("key.hotbar.2", "key.keyboard.2"),
+    ("key.hotbar.3", "key.keyboard.3"),
+    ("key.hotbar.4", "key.keyboard.4"),
+    ("key.hotbar.5", "key.keyboard.5"),
+    ("key.hotbar.6", "key.keyboard.6"),
+    ("key.hotbar.7", "key.keyboard.7"),
+    ("key.hotbar.8", "key.keyboard.8"),
+    ("key.hotbar.9", "key.keyboard.9"),
+];
+
+/// Whether any token in `tokens`, including the contents of tooltips, is a [`Token::Content`].
+pub(crate) fn has_content(tokens: &[Token]) -> bool {
+    tokens.iter().any(|token| match token {
+        Token::Content(_) => true,
+        Token::Hover(HoverEvent::ShowText(text)) => has_content(text),
+        _ => false,
+    })
+}
+
+/// Returns `tokens` with every [`Token::Content`] filled in by `resolver`.
+pub(crate) fn resolve(tokens: &[Token], resolver: &(impl Resolver + ?Sized)) -> Vec<Token> {
+    let mut output = vec![];
+    resolve_into(&mut output, tokens, resolver, vec![]);
+
+    output
+}
+
+/// Push `tokens` into `output`, with every [`Token::Content`] filled in by `resolver`.
+///
+/// `active` holds the formatting and events that apply at the start of `tokens`, which are
+/// applied again after any piece of content that [resets][`Format::Reset`] them.
+fn resolve_into(
+    output: &mut Vec<Token>,
+    tokens: &[Token],
+    resolver: &(impl Resolver + ?Sized),
+    mut active: Vec<Token>,
+) {
+    for token in tokens {
+        match token {
+            Token::Content(content) => render(output, content, resolver, &active),
+            Token::Format(Format::Reset) | Token::ThematicBreak => {
+                active.clear();
+                output.push(token.clone());
+            }
+            Token::Hover(HoverEvent::ShowText(text)) => {
+                let token = Token::Hover(HoverEvent::ShowText(resolve(text, resolver).into()));
+                active.push(token.clone());
+                output.push(token);
+            }
+            Token::Format(_) | Token::Click(_) | Token::Insertion(_) => {
+                active.push(token.clone());
+                output.push(token.clone());
+            }
+            _ => output.push(token.clone()),
+        }
+    }
+}
+
+/// Push the tokens that `content` is filled in with into `output`.
+///
+/// `active` holds the formatting and events that apply to `content`.
+fn render(
+    output: &mut Vec<Token>,
+    content: &Content,
+    resolver: &(impl Resolver + ?Sized),
+    active: &[Token],
+) {
+    // Arguments and separators have formatting of their own, so anything they reset is applied
+    // again after them
+    let nested = |output: &mut Vec<Token>, tokens: &[Token]| {
+        let start = output.len();
+        resolve_into(output, tokens, resolver, active.to_vec());
+
+        if output[start..].contains(&Token::Format(Format::Reset)) {
+            output.extend_from_slice(active);
+        }
+    };
+
+    match content {
+        Content::Translate {
+            key,
+            fallback,
+            with,
+        } => {
+            let format = resolver
+                .translate(key)
+                .or(fallback.as_deref())
+                .unwrap_or(key);
+
+            match placeholders(format, with.len()) {
+                Some(pieces) => {
+                    for piece in pieces {
+                        match piece {
+                            Piece::Text(text) => push_text(output, &text),
+                            Piece::Argument(index) => nested(output, &with[index]),
+                        }
+                    }
+                }
+                // Like Minecraft, show a malformed translation as is
+                None => push_text(output, format),
+            }
+        }
+        Content::Keybind(keybind) => {
+            push_text(
+                output,
+                &resolver
+                    .keybind(keybind)
+                    .unwrap_or_else(|| keybind.to_string()),
+            );
+        }
+        Content::Score { name, objective } => {
+            if let Some(score) = resolver.score(name, objective) {
+                push_text(output, &score);
+            }
+        }
+        Content::Selector { pattern, separator } => {
+            let Some(names) = resolver.selector(pattern) else {
+                push_text(output, pattern);
+                return;
+            };
+
+            for (index, name) in names.iter().enumerate() {
+                if index > 0 {
+                    match separator {
+                        Some(separator) => nested(output, separator),
+                        None => output.extend([Token::Text(",".into()), Token::Space]),
+                    }
+                }
+
+                push_text(output, name);
+            }
+        }
+    }
+}
+
+/// A piece of a translation.
+#[derive(PartialEq, Eq, Debug)]
+enum Piece {
+    /// Literal text.
+    Text(String),
+    /// The argument with the given index, counting from zero.
+    Argument(usize),
+}
+
+/// Split a translation into literal text and the arguments that fill in its placeholders.
+///
+/// `%s` is the next argument, `%1$s` is the first argument, and `%%` is a literal `'%'`.
+///
+/// Returns [`None`] if the translation is malformed, or uses more than `arguments` arguments.
+fn placeholders(format: &str, arguments: usize) -> Option<Vec<Piece>> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut next = 0;
+    let mut chars = format.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        if char != '%' {
+            text.push(char);
+            continue;
+        }
+
+        let mut digits = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            digits.push(digit);
+        }
+
+        let index = if digits.is_empty() {
+            None
+        } else {
+            chars.next_if_eq(&'$')?;
+            Some(digits.parse::<usize>().ok()?.checked_sub(1)?)
+        };
+
+        match (chars.next()?, index) {
+            ('%', None) => text.push('%'),
+            ('s', index) => {
+                let index = index.unwrap_or_else(|| {
+                    next += 1;
+                    next - 1
+                });
+
+                if index >= arguments {
+                    return None;
+                }
+
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Argument(index));
+            }
+            _ => return None,
+        }
+    }
+
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+
+    Some(pieces)
+}
+
+/// Push `text` into `output` as [`Token::Text`], [`Token::Space`], and [`Token::LineBreak`].
+fn push_text(output: &mut Vec<Token>, text: &str) {
+    let mut word: Vec<char> = vec![];
+
+    for char in text.chars() {
+        let token = match char {
+            ' ' => Token::Space,
+            '\n' => Token::LineBreak,
+            _ => {
+                word.push(char);
+                continue;
+            }
+        };
+
+        if !word.is_empty() {
+            output.push((&mut word).into());
+        }
+        output.push(token);
+    }
+
+    if !word.is_empty() {
+        output.push((&mut word).into());
+    }
+}
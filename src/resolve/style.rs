@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Applying the rules Minecraft: Java Edition formats text by.
+//!
+//! See [`TokenList::resolve_styles`][`crate::syntax::TokenList::resolve_styles`].
+
+use crate::syntax::{
+    minecraft::{Format, HoverEvent, Style},
+    Token,
+};
+
+/// Returns `tokens` rewritten such that every hidden token only adds to the style before it.
+///
+/// Tokens that change nothing are left out, colors that would reset the formatting are preceded by
+/// a [`Format::Reset`] and the style that is left, and a [`Token::ThematicBreak`] is preceded by a
+/// reset if anything still applies.
+pub fn resolve_styles(tokens: &[Token]) -> Vec<Token> {
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut active = Style::new();
+
+    for token in tokens {
+        let token = match token {
+            Token::ThematicBreak => {
+                if !active.is_plain() {
+                    output.push(Token::Format(Format::Reset));
+                    active = Style::new();
+                }
+
+                output.push(Token::ThematicBreak);
+                continue;
+            }
+            Token::Hover(HoverEvent::ShowText(text)) => {
+                &Token::Hover(HoverEvent::ShowText(resolve_styles(text).into()))
+            }
+            token => token,
+        };
+
+        let mut style = active.clone();
+        if !style.apply(token) {
+            output.push(token.clone());
+            continue;
+        }
+
+        if style == active {
+            continue;
+        }
+
+        if resets_formatting(&active, token) {
+            output.push(Token::Format(Format::Reset));
+            output.extend(style.tokens());
+        } else {
+            output.push(token.clone());
+        }
+
+        active = style;
+    }
+
+    output
+}
+
+/// Whether `token` is a color that resets formatting that applies in `active`, which a reader
+/// that only stacks formats would not know to do.
+fn resets_formatting(active: &Style, token: &Token) -> bool {
+    matches!(token, Token::Format(Format::Color(_)))
+        && active.formats().any(|format| {
+            !matches!(
+                format,
+                Format::Color(_) | Format::Font(_) | Format::ShadowColor(_)
+            )
+        })
+}
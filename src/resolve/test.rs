@@ -0,0 +1,381 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for [filling in content][`super`].
+
+use super::{
+    placeholders, resolve, resolve_styles, Language, LanguageError, Piece, Resolver, Untranslated,
+};
+use crate::syntax::{
+    minecraft::{ClickEvent, Color, Content, Format, HoverEvent},
+    Token,
+};
+
+/// Insert a [`Token::Format`] with the given variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+/// Insert a [`Token::Content`] holding a translation of `$key` with the given arguments.
+macro_rules! translate {
+    ($key:expr $(, $with:expr )* $(,)?) => {
+        crate::syntax::Token::Content(crate::syntax::minecraft::Content::Translate {
+            key: $key.into(),
+            fallback: None,
+            with: Box::new([ $( Box::new($with) ),* ]),
+        })
+    };
+}
+
+/// A [`Resolver`] that knows of a few scores and players.
+struct Scoreboard;
+
+impl Resolver for Scoreboard {
+    fn score(&self, name: &str, objective: &str) -> Option<String> {
+        (name == "Steve" && objective == "deaths").then(|| "3".into())
+    }
+
+    fn selector(&self, pattern: &str) -> Option<Vec<String>> {
+        (pattern == "@a").then(|| vec!["Steve".into(), "Alex".into()])
+    }
+}
+
+#[test]
+fn test_placeholders() {
+    use Piece::{Argument, Text};
+
+    assert_eq!(
+        placeholders("%s and %s", 2),
+        Some(vec![Argument(0), Text(" and ".into()), Argument(1)])
+    );
+    assert_eq!(
+        placeholders("%2$s before %1$s, 100%%", 2),
+        Some(vec![
+            Argument(1),
+            Text(" before ".into()),
+            Argument(0),
+            Text(", 100%".into()),
+        ])
+    );
+    assert_eq!(placeholders("plain", 0), Some(vec![Text("plain".into())]));
+
+    // Too few arguments, or placeholders that Minecraft does not know of
+    for malformed in ["%s", "%d", "%0$s", "%1s", "50%"] {
+        assert_eq!(placeholders(malformed, 0), None, "{malformed}");
+    }
+}
+
+#[test]
+fn test_translate() -> Result<(), LanguageError> {
+    let language = Language::from_json(
+        r#"{"chat.type.text": "<%s> %s", "broken": "%d", "greeting": "Hello,\n%1$s!"}"#,
+    )?;
+    assert_eq!(language.len(), 3);
+
+    assert_eq!(
+        resolve(
+            &[translate!(
+                "chat.type.text",
+                [text!("Steve")],
+                [text!("hi")]
+            )],
+            &language
+        ),
+        [
+            text!("<"),
+            text!("Steve"),
+            text!(">"),
+            Token::Space,
+            text!("hi")
+        ]
+    );
+
+    // Formatting in an argument is reset at its end, so the outer formatting is applied again
+    let tokens = [
+        format!(Bold),
+        translate!("greeting", [format!(Italic), text!("Alex"), format!(Reset)]),
+        format!(Reset),
+    ];
+    assert_eq!(
+        resolve(&tokens, &language),
+        [
+            format!(Bold),
+            text!("Hello,"),
+            Token::LineBreak,
+            format!(Italic),
+            text!("Alex"),
+            format!(Reset),
+            format!(Bold),
+            text!("!"),
+            format!(Reset),
+        ]
+    );
+
+    // Without a translation, the fallback or the key is used, and malformed ones are shown as is
+    let fallback = Token::Content(Content::Translate {
+        key: "missing".into(),
+        fallback: Some("Fallback".into()),
+        with: Box::new([]),
+    });
+    assert_eq!(resolve(&[fallback], &language), [text!("Fallback")]);
+    assert_eq!(
+        resolve(&[translate!("missing")], &language),
+        [text!("missing")]
+    );
+    assert_eq!(resolve(&[translate!("broken")], &language), [text!("%d")]);
+
+    // Content inside of tooltips is filled in too
+    let hover = Token::Hover(HoverEvent::ShowText(Box::new([translate!(
+        "greeting",
+        [text!("Steve")]
+    )])));
+    assert_eq!(
+        resolve(&[hover], &Untranslated),
+        [Token::Hover(HoverEvent::ShowText(Box::new([text!(
+            "greeting"
+        )])))]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_other_content() -> Result<(), LanguageError> {
+    let keybind = |control: &str| Token::Content(Content::Keybind(control.into()));
+
+    let language = Language::from_json(r#"{"key.keyboard.space": "Space"}"#)?;
+    assert_eq!(resolve(&[keybind("key.jump")], &language), [text!("Space")]);
+    assert_eq!(resolve(&[keybind("key.forward")], &language), [text!("W")]);
+    assert_eq!(
+        resolve(&[keybind("key.jump")], &Untranslated),
+        [text!("key.keyboard.space")]
+    );
+    assert_eq!(
+        resolve(&[keybind("key.unknown")], &language),
+        [text!("key.unknown")]
+    );
+
+    let score = |name: &str| {
+        Token::Content(Content::Score {
+            name: name.into(),
+            objective: "deaths".into(),
+        })
+    };
+    assert_eq!(resolve(&[score("Steve")], &Scoreboard), [text!("3")]);
+    assert_eq!(resolve(&[score("Alex")], &Scoreboard), []);
+
+    let selector = |pattern: &str, separator: Option<Box<[Token]>>| {
+        Token::Content(Content::Selector {
+            pattern: pattern.into(),
+            separator,
+        })
+    };
+    assert_eq!(
+        resolve(&[selector("@a", None)], &Scoreboard),
+        [text!("Steve"), text!(","), Token::Space, text!("Alex")]
+    );
+    assert_eq!(resolve(&[selector("@p", None)], &Scoreboard), [text!("@p")]);
+
+    // A formatted separator has its formatting reset, and the outer formatting applied again
+    let separator = Box::new([
+        Token::Format(Format::Color(Color::Gray.into())),
+        text!("|"),
+        format!(Reset),
+    ]);
+    assert_eq!(
+        resolve(
+            &[format!(Bold), selector("@a", Some(separator))],
+            &Scoreboard
+        ),
+        [
+            format!(Bold),
+            text!("Steve"),
+            Token::Format(Format::Color(Color::Gray.into())),
+            text!("|"),
+            format!(Reset),
+            format!(Bold),
+            text!("Alex"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_language_errors() {
+    assert!(matches!(
+        Language::from_json(r#"{"key": "value""#),
+        Err(LanguageError::Syntax(_))
+    ));
+    assert!(matches!(
+        Language::from_json(r#"["key", "value"]"#),
+        Err(LanguageError::UnexpectedType(_))
+    ));
+    assert!(matches!(
+        Language::from_json(r#"{"key": 5}"#),
+        Err(LanguageError::UnexpectedType(_))
+    ));
+    assert!(matches!(
+        Language::from_file("/nonexistent/en_us.json"),
+        Err(LanguageError::Io(_))
+    ));
+}
+
+/// Returns each piece of text in `tokens` alongside the formats that apply to it, read like an
+/// exporter reads them: a color replaces the last, every other format adds to those before it,
+/// and a reset clears them all.
+fn stacked(tokens: &[Token]) -> Vec<(&str, Vec<Format>)> {
+    let mut formats = vec![];
+    let mut output = vec![];
+
+    for token in tokens {
+        match token {
+            Token::Format(Format::Reset) => formats.clear(),
+            Token::Format(format @ Format::Color(_)) => {
+                formats.retain(|format| !matches!(format, Format::Color(_)));
+                formats.push(*format);
+            }
+            Token::Format(format) => formats.push(*format),
+            Token::Text(text) => output.push((text.as_ref(), formats.clone())),
+            _ => (),
+        }
+    }
+
+    output
+}
+
+#[test]
+fn test_styles() {
+    use Token::ThematicBreak;
+
+    let red = Format::Color(Color::Red.into());
+    let gold = Format::Color(Color::Gold.into());
+
+    // Each input alongside how Minecraft shows its text
+    let inputs = [
+        // `"§l§cRed"`
+        (
+            vec![format!(Bold), Token::Format(red), text!("Red")],
+            vec![("Red", vec![red])],
+        ),
+        // `"§c§lBold red"`
+        (
+            vec![Token::Format(red), format!(Bold), text!("Bold red")],
+            vec![("Bold red", vec![red, Format::Bold])],
+        ),
+        // `"§c§l§oA§6B§nC"`
+        (
+            vec![
+                Token::Format(red),
+                format!(Bold),
+                format!(Italic),
+                text!("A"),
+                Token::Format(gold),
+                text!("B"),
+                format!(Underline),
+                text!("C"),
+            ],
+            vec![
+                ("A", vec![red, Format::Bold, Format::Italic]),
+                ("B", vec![gold]),
+                ("C", vec![gold, Format::Underline]),
+            ],
+        ),
+        // `"§l§lA§rB"`, and a bold page followed by one without formatting
+        (
+            vec![
+                format!(Bold),
+                format!(Bold),
+                text!("A"),
+                format!(Reset),
+                text!("B"),
+                format!(Bold),
+                text!("C"),
+                ThematicBreak,
+                text!("D"),
+            ],
+            vec![
+                ("A", vec![Format::Bold]),
+                ("B", vec![]),
+                ("C", vec![Format::Bold]),
+                ("D", vec![]),
+            ],
+        ),
+    ];
+
+    for (input, rendering) in inputs {
+        assert_eq!(stacked(&resolve_styles(&input)), rendering, "{input:?}");
+    }
+
+    // Tokens that change nothing are left out, and the last page is reset before a new one
+    assert_eq!(
+        resolve_styles(&[
+            format!(Reset),
+            format!(Bold),
+            format!(Bold),
+            text!("a"),
+            ThematicBreak,
+            format!(Reset),
+            text!("b"),
+        ]),
+        [
+            format!(Bold),
+            text!("a"),
+            format!(Reset),
+            ThematicBreak,
+            text!("b"),
+        ]
+    );
+
+    // Events outlast a change of color, so they are applied again after the reset
+    let click = Token::Click(ClickEvent::ChangePage(2));
+    assert_eq!(
+        resolve_styles(&[click.clone(), format!(Bold), Token::Format(red), text!("a")]),
+        [
+            click.clone(),
+            format!(Bold),
+            format!(Reset),
+            Token::Format(red),
+            click,
+            text!("a"),
+        ]
+    );
+
+    // Tooltips follow the same rules
+    assert_eq!(
+        resolve_styles(&[Token::Hover(HoverEvent::ShowText(Box::new([
+            format!(Italic),
+            Token::Format(gold),
+            text!("a"),
+        ])))]),
+        [Token::Hover(HoverEvent::ShowText(Box::new([
+            format!(Italic),
+            format!(Reset),
+            Token::Format(gold),
+            text!("a"),
+        ])))]
+    );
+}
@@ -30,6 +30,22 @@ pub enum ConversionError {
     /// Encountered when attempting to parse a format string with an invalid format code.
     #[error("no such format code '{0}'")]
     NoSuchFormatCode(char),
+    /// Encountered when attempting to parse an unknown color name, ex. `"orange"` instead of
+    /// `"gold"`.
+    #[error("no such color '{0}'")]
+    NoSuchColor(String),
+    /// Encountered when attempting to parse a malformed hex color, ex. `"#12AB3"` instead of
+    /// `"#12AB34"`.
+    #[error("expected a hex color of the form '#RRGGBB', received '{0}'")]
+    InvalidHexColor(String),
+    /// Encountered when attempting to get the format code of a
+    /// [`Format`][`super::minecraft::Format`] that has none, ex. a font.
+    #[error("no format code for {0:?}")]
+    NoFormatCode(super::minecraft::Format),
+    /// Encountered when attempting to parse an unknown font, ex. `"minecraft:fancy"` instead of
+    /// `"minecraft:alt"`.
+    #[error("no such font '{0}'")]
+    NoSuchFont(String),
     /// Encountered when `'§'` is encountered but not followed by a format code.
     #[error("expected a format code after '§'")]
     MissingFormatCode,
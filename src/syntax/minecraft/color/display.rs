@@ -18,7 +18,7 @@
 
 //! Display implementations for [`color`][`super`].
 
-use super::{Color, ColorValue, Rgb};
+use super::{Color, ColorValue, Rgb, ShadowColor, TextColor};
 use std::fmt::{Display, UpperHex};
 
 impl Display for Rgb {
@@ -33,9 +33,15 @@ impl Display for Rgb {
 impl UpperHex for Rgb {
     /// Displays the color in hexadecimal without a leading `#` (`"RRGGBB"`).
     ///
-    /// Ex. `(255, 255, 255)` -> `"FFFFFF"`.
+    /// Ex. `(255, 255, 255)` -> `"FFFFFF"`, and `(0, 0, 170)` -> `"0000AA"`.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:X}{:X}{:X}", self.red(), self.green(), self.blue())
+        write!(
+            f,
+            "{:02X}{:02X}{:02X}",
+            self.red(),
+            self.green(),
+            self.blue()
+        )
     }
 }
 
@@ -74,3 +80,39 @@ impl UpperHex for Color {
         write!(f, "{:X}", ColorValue::from(*self))
     }
 }
+
+impl Display for TextColor {
+    /// Displays the exact color in hexadecimal with a leading `'#'` (`"#RRGGBB"`).
+    ///
+    /// Ex. `(255, 255, 255)` -> `"#FFFFFF"`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.rgb())
+    }
+}
+
+impl UpperHex for TextColor {
+    /// Displays the exact color in hexadecimal without a leading `'#'` (`"RRGGBB"`).
+    ///
+    /// Ex. `(255, 255, 255)` -> `"FFFFFF"`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:X}", self.rgb())
+    }
+}
+
+impl Display for ShadowColor {
+    /// Displays the color in hexadecimal with a leading `'#'` (`"#RRGGBBAA"`), as used by CSS.
+    ///
+    /// Ex. `(255, 255, 255)` at half opacity -> `"#FFFFFF80"`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{self:X}")
+    }
+}
+
+impl UpperHex for ShadowColor {
+    /// Displays the color in hexadecimal without a leading `'#'` (`"RRGGBBAA"`).
+    ///
+    /// Ex. `(255, 255, 255)` at half opacity -> `"FFFFFF80"`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:X}{:02X}", self.rgb(), self.alpha())
+    }
+}
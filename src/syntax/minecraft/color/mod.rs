@@ -18,11 +18,176 @@
 
 //! Syntax definitions for Minecraft text coloring.
 //!
-//! See [`Color`] and [`ColorValue`].
+//! See [`TextColor`], [`Color`], [`ColorValue`], and [`ShadowColor`].
 
 #![allow(clippy::module_name_repetitions)]
 
+use super::ConversionError;
+use std::str::FromStr;
+
 mod display;
+#[cfg(test)]
+mod test;
+
+/// Represents the color of text, which is either one of the sixteen legacy [`Color`]s, or (since
+/// Minecraft: Java Edition 1.16) an arbitrary [`Rgb`] value.
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::syntax::minecraft::{Color, Rgb, TextColor};
+///
+/// // Text components name colors either by name or by hex value
+/// assert_eq!("gold".parse::<TextColor>().unwrap(), TextColor::Legacy(Color::Gold));
+/// assert_eq!(
+///     "#12AB34".parse::<TextColor>().unwrap(),
+///     TextColor::Rgb(Rgb::new(0x12, 0xAB, 0x34))
+/// );
+///
+/// // Either way, it has an exact value
+/// assert_eq!(TextColor::from(Color::Gold).rgb(), Rgb::new(255, 170, 0));
+/// assert_eq!(format!("{}", TextColor::from(Color::DarkBlue)), "#0000AA");
+///
+/// // And a closest legacy color, for formats that only know of those (ex. `'§'` format codes)
+/// assert_eq!(TextColor::from(Rgb::new(250, 160, 10)).nearest_legacy(), Color::Gold);
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub enum TextColor {
+    /// One of the sixteen colors with a `'§'` format code.
+    Legacy(Color),
+    /// An arbitrary 24-bit color.
+    Rgb(Rgb),
+}
+
+impl TextColor {
+    /// Returns the exact [`Rgb`] value of the color.
+    ///
+    /// For a legacy [`Color`], this is its [foreground][`ColorValue::fg`] value.
+    #[must_use]
+    pub fn rgb(self) -> Rgb {
+        match self {
+            Self::Legacy(color) => ColorValue::from(color).fg(),
+            Self::Rgb(rgb) => rgb,
+        }
+    }
+
+    /// Returns the legacy [`Color`] closest to this color.
+    ///
+    /// See [`Rgb::nearest_legacy`].
+    #[must_use]
+    pub fn nearest_legacy(self) -> Color {
+        match self {
+            Self::Legacy(color) => color,
+            Self::Rgb(rgb) => rgb.nearest_legacy(),
+        }
+    }
+
+    /// Returns the color of the shadow drawn behind text of this color, when no [`ShadowColor`]
+    /// is given.
+    ///
+    /// For a legacy [`Color`], this is its [background][`ColorValue::bg`] value. Other than for
+    /// [`Color::Gold`], those are each channel of the foreground divided by four, which is what
+    /// Minecraft does for an arbitrary [`Rgb`] value.
+    #[must_use]
+    pub fn shadow(self) -> Rgb {
+        match self {
+            Self::Legacy(color) => ColorValue::from(color).bg(),
+            Self::Rgb(rgb) => Rgb::new(rgb.red() / 4, rgb.green() / 4, rgb.blue() / 4),
+        }
+    }
+}
+
+impl From<Color> for TextColor {
+    fn from(color: Color) -> Self {
+        Self::Legacy(color)
+    }
+}
+
+impl From<Rgb> for TextColor {
+    fn from(rgb: Rgb) -> Self {
+        Self::Rgb(rgb)
+    }
+}
+
+impl FromStr for TextColor {
+    type Err = ConversionError;
+
+    /// Parse a color as written in Minecraft: Java Edition's text components, either a hex value
+    /// (ex. `"#12AB34"`) or the name of a legacy [`Color`] (ex. `"dark_aqua"`).
+    ///
+    /// # Errors
+    ///
+    /// - [`ConversionError::InvalidHexColor`] if a hex value is malformed
+    /// - [`ConversionError::NoSuchColor`] if a name does not correspond to a variant of [`Color`]
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.starts_with('#') {
+            string.parse().map(Self::Rgb)
+        } else {
+            string.parse().map(Self::Legacy)
+        }
+    }
+}
+
+/// Represents the color of the shadow drawn behind text (since Minecraft: Java Edition 1.21.4), an
+/// [`Rgb`] value with an alpha channel.
+///
+/// Without one, the shadow is a darker version of the color of the text, see
+/// [`TextColor::shadow`].
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::syntax::minecraft::{Rgb, ShadowColor};
+///
+/// // Text components store it as a single ARGB integer
+/// let shadow = ShadowColor::from_argb(0x8012_AB34);
+/// assert_eq!(shadow, ShadowColor::new(Rgb::new(0x12, 0xAB, 0x34), 0x80));
+/// assert_eq!(shadow.argb(), 0x8012_AB34);
+///
+/// // Displays as a CSS-style hex color (`"#RRGGBBAA"`)
+/// assert_eq!(format!("{shadow}"), "#12AB3480");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub struct ShadowColor {
+    rgb: Rgb,
+    alpha: u8,
+}
+
+impl ShadowColor {
+    /// Create a new [`ShadowColor`], where an `alpha` of zero is fully transparent.
+    #[must_use]
+    pub const fn new(rgb: Rgb, alpha: u8) -> Self {
+        Self { rgb, alpha }
+    }
+
+    /// Create a new [`ShadowColor`] out of an integer of the form `0xAARRGGBB`.
+    #[must_use]
+    pub const fn from_argb(argb: u32) -> Self {
+        let [alpha, red, green, blue] = argb.to_be_bytes();
+
+        Self::new(Rgb::new(red, green, blue), alpha)
+    }
+
+    /// Returns the color as an integer of the form `0xAARRGGBB`.
+    #[must_use]
+    pub const fn argb(self) -> u32 {
+        let (red, green, blue) = self.rgb.as_tuple();
+
+        u32::from_be_bytes([self.alpha, red, green, blue])
+    }
+
+    /// Returns the [`Rgb`] value of the color.
+    #[must_use]
+    pub const fn rgb(self) -> Rgb {
+        self.rgb
+    }
+
+    /// Returns the opacity of the color, where zero is fully transparent.
+    #[must_use]
+    pub const fn alpha(self) -> u8 {
+        self.alpha
+    }
+}
 
 /// Represents the possible text colors (foreground and background) in Minecraft: Java Edition.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
@@ -45,6 +210,47 @@ pub enum Color {
     White,
 }
 
+impl Color {
+    /// Every [`Color`], in the order of their format codes (`'0'` through `'f'`).
+    pub const ALL: [Self; 16] = [
+        Self::Black,
+        Self::DarkBlue,
+        Self::DarkGreen,
+        Self::DarkAqua,
+        Self::DarkRed,
+        Self::DarkPurple,
+        Self::Gold,
+        Self::Gray,
+        Self::DarkGray,
+        Self::Blue,
+        Self::Green,
+        Self::Aqua,
+        Self::Red,
+        Self::LightPurple,
+        Self::Yellow,
+        Self::White,
+    ];
+}
+
+impl FromStr for Color {
+    type Err = ConversionError;
+
+    /// Look up a color by its proper name, as used by Minecraft: Java Edition's text components.
+    ///
+    /// Ex. `"dark_aqua"` -> [`Color::DarkAqua`].
+    ///
+    /// # Errors
+    ///
+    /// - [`ConversionError::NoSuchColor`] if the name does not correspond to a variant of
+    ///   [`Color`]
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|&color| ColorValue::from(color).name() == name)
+            .ok_or_else(|| ConversionError::NoSuchColor(name.to_string()))
+    }
+}
+
 impl From<Color> for ColorValue {
     /// Get the values associated with a given [`Color`] in Minecraft: Java Edition.
     fn from(color: Color) -> Self {
@@ -169,7 +375,7 @@ impl ColorValue {
 }
 
 /// Represents a 24-bit RGB color value.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Rgb {
     red: u8,
     green: u8,
@@ -206,6 +412,55 @@ impl Rgb {
     pub const fn blue(&self) -> u8 {
         self.blue
     }
+
+    /// Returns the legacy [`Color`] whose [foreground][`ColorValue::fg`] value is closest to this
+    /// color, by Euclidean distance.
+    #[must_use]
+    pub fn nearest_legacy(self) -> Color {
+        let distance = |color: Color| {
+            let (red, green, blue) = ColorValue::from(color).fg().as_tuple();
+
+            [(self.red, red), (self.green, green), (self.blue, blue)]
+                .into_iter()
+                .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+                .sum::<u32>()
+        };
+
+        Color::ALL
+            .into_iter()
+            .min_by_key(|&color| distance(color))
+            .unwrap_or(Color::White)
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = ConversionError;
+
+    /// Parse an HTML-style hex color (`"#RRGGBB"`), as used by Minecraft: Java Edition's text
+    /// components.
+    ///
+    /// Ex. `"#12AB34"` -> `(18, 171, 52)`.
+    ///
+    /// # Errors
+    ///
+    /// - [`ConversionError::InvalidHexColor`] if the string is not a `'#'` followed by six
+    ///   hexadecimal digits
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let invalid = || ConversionError::InvalidHexColor(string.to_string());
+
+        let hex = string
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .ok_or_else(invalid)?;
+
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16);
+
+        Ok(Self::new(
+            channel(0).map_err(|_| invalid())?,
+            channel(2).map_err(|_| invalid())?,
+            channel(4).map_err(|_| invalid())?,
+        ))
+    }
 }
 
 impl From<(u8, u8, u8)> for Rgb {
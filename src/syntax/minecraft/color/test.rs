@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for [`super::TextColor`] and [`super::Rgb`].
+
+use super::{Color, ColorValue, Rgb, ShadowColor, TextColor};
+use crate::syntax::ConversionError;
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        "#12ab34".parse::<Rgb>().ok(),
+        Some(Rgb::new(0x12, 0xAB, 0x34))
+    );
+    assert_eq!(
+        "#FFFFFF".parse::<TextColor>().ok(),
+        Some(TextColor::Rgb(Rgb::new(255, 255, 255)))
+    );
+    assert_eq!(
+        "dark_aqua".parse::<TextColor>().ok(),
+        Some(TextColor::Legacy(Color::DarkAqua))
+    );
+
+    for invalid in ["12AB34", "#12AB3", "#12AB345", "#12AB3G", "#+2AB34", "#ÿÿÿ"] {
+        assert!(
+            matches!(
+                invalid.parse::<TextColor>(),
+                Err(ConversionError::InvalidHexColor(_) | ConversionError::NoSuchColor(_))
+            ),
+            "{invalid}"
+        );
+        assert!(
+            matches!(
+                invalid.parse::<Rgb>(),
+                Err(ConversionError::InvalidHexColor(_))
+            ),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(format!("{}", Rgb::new(0, 0, 170)), "#0000AA");
+    assert_eq!(format!("{:X}", Rgb::new(1, 2, 3)), "010203");
+    assert_eq!(format!("{}", Color::DarkBlue), "#0000AA");
+    assert_eq!(
+        format!("{}", TextColor::Rgb(Rgb::new(0x12, 0x0A, 0x34))),
+        "#120A34"
+    );
+
+    // Every color round trips through its hex value
+    for color in Color::ALL {
+        let rgb = TextColor::from(color).rgb();
+        assert_eq!(format!("{rgb}").parse::<Rgb>().ok(), Some(rgb));
+    }
+}
+
+#[test]
+fn test_nearest_legacy() {
+    for color in Color::ALL {
+        assert_eq!(TextColor::from(color).nearest_legacy(), color);
+        assert_eq!(TextColor::from(color).rgb().nearest_legacy(), color);
+    }
+
+    assert_eq!(Rgb::new(250, 160, 10).nearest_legacy(), Color::Gold);
+    assert_eq!(Rgb::new(10, 10, 10).nearest_legacy(), Color::Black);
+    assert_eq!(Rgb::new(100, 90, 240).nearest_legacy(), Color::Blue);
+}
+
+#[test]
+fn test_shadow() {
+    for color in Color::ALL {
+        assert_eq!(
+            TextColor::from(color).shadow(),
+            ColorValue::from(color).bg()
+        );
+        // The background values are the foreground values divided by four, except for gold
+        if color == Color::Gold {
+            continue;
+        }
+        assert_eq!(
+            TextColor::from(TextColor::from(color).rgb()).shadow(),
+            ColorValue::from(color).bg()
+        );
+    }
+
+    let shadow = ShadowColor::from_argb(0xFF00_00AA);
+    assert_eq!(shadow.rgb(), Rgb::new(0, 0, 170));
+    assert_eq!(shadow.alpha(), 255);
+    assert_eq!(format!("{shadow}"), "#0000AAFF");
+    assert_eq!(
+        format!("{:X}", ShadowColor::from_argb(0x0102_0304)),
+        "02030401"
+    );
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Syntax definitions for the parts of Minecraft: Java Edition text that are filled in as they
+//! are shown.
+//!
+//! See [`Content`].
+
+use crate::syntax::Token;
+
+/// Text that Minecraft fills in as it is shown, rather than storing it literally.
+///
+/// Until it is [resolved][`crate::syntax::TokenList::resolve`], it has no text of its own.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Content {
+    /// Text translated into the reader's language, ex. `"%s joined the game"`.
+    Translate {
+        /// The key of the translation, ex. `"multiplayer.player.joined"`.
+        key: Box<str>,
+        /// The text to use if the key has no translation, instead of the key itself.
+        fallback: Option<Box<str>>,
+        /// The arguments that fill in the translation's `%s` and `%1$s` placeholders.
+        ///
+        /// Any formatting in an argument is [reset][`super::Format::Reset`] at the end of it.
+        with: Box<[Box<[Token]>]>,
+    },
+    /// The name of the key bound to a control, ex. `"key.jump"`.
+    Keybind(Box<str>),
+    /// The score that a player or entity has in a scoreboard objective.
+    Score {
+        /// The name of the player or a selector for the entity, ex. `"@s"`.
+        name: Box<str>,
+        /// The name of the scoreboard objective.
+        objective: Box<str>,
+    },
+    /// The names of the players or entities that a selector matches, ex. `"@a"`.
+    Selector {
+        /// The selector, ex. `"@e[type=cow]"`.
+        pattern: Box<str>,
+        /// The text to put between names, instead of `", "`.
+        separator: Option<Box<[Token]>>,
+    },
+}
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Syntax definitions for the interactive parts of Minecraft: Java Edition text.
+//!
+//! See [`ClickEvent`] and [`HoverEvent`].
+
+use crate::syntax::Token;
+
+/// What happens when a player clicks on a span of text.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ClickEvent {
+    /// Turn to a page of the book, counting from one.
+    ChangePage(usize),
+    /// Open a URL in the player's web browser, after asking for confirmation.
+    OpenUrl(Box<str>),
+    /// Copy text to the player's clipboard.
+    CopyToClipboard(Box<str>),
+    /// Run a command as the player, ex. `"/say hi"`.
+    RunCommand(Box<str>),
+    /// Insert a command into the player's chat box, without running it.
+    SuggestCommand(Box<str>),
+}
+
+impl ClickEvent {
+    /// Returns the name of the action, as used by text components, ex. `"change_page"`.
+    #[must_use]
+    pub const fn action(&self) -> &'static str {
+        match self {
+            Self::ChangePage(_) => "change_page",
+            Self::OpenUrl(_) => "open_url",
+            Self::CopyToClipboard(_) => "copy_to_clipboard",
+            Self::RunCommand(_) => "run_command",
+            Self::SuggestCommand(_) => "suggest_command",
+        }
+    }
+}
+
+/// What is shown when a player hovers over a span of text.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum HoverEvent {
+    /// Show a tooltip holding formatted text.
+    ///
+    /// The text is made of [`Token::Text`], [`Token::Space`], [`Token::Format`], and line breaks.
+    ShowText(Box<[Token]>),
+}
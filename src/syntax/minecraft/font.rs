@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Syntax definitions for the fonts of Minecraft: Java Edition text.
+//!
+//! See [`Font`].
+
+use super::ConversionError;
+use std::str::FromStr;
+
+/// One of the fonts built into Minecraft: Java Edition.
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::syntax::minecraft::Font;
+///
+/// // Text components name fonts by their resource location, where the namespace is optional
+/// assert_eq!("minecraft:alt".parse::<Font>().unwrap(), Font::Alt);
+/// assert_eq!("illageralt".parse::<Font>().unwrap(), Font::IllagerAlt);
+///
+/// assert_eq!(Font::Uniform.id(), "minecraft:uniform");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug, Default)]
+pub enum Font {
+    /// The usual font.
+    #[default]
+    Default,
+    /// The Standard Galactic Alphabet, as used by enchanting tables.
+    Alt,
+    /// The runes used by illagers.
+    IllagerAlt,
+    /// The Unifont-based font used by the "Force Unicode Font" option.
+    Uniform,
+}
+
+impl Font {
+    /// Every [`Font`].
+    pub const ALL: [Self; 4] = [Self::Default, Self::Alt, Self::IllagerAlt, Self::Uniform];
+
+    /// Returns the name of the font without its namespace, ex. `"illageralt"`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Alt => "alt",
+            Self::IllagerAlt => "illageralt",
+            Self::Uniform => "uniform",
+        }
+    }
+
+    /// Returns the resource location of the font, as used by text components, ex.
+    /// `"minecraft:illageralt"`.
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Default => "minecraft:default",
+            Self::Alt => "minecraft:alt",
+            Self::IllagerAlt => "minecraft:illageralt",
+            Self::Uniform => "minecraft:uniform",
+        }
+    }
+}
+
+impl FromStr for Font {
+    type Err = ConversionError;
+
+    /// Look up a font by its resource location, ex. `"minecraft:alt"` or `"alt"` -> [`Font::Alt`].
+    ///
+    /// # Errors
+    ///
+    /// - [`ConversionError::NoSuchFont`] if the name is not one of the fonts built into
+    ///   Minecraft: Java Edition, ex. a font from a resource pack
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let name = id.strip_prefix("minecraft:").unwrap_or(id);
+
+        Self::ALL
+            .into_iter()
+            .find(|font| font.name() == name)
+            .ok_or_else(|| ConversionError::NoSuchFont(id.to_string()))
+    }
+}
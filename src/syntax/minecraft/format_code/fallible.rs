@@ -19,7 +19,7 @@
 //! Fallible conversions for [`FormatCode`].
 
 use super::{
-    super::{Color, ConversionError, Format},
+    super::{Color, ConversionError, Format, TextColor},
     FormatCode,
 };
 use std::str::FromStr;
@@ -74,7 +74,7 @@ impl TryFrom<char> for FormatCode {
                     $(
                         $color_code => Ok(Self {
                             code: $color_code,
-                            format: Format::Color(Color::$color)
+                            format: Format::Color(TextColor::Legacy(Color::$color))
                         })
                     ),+ ,
 
@@ -116,3 +116,47 @@ impl TryFrom<char> for FormatCode {
         })
     }
 }
+
+impl TryFrom<Format> for FormatCode {
+    type Error = ConversionError;
+
+    /// Returns a [`Format`]'s associated [`FormatCode`].
+    ///
+    /// Looks up the code against Minecraft: Java Edition's list of formatting codes.
+    ///
+    /// Format codes only exist for the legacy [`Color`]s, so an [`Rgb`][`super::super::Rgb`] color
+    /// is replaced by the [nearest legacy color][`TextColor::nearest_legacy`].
+    ///
+    /// # Errors
+    ///
+    /// - [`ConversionError::NoFormatCode`] if the [`Format`] has no format code, ex.
+    ///   [`Format::Font`]
+    fn try_from(format: Format) -> Result<Self, Self::Error> {
+        /// Match the input [`Format`] to a [`FormatCode`] value.
+        macro_rules! match_format {
+            (
+                $value:expr => { $( $variant:ident => $format_code:literal ),+ , }
+            ) => {
+                match $value {
+                    Format::Color(color) => Ok(color.nearest_legacy().into()),
+                    $( Format::$variant => Ok(Self {
+                            code: $format_code,
+                            format: $value,
+                    }) ),+ ,
+                    Format::Font(_) | Format::ShadowColor(_) => {
+                        Err(Self::Error::NoFormatCode($value))
+                    }
+                }
+            };
+        }
+
+        match_format!(format => {
+            Obfuscated => 'k',
+            Bold => 'l',
+            Strikethrough => 'm',
+            Underline => 'n',
+            Italic => 'o',
+            Reset => 'r',
+        })
+    }
+}
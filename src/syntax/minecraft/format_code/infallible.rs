@@ -19,7 +19,7 @@
 //! Infallible conversions for [`FormatCode`].
 
 use super::{
-    super::{Color, Format},
+    super::{Color, Format, TextColor},
     FormatCode,
 };
 
@@ -30,37 +30,6 @@ impl From<FormatCode> for char {
     }
 }
 
-impl From<Format> for FormatCode {
-    /// Returns a [`Format`]'s associated [`FormatCode`].
-    ///
-    /// Looks up the code against Minecraft: Java Edition's list of formatting codes.
-    fn from(format: Format) -> Self {
-        /// Match the input [`Format`] to a [`FormatCode`] value.
-        macro_rules! match_format {
-            (
-                $value:expr => { $( $variant:ident => $format_code:literal ),+ , }
-            ) => {
-                match $value {
-                    Format::Color(color) => color.into(),
-                    $( Format::$variant => Self {
-                            code: $format_code,
-                            format: $value,
-                    } ),+ ,
-                }
-            };
-        }
-
-        match_format!(format => {
-            Obfuscated => 'k',
-            Bold => 'l',
-            Strikethrough => 'm',
-            Underline => 'n',
-            Italic => 'o',
-            Reset => 'r',
-        })
-    }
-}
-
 impl From<Color> for FormatCode {
     /// Returns a [`Color`]'s associated [`FormatCode`].
     ///
@@ -72,7 +41,7 @@ impl From<Color> for FormatCode {
                 match $value {
                     $( Color::$color => Self {
                             code: $color_code,
-                            format: Format::Color($value),
+                            format: Format::Color(TextColor::Legacy($value)),
                     } ),+ ,
                 }
             };
@@ -32,7 +32,7 @@ fn format_code_from_char() -> Result {
                     FormatCode::try_from($code)?,
                     FormatCode {
                         code: $code,
-                        format: Format::Color(Color::$expected_color),
+                        format: Format::Color(Color::$expected_color.into()),
                     }
                 ) );+
             };
@@ -93,7 +93,7 @@ fn format_code_from_str() -> Result {
                     FormatCode::from_str(concat!('§', $code))?,
                     FormatCode {
                         code: $code,
-                        format: Format::Color(Color::$expected_color),
+                        format: Format::Color(Color::$expected_color.into()),
                     }
                 ) );+
             };
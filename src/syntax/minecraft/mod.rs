@@ -18,26 +18,43 @@
 
 //! Syntax definitions for Minecraft: Java Edition text.
 //!
-//! See [`Format`].
+//! See [`Format`], [`ClickEvent`], [`HoverEvent`], and [`Content`].
 
 use super::ConversionError;
-pub use color::{Color, ColorValue, Rgb};
+pub use color::{Color, ColorValue, Rgb, ShadowColor, TextColor};
+pub use content::Content;
+pub use event::{ClickEvent, HoverEvent};
+pub use font::Font;
 pub use format_code::FormatCode;
 use std::str::FromStr;
+pub use style::Style;
 
 mod color;
+mod content;
+mod event;
+mod font;
 mod format_code;
+mod style;
 
 /// Represents the ways that Minecraft: Java Edition will format text.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
 pub enum Format {
-    Color(Color),
+    /// The color of the text, either a legacy [`Color`] or an arbitrary [`Rgb`] value.
+    Color(TextColor),
     /// AKA "Magical Text Source", characters should rapidly swap between a set of characters.
     Obfuscated,
     Bold,
     Strikethrough,
     Underline,
     Italic,
+    /// The font of the text (since Minecraft: Java Edition 1.16).
+    ///
+    /// A new font replaces the last.
+    Font(Font),
+    /// The color of the shadow drawn behind the text (since Minecraft: Java Edition 1.21.4).
+    ///
+    /// A new shadow color replaces the last.
+    ShadowColor(ShadowColor),
     Reset,
 }
 
@@ -89,8 +106,15 @@ impl FromStr for Format {
     }
 }
 
-impl From<Format> for char {
-    fn from(value: Format) -> Self {
-        Self::from(FormatCode::from(value))
+impl TryFrom<Format> for char {
+    type Error = ConversionError;
+
+    /// Returns the character following the `'§'` in a [`Format`]'s format code.
+    ///
+    /// # Errors
+    ///
+    /// - [`ConversionError::NoFormatCode`] if the [`Format`] has no format code
+    fn try_from(value: Format) -> Result<Self, Self::Error> {
+        FormatCode::try_from(value).map(Self::from)
     }
 }
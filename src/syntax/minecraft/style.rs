@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The computed style of Minecraft: Java Edition text.
+//!
+//! See [`Style`].
+
+use super::{ClickEvent, Font, Format, HoverEvent, ShadowColor, TextColor};
+use crate::syntax::Token;
+
+/// Everything that applies to a piece of text, as computed from the hidden tokens before it.
+///
+/// Where a [`TokenList`][`crate::syntax::TokenList`] only records changes to the style (each
+/// applying until the next [`Format::Reset`]), a [`Style`] is the sum of those changes.
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::syntax::{
+///     minecraft::{Color, Format, Style, TextColor},
+///     Token,
+/// };
+///
+/// let mut style = Style::default();
+/// style.apply(&Token::Format(Format::Color(TextColor::Legacy(Color::Red))));
+/// style.apply(&Token::Format(Format::Bold));
+/// assert!(style.is_bold());
+///
+/// // Like in Minecraft, a new color clears bold, italic, etc.
+/// style.apply(&Token::Format(Format::Color(TextColor::Legacy(Color::Gold))));
+/// assert!(!style.is_bold());
+/// assert_eq!(style.color(), Some(TextColor::Legacy(Color::Gold)));
+///
+/// style.apply(&Token::Format(Format::Reset));
+/// assert!(style.is_plain());
+/// ```
+#[allow(clippy::struct_excessive_bools)] // Mirrors the formats of Minecraft: Java Edition
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Style {
+    color: Option<TextColor>,
+    obfuscated: bool,
+    bold: bool,
+    strikethrough: bool,
+    underline: bool,
+    italic: bool,
+    font: Option<Font>,
+    shadow_color: Option<ShadowColor>,
+    click: Option<ClickEvent>,
+    hover: Option<HoverEvent>,
+    insertion: Option<Box<str>>,
+}
+
+impl Style {
+    /// Creates a [`Style`] where nothing applies.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            color: None,
+            obfuscated: false,
+            bold: false,
+            strikethrough: false,
+            underline: false,
+            italic: false,
+            font: None,
+            shadow_color: None,
+            click: None,
+            hover: None,
+            insertion: None,
+        }
+    }
+
+    /// Applies a hidden token on top of the style.
+    ///
+    /// Like in Minecraft, a color also clears the formatting (bold, italic, etc.) before it, but
+    /// not the font, shadow color, or events. A [`Format::Reset`] clears the whole style,
+    /// including events, and a color, font, shadow color, or event replaces the last.
+    ///
+    /// Returns whether `token` was a hidden token, leaving the style unchanged if it was not.
+    pub fn apply(&mut self, token: &Token) -> bool {
+        match token {
+            Token::Format(format) => self.apply_format(*format),
+            Token::Click(click) => self.click = Some(click.clone()),
+            Token::Hover(hover) => self.hover = Some(hover.clone()),
+            Token::Insertion(insertion) => self.insertion = Some(insertion.clone()),
+            Token::Text(_)
+            | Token::Content(_)
+            | Token::Space
+            | Token::LineBreak
+            | Token::ParagraphBreak
+            | Token::ThematicBreak => return false,
+        }
+
+        true
+    }
+
+    /// Applies a [`Format`] on top of the style.
+    fn apply_format(&mut self, format: Format) {
+        match format {
+            Format::Color(color) => {
+                *self = Self {
+                    color: Some(color),
+                    font: self.font,
+                    shadow_color: self.shadow_color,
+                    click: self.click.take(),
+                    hover: self.hover.take(),
+                    insertion: self.insertion.take(),
+                    ..Self::new()
+                };
+            }
+            Format::Obfuscated => self.obfuscated = true,
+            Format::Bold => self.bold = true,
+            Format::Strikethrough => self.strikethrough = true,
+            Format::Underline => self.underline = true,
+            Format::Italic => self.italic = true,
+            Format::Font(font) => self.font = Some(font),
+            Format::ShadowColor(shadow_color) => self.shadow_color = Some(shadow_color),
+            Format::Reset => *self = Self::default(),
+        }
+    }
+
+    /// Returns the [`Format`]s that make up the style, colors first.
+    pub fn formats(&self) -> impl Iterator<Item = Format> {
+        [
+            self.color.map(Format::Color),
+            self.obfuscated.then_some(Format::Obfuscated),
+            self.bold.then_some(Format::Bold),
+            self.strikethrough.then_some(Format::Strikethrough),
+            self.underline.then_some(Format::Underline),
+            self.italic.then_some(Format::Italic),
+            self.font.map(Format::Font),
+            self.shadow_color.map(Format::ShadowColor),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Returns the hidden tokens that make up the style, formats first (see [`Self::formats`]).
+    ///
+    /// Applied in order on top of a plain style, they build the style again.
+    pub fn tokens(&self) -> impl Iterator<Item = Token> + '_ {
+        self.formats()
+            .map(Token::Format)
+            .chain(self.click.clone().map(Token::Click))
+            .chain(self.hover.clone().map(Token::Hover))
+            .chain(self.insertion.clone().map(Token::Insertion))
+    }
+
+    /// Whether nothing applies, as at the start of a work or after a [`Format::Reset`].
+    #[must_use]
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The color of the text, if it is not the default.
+    #[must_use]
+    pub const fn color(&self) -> Option<TextColor> {
+        self.color
+    }
+
+    /// Whether the text is [`Format::Obfuscated`].
+    #[must_use]
+    pub const fn is_obfuscated(&self) -> bool {
+        self.obfuscated
+    }
+
+    /// Whether the text is [`Format::Bold`].
+    #[must_use]
+    pub const fn is_bold(&self) -> bool {
+        self.bold
+    }
+
+    /// Whether the text is [`Format::Strikethrough`].
+    #[must_use]
+    pub const fn is_strikethrough(&self) -> bool {
+        self.strikethrough
+    }
+
+    /// Whether the text is [`Format::Underline`].
+    #[must_use]
+    pub const fn is_underline(&self) -> bool {
+        self.underline
+    }
+
+    /// Whether the text is [`Format::Italic`].
+    #[must_use]
+    pub const fn is_italic(&self) -> bool {
+        self.italic
+    }
+
+    /// The font of the text, if one was set.
+    #[must_use]
+    pub const fn font(&self) -> Option<Font> {
+        self.font
+    }
+
+    /// The color of the shadow behind the text, if one was set.
+    #[must_use]
+    pub const fn shadow_color(&self) -> Option<ShadowColor> {
+        self.shadow_color
+    }
+
+    /// What happens when the text is clicked.
+    #[must_use]
+    pub const fn click(&self) -> Option<&ClickEvent> {
+        self.click.as_ref()
+    }
+
+    /// What is shown when hovering over the text.
+    #[must_use]
+    pub const fn hover(&self) -> Option<&HoverEvent> {
+        self.hover.as_ref()
+    }
+
+    /// The text inserted into the chat box when the text is shift-clicked.
+    #[must_use]
+    pub fn insertion(&self) -> Option<&str> {
+        self.insertion.as_deref()
+    }
+}
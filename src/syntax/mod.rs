@@ -20,13 +20,19 @@
 //! Defines the intermediary representations of documents, and some of the parsing necessary to
 //! create it.
 //!
-//! See [`TokenList`].
+//! See [`TokenList`], and [`tree::Tree`] for a tree-shaped representation.
 
+use crate::resolve::{self, Resolver};
 pub use error::ConversionError;
+pub use page::{Page, Pages};
+pub use source::SourceSpan;
 use std::sync::Arc;
 
 mod error;
 pub mod minecraft;
+mod page;
+mod source;
+pub mod tree;
 
 /// Represents and entire work in abstract syntax.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -53,6 +59,101 @@ impl TokenList {
         }
     }
 
+    /// Creates a new [`TokenList`] from the contents of each page, in order.
+    ///
+    /// Each page is started with a [`Token::ThematicBreak`], so a page should not contain any
+    /// thematic breaks of its own, or it will be split into several.
+    #[must_use]
+    pub fn new_from_pages<P>(metadata: Box<[Metadata]>, pages: impl IntoIterator<Item = P>) -> Self
+    where
+        P: IntoIterator<Item = Token>,
+    {
+        let tokens: Vec<Token> = pages
+            .into_iter()
+            .flat_map(|page| std::iter::once(Token::ThematicBreak).chain(page))
+            .collect();
+
+        Self::new_from_boxed(metadata, tokens.into())
+    }
+
+    /// Returns an iterator over the [`Page`]s of the work.
+    ///
+    /// Pages are separated by [`Token::ThematicBreak`]s. A work that starts with a thematic break
+    /// (like those built by [`Self::new_from_pages`]) starts with the page after it, otherwise the
+    /// contents before the first thematic break are the first page.
+    #[must_use]
+    pub fn pages(&self) -> Pages<'_> {
+        Pages::new(&self.tokens)
+    }
+
+    /// Returns the [`Page`] with the given number, counting from one.
+    #[must_use]
+    pub fn page(&self, number: usize) -> Option<Page<'_>> {
+        self.pages().nth(number.checked_sub(1)?)
+    }
+
+    /// Returns the work with every [`Token::Content`] filled in by `resolver`, in the formatting
+    /// that applies where it is.
+    ///
+    /// See [`crate::resolve`].
+    #[must_use]
+    pub fn resolve(&self, resolver: &(impl Resolver + ?Sized)) -> Self {
+        if !resolve::has_content(&self.tokens) {
+            return self.clone();
+        }
+
+        Self {
+            metadata: self.metadata.clone(),
+            tokens: resolve::resolve(&self.tokens, resolver).into(),
+        }
+    }
+
+    /// Returns the work with Minecraft: Java Edition's rules for formatting applied, such that
+    /// every hidden token only adds to the formatting before it.
+    ///
+    /// A [`TokenList`] records formatting like Minecraft reads it, where:
+    ///
+    /// - A [color][`minecraft::Format::Color`] clears any bold, italic, etc. before it
+    /// - Applying a format that already applies does nothing
+    /// - A [`minecraft::Format::Reset`] clears everything
+    /// - Formatting ends at the end of each page
+    ///
+    /// Exporters, which open an element for each hidden token and close them all on a reset, call
+    /// this before exporting. Afterwards, a color that clears formatting is preceded by a reset
+    /// and the formatting that is left, tokens that change nothing are left out, and the last page
+    /// is reset before each [`Token::ThematicBreak`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crafty_novels::syntax::{
+    ///     minecraft::{Color, Format, TextColor},
+    ///     Token, TokenList,
+    /// };
+    ///
+    /// let red = Token::Format(Format::Color(TextColor::Legacy(Color::Red)));
+    /// let bold = Token::Format(Format::Bold);
+    /// let text = Token::Text("text".into());
+    ///
+    /// // `"§l§l§ctext"` is red, but not bold
+    /// let tokens = TokenList::new_from_boxed(
+    ///     Box::new([]),
+    ///     Box::new([bold.clone(), bold.clone(), red.clone(), text.clone()]),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     tokens.resolve_styles().tokens_as_slice(),
+    ///     [bold, Token::Format(Format::Reset), red, text]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn resolve_styles(&self) -> Self {
+        Self {
+            metadata: self.metadata.clone(),
+            tokens: resolve::resolve_styles(&self.tokens).into(),
+        }
+    }
+
     /// Returns a shared reference to the internal [`Metadata`] slice.
     #[must_use]
     pub fn metadata_as_slice(&self) -> &[Metadata] {
@@ -81,21 +182,41 @@ impl TokenList {
 /// A lexical token.
 ///
 /// Represents an abstract representation of the text, formatting, structure, etc. of a document.
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub enum Token {
     /// Represents a string of plain text in the document.
     Text(Box<str>),
     /// A hidden node to control the text formatting of the document.
     Format(minecraft::Format),
+    /// A hidden node to make the following text clickable.
+    ///
+    /// Like a [`Token::Format`], it applies until the next [`minecraft::Format::Reset`], and a new
+    /// click event replaces the last.
+    Click(minecraft::ClickEvent),
+    /// A hidden node to show something when hovering over the following text.
+    ///
+    /// Like a [`Token::Format`], it applies until the next [`minecraft::Format::Reset`], and a new
+    /// hover event replaces the last.
+    Hover(minecraft::HoverEvent),
+    /// A hidden node to insert text into the player's chat box when they shift-click the following
+    /// text.
+    ///
+    /// Like a [`Token::Format`], it applies until the next [`minecraft::Format::Reset`], and a new
+    /// insertion replaces the last.
+    Insertion(Box<str>),
+    /// Text that is filled in as it is shown, like a translation or a player's score.
+    ///
+    /// Exporters [resolve][`TokenList::resolve`] it into other tokens, in the current formatting.
+    Content(minecraft::Content),
     /// Reprents a literal space (`' '`).
     Space,
     /// Represents a line break, such as `'\n'` or `"\r\n"`.
     LineBreak,
     /// Represents the space between paragraphs.
     ParagraphBreak,
-    /// Represents the space between sections of a document.
+    /// Represents the start of a new page.
     ///
-    /// Typically used to represent page breaks or topic shifts.
+    /// See [`TokenList::pages`].
     ThematicBreak,
 }
 
@@ -130,10 +251,26 @@ impl From<&mut Vec<char>> for Token {
 }
 
 /// Metadata about a literary work.
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub enum Metadata {
     /// A title of a literary work.
     Title(Box<str>),
     /// An author of a literary work.
     Author(Box<str>),
+    /// The dimension of a Minecraft world a work was found in, ex. `"minecraft:the_nether"`.
+    Dimension(Box<str>),
+    /// The ID of the block entity or entity a work was found in, ex. `"minecraft:chest"` or
+    /// `"minecraft:item_frame"`.
+    Container(Box<str>),
+    /// The block position in a Minecraft world a work was found at.
+    Position { x: i32, y: i32, z: i32 },
+    /// The UUID of the player a work was found on, ex. `"069a79f4-44e9-4726-a5be-fca90e38aaf5"`.
+    Owner(Box<str>),
+    /// The slot of the inventory or container a work was found in.
+    ///
+    /// For items nested inside of other items (ex. a book in a shulker box), this is the slot of
+    /// the outermost item.
+    Slot(i32),
+    /// A field that has no meaning of its own, ex. an unknown key in a Stendhal frontmatter.
+    Custom { key: Box<str>, value: Box<str> },
 }
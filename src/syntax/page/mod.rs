@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! The pages of a work.
+//!
+//! See [`Page`].
+
+use super::Token;
+
+#[cfg(test)]
+mod test;
+
+/// A single page of a work, as returned by [`TokenList::pages`][`super::TokenList::pages`].
+///
+/// Pages are stored in a [`TokenList`][`super::TokenList`] as the tokens following each
+/// [`Token::ThematicBreak`]. Contents before the first thematic break are a page of their own, so
+/// that a work without any page breaks is a single page.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Page<'t> {
+    number: usize,
+    tokens: &'t [Token],
+}
+
+impl<'t> Page<'t> {
+    /// The number of this page in the work, counting from one.
+    #[must_use]
+    pub const fn number(&self) -> usize {
+        self.number
+    }
+
+    /// The contents of this page, not including the [`Token::ThematicBreak`] that starts it.
+    #[must_use]
+    pub const fn tokens(&self) -> &'t [Token] {
+        self.tokens
+    }
+
+    /// Whether this page has no contents.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+/// An iterator over the [`Page`]s of a work.
+///
+/// See [`TokenList::pages`][`super::TokenList::pages`].
+#[derive(Clone, Debug)]
+pub struct Pages<'t> {
+    /// The tokens that have not been split into pages yet, or [`None`] once every page has been
+    /// returned.
+    rest: Option<&'t [Token]>,
+    /// The number of the last page returned.
+    number: usize,
+}
+
+impl<'t> Pages<'t> {
+    /// Create a new [`Pages`] iterator over `tokens`.
+    pub(super) const fn new(tokens: &'t [Token]) -> Self {
+        let rest = match tokens.split_first() {
+            Some((Token::ThematicBreak, rest)) => Some(rest),
+            Some(_) => Some(tokens),
+            None => None,
+        };
+
+        Self { rest, number: 0 }
+    }
+}
+
+impl<'t> Iterator for Pages<'t> {
+    type Item = Page<'t>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest?;
+
+        let (tokens, rest) = rest
+            .iter()
+            .position(|token| *token == Token::ThematicBreak)
+            .map_or((rest, None), |end| (&rest[..end], Some(&rest[end + 1..])));
+
+        self.rest = rest;
+        self.number += 1;
+
+        Some(Page {
+            number: self.number,
+            tokens,
+        })
+    }
+}
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for splitting a [`TokenList`] into [`Page`][`super::Page`]s.
+
+use crate::syntax::{Token, TokenList};
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+/// Returns the number and contents of every page of `tokens`.
+fn pages(tokens: &TokenList) -> Vec<(usize, &[Token])> {
+    tokens
+        .pages()
+        .map(|page| (page.number(), page.tokens()))
+        .collect()
+}
+
+#[test]
+fn test_pages() {
+    use Token::{LineBreak, ThematicBreak};
+
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([
+            ThematicBreak,
+            text!("one"),
+            LineBreak,
+            ThematicBreak,
+            ThematicBreak,
+            text!("three"),
+            ThematicBreak,
+        ]),
+    );
+
+    assert_eq!(
+        pages(&input),
+        [
+            (1, [text!("one"), LineBreak].as_slice()),
+            (2, [].as_slice()),
+            (3, [text!("three")].as_slice()),
+            (4, [].as_slice()),
+        ]
+    );
+    assert_eq!(
+        input.page(3).map(|page| page.tokens()),
+        Some([text!("three")].as_slice())
+    );
+    assert!(input.page(2).is_some_and(|page| page.is_empty()));
+    assert_eq!(input.page(0), None);
+    assert_eq!(input.page(5), None);
+}
+
+#[test]
+fn test_implicit_pages() {
+    // Contents before the first page break are a page of their own
+    let input = TokenList::new_from_boxed(
+        Box::new([]),
+        Box::new([text!("one"), Token::ThematicBreak, text!("two")]),
+    );
+    assert_eq!(
+        pages(&input),
+        [
+            (1, [text!("one")].as_slice()),
+            (2, [text!("two")].as_slice())
+        ]
+    );
+
+    let empty = TokenList::new_from_boxed(Box::new([]), Box::new([]));
+    assert_eq!(empty.pages().count(), 0);
+}
+
+#[test]
+fn test_new_from_pages() {
+    let pages = [vec![text!("one")], vec![], vec![text!("three")]];
+    let input = TokenList::new_from_pages(Box::new([]), pages.clone());
+
+    assert_eq!(
+        input.tokens_as_slice(),
+        [
+            Token::ThematicBreak,
+            text!("one"),
+            Token::ThematicBreak,
+            Token::ThematicBreak,
+            text!("three"),
+        ]
+    );
+    assert!(input
+        .pages()
+        .map(|page| page.tokens())
+        .eq(pages.iter().map(Vec::as_slice)));
+}
@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Locations in the input that a work was parsed from.
+//!
+//! See [`SourceSpan`].
+
+use std::fmt::{Display, Write};
+
+/// A span of the input that a token or error came from.
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::syntax::SourceSpan;
+///
+/// let source = "first line\nSome §zRED text";
+/// let span = SourceSpan::new(16, 3, 2, 6);
+///
+/// assert_eq!(&source[span.range()], "§z");
+/// assert_eq!(span.to_string(), "line 2, column 6");
+/// assert_eq!(
+///     span.render(source, "no such format code 'z'"),
+///     concat!(
+///         "error: no such format code 'z'\n",
+///         " --> line 2, column 6\n",
+///         "  |\n",
+///         "2 | Some §zRED text\n",
+///         "  |      ^^\n",
+///     )
+/// );
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct SourceSpan {
+    /// The byte offset of the start of the span.
+    offset: usize,
+    /// The length of the span, in bytes.
+    length: usize,
+    /// The line the span starts on, counting from one.
+    line: usize,
+    /// The column the span starts at, in characters, counting from one.
+    column: usize,
+}
+
+impl SourceSpan {
+    /// Creates a new [`SourceSpan`] from its byte offset and length, and the line and column it
+    /// starts at (each counting from one).
+    #[must_use]
+    pub const fn new(offset: usize, length: usize, line: usize, column: usize) -> Self {
+        Self {
+            offset,
+            length,
+            line,
+            column,
+        }
+    }
+
+    /// The byte offset of the start of the span.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The length of the span, in bytes.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Whether the span is empty, like the span of a token that the parser inserts on its own.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// The bytes of the input that the span covers.
+    #[must_use]
+    pub const fn range(&self) -> std::ops::Range<usize> {
+        self.offset..self.offset + self.length
+    }
+
+    /// The line the span starts on, counting from one.
+    #[must_use]
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column the span starts at, in characters, counting from one.
+    #[must_use]
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Renders `message` as an error at this span of `source`, showing the line the span starts
+    /// on with a caret under each of its characters.
+    ///
+    /// `source` should be the input that the span was taken from.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)] // Writing into a `String` cannot fail
+    pub fn render(&self, source: &str, message: &str) -> String {
+        let start = source
+            .get(..self.offset)
+            .and_then(|before| before.rfind('\n'))
+            .map_or(0, |newline| newline + 1);
+        let line = source.get(start..).unwrap_or_default();
+        let line = line.split('\n').next().unwrap_or_default();
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        // Carets under every character of the span that is on its first line
+        let carets = source
+            .get(self.range())
+            .map_or(0, |span| {
+                span.chars().take_while(|&char| char != '\n').count()
+            })
+            .max(1);
+
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        let mut output = String::new();
+        writeln!(output, "error: {message}").expect("writing into a `String` cannot fail");
+        writeln!(output, "{gutter}--> {self}").expect("writing into a `String` cannot fail");
+        writeln!(output, "{gutter} |").expect("writing into a `String` cannot fail");
+        writeln!(output, "{number} | {line}").expect("writing into a `String` cannot fail");
+        writeln!(
+            output,
+            "{gutter} | {}{}",
+            " ".repeat(self.column.saturating_sub(1)),
+            "^".repeat(carets)
+        )
+        .expect("writing into a `String` cannot fail");
+
+        output
+    }
+}
+
+impl Display for SourceSpan {
+    /// Displays the line and column the span starts at, ex. `"line 2, column 6"`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
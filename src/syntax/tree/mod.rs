@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! A tree-shaped representation of a work.
+//!
+//! See [`Tree`].
+
+use super::{
+    minecraft::{Format, Style},
+    Metadata, Token, TokenList,
+};
+use std::sync::Arc;
+
+#[cfg(test)]
+mod test;
+
+/// The style of text outside of any [`Span`].
+static PLAIN: Style = Style::new();
+
+/// Represents an entire work as a tree of styled [`Span`]s.
+///
+/// A [`TokenList`] records each change to the style as a hidden token (a [`Token::Format`],
+/// [`Token::Click`], [`Token::Hover`], or [`Token::Insertion`]), which leaves exporters that need
+/// well-formed nesting replaying a stack of everything that is open. A [`Tree`] instead records
+/// where each part of the style applies: a [`Span`] holds the run of visible tokens that share
+/// one hidden token, nested inside of the spans for the rest of their style. Where bold text ends
+/// but its color continues, the span of the color continues past the end of the bold span.
+///
+/// Converting a [`TokenList`] into a [`Tree`] keeps every visible token and the [`Style`] it is
+/// shown in, as computed by Minecraft's rules (see [`TokenList::resolve_styles`]). Converting it
+/// back gives tokens that show the same, and that convert into the same [`Tree`] again, but
+/// hidden tokens that changed nothing are left out.
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::syntax::{
+///     minecraft::{Color, Format, TextColor},
+///     tree::{Node, Tree},
+///     Token, TokenList,
+/// };
+///
+/// let red = Format::Color(TextColor::Legacy(Color::Red));
+/// let tokens = TokenList::new_from_boxed(
+///     Box::new([]),
+///     Box::new([
+///         Token::Format(red),
+///         Token::Format(Format::Bold),
+///         Token::Text("a".into()),
+///         Token::Format(Format::Reset),
+///         Token::Format(red),
+///         Token::Text("b".into()),
+///         Token::Format(Format::Reset),
+///         Token::Text("c".into()),
+///     ]),
+/// );
+///
+/// // The bold text ends, but the red text continues
+/// let tree = Tree::from(&tokens);
+/// let [Node::Span(color), Node::Token(_)] = tree.nodes() else {
+///     panic!("expected a span, then text");
+/// };
+/// let [Node::Span(bold), Node::Token(_)] = color.children() else {
+///     panic!("expected the bold span inside of the color span, then text");
+/// };
+/// assert!(bold.style().is_bold());
+/// assert_eq!(bold.style().color(), Some(TextColor::Legacy(Color::Red)));
+///
+/// assert_eq!(Tree::from(&TokenList::from(&tree)), tree);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree {
+    /// Meta information about the work.
+    metadata: Arc<[Metadata]>,
+    /// The top level of the content of the work.
+    nodes: Box<[Node]>,
+}
+
+impl Tree {
+    /// Returns a copy of the internal [`Arc`] holding a [`Metadata`] slice.
+    #[must_use]
+    pub fn metadata(&self) -> Arc<[Metadata]> {
+        self.metadata.clone()
+    }
+
+    /// Returns the top level of the content of the work.
+    #[must_use]
+    pub const fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns an iterator over every visible token of the work, in order, alongside the
+    /// [`Style`] it is shown in.
+    #[must_use]
+    pub fn leaves(&self) -> Leaves<'_> {
+        Leaves {
+            stack: vec![(&PLAIN, self.nodes.iter())],
+        }
+    }
+}
+
+impl From<&TokenList> for Tree {
+    /// Sort the visible tokens of a [`TokenList`] into [`Span`]s by their style.
+    fn from(value: &TokenList) -> Self {
+        let leaves = styled_leaves(value.tokens_as_slice());
+
+        Self {
+            metadata: value.metadata(),
+            nodes: build(&leaves, &PLAIN).into(),
+        }
+    }
+}
+
+impl From<&Tree> for TokenList {
+    /// Flatten a [`Tree`] into tokens that show the same.
+    ///
+    /// Every change in style only adds to the style before it where possible, and otherwise starts
+    /// with a [`Format::Reset`], like after [`TokenList::resolve_styles`].
+    fn from(value: &Tree) -> Self {
+        let mut tokens = vec![];
+        let mut active = Style::new();
+
+        for (style, token) in value.leaves() {
+            if *token == Token::ThematicBreak {
+                // Formatting ends with the page on its own
+                active = Style::new();
+            } else if *style != active {
+                transition(&mut tokens, &active, style);
+                active = style.clone();
+            }
+
+            tokens.push(token.clone());
+        }
+
+        Self::new(value.metadata(), tokens.into())
+    }
+}
+
+/// A single piece of a [`Tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// A visible token, shown in the style of the [`Span`] it is inside of.
+    ///
+    /// Never a hidden token (see [`Span::token`]). A [`Token::ThematicBreak`] ends all
+    /// formatting, so it is only found at the top level of a [`Tree`].
+    Token(Token),
+    /// A hidden token and the run of visible tokens it applies to.
+    Span(Span),
+}
+
+/// A hidden token and the run of visible tokens it applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The hidden token that the span adds to the style around it.
+    token: Token,
+    /// The style in effect inside of the span, including `token`.
+    style: Style,
+    /// The contents of the span.
+    children: Box<[Node]>,
+}
+
+impl Span {
+    /// The hidden token that the span adds to the style around it: a [`Token::Format`] (but never
+    /// [`Format::Reset`]), [`Token::Click`], [`Token::Hover`], or [`Token::Insertion`].
+    #[must_use]
+    pub const fn token(&self) -> &Token {
+        &self.token
+    }
+
+    /// The style in effect inside of the span, computed from every span around it.
+    #[must_use]
+    pub const fn style(&self) -> &Style {
+        &self.style
+    }
+
+    /// The contents of the span.
+    #[must_use]
+    pub const fn children(&self) -> &[Node] {
+        &self.children
+    }
+}
+
+/// An iterator over the visible tokens of a [`Tree`] and their [`Style`]s.
+///
+/// See [`Tree::leaves`].
+#[derive(Clone, Debug)]
+pub struct Leaves<'t> {
+    /// The style and remaining contents of each open span, innermost last.
+    stack: Vec<(&'t Style, std::slice::Iter<'t, Node>)>,
+}
+
+impl<'t> Iterator for Leaves<'t> {
+    type Item = (&'t Style, &'t Token);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (style, nodes) = self.stack.last_mut()?;
+            let style = *style;
+
+            match nodes.next() {
+                Some(Node::Token(token)) => return Some((style, token)),
+                Some(Node::Span(span)) => self.stack.push((&span.style, span.children.iter())),
+                None => drop(self.stack.pop()),
+            }
+        }
+    }
+}
+
+/// Returns every visible token of `tokens` alongside the [`Style`] it is shown in.
+///
+/// Like [`TokenList::resolve_styles`], the style is computed with [`Style::apply`], and ends at
+/// each [`Token::ThematicBreak`].
+fn styled_leaves(tokens: &[Token]) -> Vec<(Style, Token)> {
+    let mut leaves = vec![];
+    let mut style = Style::new();
+
+    for token in tokens {
+        if *token == Token::ThematicBreak {
+            style = Style::new();
+        }
+
+        if !style.apply(token) {
+            leaves.push((style.clone(), token.clone()));
+        }
+    }
+
+    leaves
+}
+
+/// Sort `leaves`, which are all inside of a span of `outer` style, into [`Node`]s.
+///
+/// Each span is opened for whichever hidden token that `outer` does not have applies to the
+/// longest run of leaves, preferring those that come first in [`Style::tokens`] (ex. colors).
+fn build(leaves: &[(Style, Token)], outer: &Style) -> Vec<Node> {
+    let mut nodes = vec![];
+    let mut rest = leaves;
+
+    while let Some((style, token)) = rest.first() {
+        let mut longest: Option<(Token, usize)> = None;
+        for hidden in style.tokens().filter(|hidden| !has(outer, hidden)) {
+            let run = rest
+                .iter()
+                .take_while(|(style, _)| has(style, &hidden))
+                .count();
+
+            if longest.as_ref().is_none_or(|(_, longest)| run > *longest) {
+                longest = Some((hidden, run));
+            }
+        }
+
+        let Some((hidden, run)) = longest else {
+            nodes.push(Node::Token(token.clone()));
+            rest = &rest[1..];
+            continue;
+        };
+
+        let style = with(outer, &hidden);
+        nodes.push(Node::Span(Span {
+            token: hidden,
+            children: build(&rest[..run], &style).into(),
+            style,
+        }));
+        rest = &rest[run..];
+    }
+
+    nodes
+}
+
+/// Whether `hidden` is part of `style`, as in [`Style::tokens`].
+fn has(style: &Style, hidden: &Token) -> bool {
+    match hidden {
+        Token::Format(format) => style.formats().any(|f| f == *format),
+        Token::Click(click) => style.click() == Some(click),
+        Token::Hover(hover) => style.hover() == Some(hover),
+        Token::Insertion(insertion) => style.insertion() == Some(insertion),
+        _ => false,
+    }
+}
+
+/// Returns `outer` with `hidden` added to it, which is not a part of it yet.
+///
+/// Applying a color would clear the formatting of `outer`, so a color is applied first instead.
+fn with(outer: &Style, hidden: &Token) -> Style {
+    if matches!(hidden, Token::Format(Format::Color(_))) {
+        let mut style = Style::new();
+        style.apply(hidden);
+        for token in outer.tokens() {
+            style.apply(&token);
+        }
+        style
+    } else {
+        let mut style = outer.clone();
+        style.apply(hidden);
+        style
+    }
+}
+
+/// Write the tokens that change the style from `active` to `style` into `output`.
+fn transition(output: &mut Vec<Token>, active: &Style, style: &Style) {
+    let adds_color = style.color().is_some() && style.color() != active.color();
+    let only_adds = active.tokens().all(|token| has(style, &token))
+        && !(adds_color && active.formats().any(clears));
+
+    if only_adds {
+        output.extend(style.tokens().filter(|token| !has(active, token)));
+    } else {
+        output.push(Token::Format(Format::Reset));
+        output.extend(style.tokens());
+    }
+}
+
+/// Whether `format` is cleared by a color, as bold, italic, etc. are.
+const fn clears(format: Format) -> bool {
+    !matches!(
+        format,
+        Format::Color(_) | Format::Font(_) | Format::ShadowColor(_)
+    )
+}
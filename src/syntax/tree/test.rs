@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for converting between a [`TokenList`] and a [`Tree`].
+
+use super::{Node, Tree};
+use crate::syntax::{
+    minecraft::{ClickEvent, Color, Format, Style, TextColor},
+    Metadata, Token, TokenList,
+};
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+/// Insert a [`Token::Format`] with the given [`Format`] variant.
+macro_rules! format {
+    ($format:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::$format)
+    };
+}
+
+/// Insert a [`Token::Format`] with the given legacy [`Color`].
+macro_rules! color {
+    ($color:ident) => {
+        crate::syntax::Token::Format(crate::syntax::minecraft::Format::Color(
+            crate::syntax::minecraft::TextColor::Legacy(crate::syntax::minecraft::Color::$color),
+        ))
+    };
+}
+
+/// Builds a [`TokenList`] with a title and the given tokens.
+fn tokens(tokens: impl Into<Box<[Token]>>) -> TokenList {
+    TokenList::new_from_boxed(Box::new([Metadata::Title("Title".into())]), tokens.into())
+}
+
+/// Returns the text of every visible token of `tree`, alongside its style.
+fn leaves(tree: &Tree) -> Vec<(Style, Token)> {
+    tree.leaves()
+        .map(|(style, token)| (style.clone(), token.clone()))
+        .collect()
+}
+
+#[test]
+fn test_round_trip() {
+    use Token::{LineBreak, ParagraphBreak, Space, ThematicBreak};
+
+    let inputs = [
+        tokens([]),
+        tokens([text!("plain"), Space, text!("text")]),
+        // Stray and repeated resets
+        tokens([format!(Reset), format!(Reset), text!("a"), format!(Reset)]),
+        // Redundant and replaced formats
+        tokens([
+            format!(Bold),
+            format!(Bold),
+            color!(Red),
+            color!(Gold),
+            text!("a"),
+            LineBreak,
+            format!(Italic),
+        ]),
+        tokens([
+            ThematicBreak,
+            Token::Click(ClickEvent::ChangePage(2)),
+            Token::Insertion("insert".into()),
+            text!("a"),
+            format!(Reset),
+            ParagraphBreak,
+            format!(Underline),
+            text!("b"),
+            ThematicBreak,
+            text!("c"),
+        ]),
+        // A color that clears formatting, and formatting that continues past a page break
+        tokens([
+            format!(Italic),
+            text!("a"),
+            color!(Red),
+            format!(Bold),
+            text!("b"),
+            format!(Reset),
+            color!(Red),
+            text!("c"),
+            format!(Bold),
+            ThematicBreak,
+            text!("d"),
+        ]),
+    ];
+
+    for input in inputs {
+        let tree = Tree::from(&input);
+        let output = TokenList::from(&tree);
+
+        assert_eq!(Tree::from(&output), tree);
+        assert_eq!(leaves(&Tree::from(&output)), leaves(&tree));
+        assert_eq!(output.metadata(), input.metadata());
+    }
+
+    // Tokens that change nothing are left out
+    let tree = Tree::from(&tokens([
+        format!(Bold),
+        format!(Bold),
+        color!(Red),
+        color!(Gold),
+        text!("a"),
+        LineBreak,
+        format!(Italic),
+        format!(Reset),
+    ]));
+    assert_eq!(
+        TokenList::from(&tree),
+        tokens([color!(Gold), text!("a"), LineBreak])
+    );
+}
+
+#[test]
+fn test_structure() {
+    let input = tokens([
+        text!("a"),
+        color!(Red),
+        format!(Bold),
+        text!("b"),
+        // The bold text ends, but the red text continues
+        format!(Reset),
+        color!(Red),
+        text!("c"),
+        format!(Reset),
+        text!("d"),
+    ]);
+    let tree = Tree::from(&input);
+
+    let [Node::Token(a), Node::Span(red), Node::Token(d)] = tree.nodes() else {
+        panic!("unexpected top level: {:?}", tree.nodes());
+    };
+    assert_eq!((a, d), (&text!("a"), &text!("d")));
+    assert_eq!(red.token(), &color!(Red));
+
+    let [Node::Span(bold), Node::Token(c)] = red.children() else {
+        panic!("unexpected children: {:?}", red.children());
+    };
+    assert_eq!(c, &text!("c"));
+    assert_eq!(bold.token(), &format!(Bold));
+    assert_eq!(bold.children(), [Node::Token(text!("b"))]);
+
+    // Like in Minecraft, the red text is only bold until the reset
+    assert_eq!(
+        TokenList::from(&tree),
+        tokens([
+            text!("a"),
+            color!(Red),
+            format!(Bold),
+            text!("b"),
+            format!(Reset),
+            color!(Red),
+            text!("c"),
+            format!(Reset),
+            text!("d"),
+        ])
+    );
+
+    // The span that applies the longest is opened first, even if it came later
+    let tree = Tree::from(&tokens([
+        format!(Bold),
+        text!("a"),
+        color!(Red),
+        format!(Bold),
+        text!("b"),
+    ]));
+    let [Node::Span(bold)] = tree.nodes() else {
+        panic!("unexpected top level: {:?}", tree.nodes());
+    };
+    let [Node::Token(a), Node::Span(red)] = bold.children() else {
+        panic!("unexpected children: {:?}", bold.children());
+    };
+    assert_eq!(a, &text!("a"));
+    assert_eq!(red.token(), &color!(Red));
+    assert_eq!(red.children(), [Node::Token(text!("b"))]);
+}
+
+#[test]
+fn test_styles() {
+    let red = TextColor::Legacy(Color::Red);
+    let gold = TextColor::Legacy(Color::Gold);
+
+    let input = tokens([
+        text!("a"),
+        color!(Red),
+        format!(Bold),
+        text!("b"),
+        color!(Gold),
+        Token::Click(ClickEvent::ChangePage(2)),
+        text!("c"),
+        format!(Reset),
+        text!("d"),
+    ]);
+    let tree = Tree::from(&input);
+
+    let mut bold_red = Style::new();
+    for token in [color!(Red), format!(Bold)] {
+        assert!(bold_red.apply(&token));
+    }
+    let mut gold_link = bold_red.clone();
+    for token in [color!(Gold), Token::Click(ClickEvent::ChangePage(2))] {
+        assert!(gold_link.apply(&token));
+    }
+
+    assert_eq!(bold_red.color(), Some(red));
+    assert_eq!(
+        bold_red.formats().collect::<Vec<_>>(),
+        [Format::Color(red), Format::Bold]
+    );
+
+    // Like in Minecraft, the gold span is not bold, despite being inside of the bold span
+    assert_eq!(gold_link.color(), Some(gold));
+    assert!(!gold_link.is_bold());
+    assert_eq!(gold_link.click(), Some(&ClickEvent::ChangePage(2)));
+    assert_eq!(
+        gold_link.formats().collect::<Vec<_>>(),
+        [Format::Color(gold)]
+    );
+
+    assert_eq!(
+        leaves(&tree),
+        [
+            (Style::new(), text!("a")),
+            (bold_red, text!("b")),
+            (gold_link, text!("c")),
+            (Style::new(), text!("d")),
+        ]
+    );
+}
+
+#[test]
+fn test_pages() {
+    let input = tokens([
+        color!(Red),
+        format!(Bold),
+        text!("a"),
+        // Formatting ends with the page
+        Token::ThematicBreak,
+        text!("b"),
+        format!(Italic),
+        text!("c"),
+    ]);
+    let tree = Tree::from(&input);
+
+    let [Node::Span(red), Node::Token(Token::ThematicBreak), Node::Token(b), Node::Span(italic)] =
+        tree.nodes()
+    else {
+        panic!("unexpected nodes: {:?}", tree.nodes());
+    };
+    assert_eq!(red.token(), &color!(Red));
+    assert_eq!(b, &text!("b"));
+    assert_eq!(italic.children(), [Node::Token(text!("c"))]);
+
+    assert_eq!(TokenList::from(&tree), input);
+}
+
+#[test]
+fn test_leaf_styles() {
+    let input = tokens([
+        format!(Bold),
+        format!(Bold),
+        text!("a"),
+        color!(Red),
+        text!("b"),
+        format!(Italic),
+        Token::Click(ClickEvent::ChangePage(2)),
+        color!(Gold),
+        text!("c"),
+        Token::ThematicBreak,
+        text!("d"),
+        format!(Underline),
+        format!(Reset),
+        text!("e"),
+    ]);
+
+    // Every visible token is in the style that applying every token before it gives, like in
+    // `TokenList::resolve_styles`
+    let mut style = Style::new();
+    let mut expected = vec![];
+    for token in input.tokens_as_slice() {
+        if *token == Token::ThematicBreak {
+            style = Style::new();
+        }
+        if !style.apply(token) {
+            expected.push((style.clone(), token.clone()));
+        }
+    }
+
+    assert_eq!(leaves(&Tree::from(&input)), expected);
+    assert_eq!(
+        leaves(&Tree::from(&input.resolve_styles())),
+        expected,
+        "resolving styles first should not change them"
+    );
+}
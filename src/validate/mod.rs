@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Checking a work against the limits of books in Minecraft: Java Edition.
+//!
+//! See [`validate`].
+
+use crate::{
+    layout::{is_renderable, MAX_PAGES},
+    resolve::Untranslated,
+    syntax::{Metadata, Token, TokenList},
+};
+use std::fmt::Display;
+
+#[cfg(test)]
+mod test;
+
+/// The most characters the title of a book can have.
+pub const MAX_TITLE_LENGTH: usize = 32;
+
+/// The most pages a book and quill can have.
+pub const MAX_WRITABLE_PAGES: usize = 50;
+
+/// The most characters a page of a book can have.
+pub const MAX_PAGE_LENGTH: usize = 1024;
+
+/// The most characters that can be typed onto a page in the book editor.
+pub const MAX_TYPED_PAGE_LENGTH: usize = 256;
+
+/// The most bytes of NBT that Minecraft reads for a single item.
+pub const MAX_NBT_SIZE: usize = 2_097_152;
+
+/// Checks `tokens` against the limits of books in Minecraft: Java Edition, returning a
+/// [`Diagnostic`] for every limit it breaks.
+///
+/// Like in Minecraft, lengths and offsets are counted in UTF-16 code units, which are the same as
+/// characters for everything but characters outside of the Basic Multilingual Plane (like most
+/// emoji), which count twice. A page is measured as the text a book and quill stores, where each
+/// format code is two characters (ex. `"§l"`), each line break is one, and the last line break of
+/// the page is left out. Content is filled in with [`Untranslated`] first, see
+/// [`crate::resolve`].
+///
+/// # Examples
+///
+/// ```rust
+/// use crafty_novels::{
+///     syntax::{Metadata, Token, TokenList},
+///     validate::{validate, Violation},
+/// };
+///
+/// let tokens = TokenList::new_from_boxed(
+///     Box::new([Metadata::Title("A title that is much too long for a book".into())]),
+///     Box::new([Token::ThematicBreak, Token::Text("Bell: \u{7}".into())]),
+/// );
+///
+/// let diagnostics = validate(&tokens);
+/// assert_eq!(diagnostics[0].violation(), &Violation::TitleTooLong { length: 40 });
+/// assert_eq!(diagnostics[1].violation(), &Violation::Unrenderable('\u{7}'));
+/// assert_eq!((diagnostics[1].page(), diagnostics[1].offset()), (Some(1), Some(6)));
+/// assert_eq!(diagnostics[1].to_string(), "page 1, offset 6: U+0007 cannot be rendered");
+/// ```
+#[must_use]
+pub fn validate(tokens: &TokenList) -> Vec<Diagnostic> {
+    let tokens = tokens.resolve(&Untranslated);
+    let mut diagnostics = vec![];
+
+    let mut size = 0;
+    for metadata in tokens.metadata_as_slice() {
+        match metadata {
+            Metadata::Title(title) => {
+                let length = utf16_length(title);
+                if length > MAX_TITLE_LENGTH {
+                    diagnostics.push(Diagnostic {
+                        violation: Violation::TitleTooLong { length },
+                        page: None,
+                        offset: Some(MAX_TITLE_LENGTH),
+                    });
+                }
+
+                size += string_size("title", title);
+            }
+            Metadata::Author(author) => size += string_size("author", author),
+            _ => (),
+        }
+    }
+
+    let mut count = 0;
+    for page in tokens.pages() {
+        count += 1;
+
+        let text = page_text(page.number(), page.tokens(), &mut diagnostics);
+        let length = utf16_length(&text);
+
+        for (limit, violation) in [
+            (MAX_PAGE_LENGTH, Violation::PageTooLong { length }),
+            (
+                MAX_TYPED_PAGE_LENGTH,
+                Violation::PageTooLongToType { length },
+            ),
+        ] {
+            if length > limit {
+                diagnostics.push(Diagnostic {
+                    violation,
+                    page: Some(page.number()),
+                    offset: Some(limit),
+                });
+            }
+        }
+
+        size += json_string_size(&text);
+    }
+
+    for (limit, violation) in [
+        (MAX_PAGES, Violation::TooManyPages { count }),
+        (
+            MAX_WRITABLE_PAGES,
+            Violation::TooManyWritablePages { count },
+        ),
+    ] {
+        if count > limit {
+            diagnostics.push(Diagnostic {
+                violation,
+                page: Some(limit + 1),
+                offset: None,
+            });
+        }
+    }
+
+    // The compound around the book, and the tag type, name, and length of the list of pages
+    size += 1 + 2 + 1 + (1 + 2 + "pages".len() + 1 + 4);
+    if size > MAX_NBT_SIZE {
+        diagnostics.push(Diagnostic {
+            violation: Violation::TooLarge { bytes: size },
+            page: None,
+            offset: None,
+        });
+    }
+
+    diagnostics
+}
+
+/// A limit of books that a work breaks, and where.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Diagnostic {
+    /// The limit that was broken.
+    violation: Violation,
+    /// The page the limit was broken on, counting from one.
+    page: Option<usize>,
+    /// The offset into the page or title that the limit was broken at, in UTF-16 code units.
+    offset: Option<usize>,
+}
+
+impl Diagnostic {
+    /// The limit that was broken.
+    #[must_use]
+    pub const fn violation(&self) -> &Violation {
+        &self.violation
+    }
+
+    /// The page the limit was broken on, counting from one.
+    ///
+    /// For [`Violation::TooManyPages`] and [`Violation::TooManyWritablePages`], this is the first
+    /// page past the limit. [`None`] for limits that do not belong to a page.
+    #[must_use]
+    pub const fn page(&self) -> Option<usize> {
+        self.page
+    }
+
+    /// The offset into the page (or the title, for [`Violation::TitleTooLong`]) that the limit
+    /// was broken at, in UTF-16 code units.
+    ///
+    /// For limits on length, this is the first character past the limit, where Minecraft
+    /// truncates the text. [`None`] for limits that do not belong to a single character.
+    #[must_use]
+    pub const fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+}
+
+impl Display for Diagnostic {
+    /// Displays where the limit was broken and what it was, ex.
+    /// `"page 2, offset 256: the page is 300 characters long, but only 256 can be typed"`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.page, self.offset) {
+            (Some(page), Some(offset)) => write!(f, "page {page}, offset {offset}: ")?,
+            (Some(page), None) => write!(f, "page {page}: ")?,
+            (None, Some(offset)) => write!(f, "offset {offset}: ")?,
+            (None, None) => (),
+        }
+
+        write!(f, "{}", self.violation)
+    }
+}
+
+/// The limits of books in Minecraft: Java Edition.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Violation {
+    /// The title is longer than [`MAX_TITLE_LENGTH`], so the book cannot be signed.
+    TitleTooLong { length: usize },
+    /// The work has more than [`MAX_PAGES`] pages, so the rest are cut off.
+    TooManyPages { count: usize },
+    /// The work has more than [`MAX_WRITABLE_PAGES`] pages, so the rest cannot be written in a
+    /// book and quill.
+    TooManyWritablePages { count: usize },
+    /// The page is longer than [`MAX_PAGE_LENGTH`], so the rest is cut off.
+    PageTooLong { length: usize },
+    /// The page is longer than [`MAX_TYPED_PAGE_LENGTH`], so the rest cannot be typed in the book
+    /// editor.
+    PageTooLongToType { length: usize },
+    /// The NBT of the book is estimated to be larger than [`MAX_NBT_SIZE`], so the book will be
+    /// rejected.
+    ///
+    /// Estimated as a written book that holds each page as a JSON text component of its text.
+    TooLarge { bytes: usize },
+    /// The default font has no glyph for the character, see [`is_renderable`].
+    Unrenderable(char),
+}
+
+impl Display for Violation {
+    /// Describes the limit that was broken, ex. `"the title is 40 characters long, but titles
+    /// can be at most 32"`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::TitleTooLong { length } => write!(
+                f,
+                "the title is {length} characters long, but titles can be at most \
+                 {MAX_TITLE_LENGTH}"
+            ),
+            Self::TooManyPages { count } => write!(
+                f,
+                "the book has {count} pages, but books can have at most {MAX_PAGES}"
+            ),
+            Self::TooManyWritablePages { count } => write!(
+                f,
+                "the book has {count} pages, but books and quills can have at most \
+                 {MAX_WRITABLE_PAGES}"
+            ),
+            Self::PageTooLong { length } => write!(
+                f,
+                "the page is {length} characters long, but pages can be at most \
+                 {MAX_PAGE_LENGTH}"
+            ),
+            Self::PageTooLongToType { length } => write!(
+                f,
+                "the page is {length} characters long, but only {MAX_TYPED_PAGE_LENGTH} can be \
+                 typed"
+            ),
+            Self::TooLarge { bytes } => write!(
+                f,
+                "the book is about {bytes} bytes, but items can be at most {MAX_NBT_SIZE}"
+            ),
+            Self::Unrenderable(char) => {
+                write!(f, "U+{:04X} cannot be rendered", u32::from(char))
+            }
+        }
+    }
+}
+
+/// Returns the text of a page as a book and quill stores it, pushing a [`Diagnostic`] for every
+/// character of it that cannot be rendered.
+fn page_text(number: usize, tokens: &[Token], diagnostics: &mut Vec<Diagnostic>) -> String {
+    let mut text = String::new();
+    let mut length = 0;
+
+    for token in tokens {
+        match token {
+            Token::Text(string) => {
+                for char in string.chars() {
+                    if !is_renderable(char) {
+                        diagnostics.push(Diagnostic {
+                            violation: Violation::Unrenderable(char),
+                            page: Some(number),
+                            offset: Some(length),
+                        });
+                    }
+
+                    text.push(char);
+                    length += char.len_utf16();
+                }
+            }
+            Token::Space => {
+                text.push(' ');
+                length += 1;
+            }
+            Token::LineBreak | Token::ParagraphBreak => {
+                text.push('\n');
+                length += 1;
+            }
+            Token::Format(format) => {
+                // Fonts, shadow colors, and RGB colors cannot be written in a book and quill
+                if let Ok(code) = char::try_from(*format) {
+                    text.extend(['§', code]);
+                    length += 2;
+                }
+            }
+            Token::Click(_)
+            | Token::Hover(_)
+            | Token::Insertion(_)
+            | Token::Content(_)
+            | Token::ThematicBreak => (),
+        }
+    }
+
+    if text.ends_with('\n') {
+        text.pop();
+    }
+
+    text
+}
+
+/// Returns the length of `text` in UTF-16 code units, like Minecraft measures strings.
+fn utf16_length(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+/// Returns the length of `text` in the modified UTF-8 that NBT stores strings in.
+fn modified_utf8_length(text: &str) -> usize {
+    text.chars()
+        .map(|char| match char {
+            '\0' => 2,
+            // Stored as a surrogate pair, each encoded on its own
+            char if char.len_utf16() == 2 => 6,
+            char => char.len_utf8(),
+        })
+        .sum()
+}
+
+/// Returns the size of a string tag named `name` holding `value`.
+fn string_size(name: &str, value: &str) -> usize {
+    1 + 2 + name.len() + 2 + modified_utf8_length(value)
+}
+
+/// Returns the size of a string in a list of pages, holding `text` as a JSON text component.
+fn json_string_size(text: &str) -> usize {
+    let escapes = text
+        .chars()
+        .filter(|char| matches!(char, '"' | '\\' | '\n'))
+        .count();
+
+    2 + r#"{"text":""}"#.len() + modified_utf8_length(text) + escapes
+}
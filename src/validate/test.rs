@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of crafty_novels.
+//
+// crafty_novels is free software: you can redistribute it and/or modify it under the terms of the
+// GNU Affero General Public License as published by the Free Software Foundation, either version
+// 3 of the License, or (at your option) any later version.
+//
+// crafty_novels is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See
+// the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with
+// crafty_novels. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for checking a work against the limits of books.
+
+use super::{
+    validate, Diagnostic, Violation, MAX_NBT_SIZE, MAX_PAGE_LENGTH, MAX_TYPED_PAGE_LENGTH,
+    MAX_WRITABLE_PAGES,
+};
+use crate::{
+    layout::MAX_PAGES,
+    syntax::{Metadata, Token, TokenList},
+};
+
+/// Insert a [`Token::Text`] with the given string.
+macro_rules! text {
+    ($text:expr) => {
+        crate::syntax::Token::Text($text.into())
+    };
+}
+
+/// Returns the violations of `diagnostics`, alongside their page and offset.
+fn violations(diagnostics: &[Diagnostic]) -> Vec<(Violation, Option<usize>, Option<usize>)> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                *diagnostic.violation(),
+                diagnostic.page(),
+                diagnostic.offset(),
+            )
+        })
+        .collect()
+}
+
+/// Builds a [`TokenList`] with the given title and pages.
+fn book(title: &str, pages: impl IntoIterator<Item = Vec<Token>>) -> TokenList {
+    TokenList::new_from_pages(Box::new([Metadata::Title(title.into())]), pages)
+}
+
+#[test]
+fn test_valid() {
+    use Token::{LineBreak, Space};
+
+    let tokens = book(
+        &"t".repeat(32),
+        [
+            vec![text!("Hello,"), Space, text!("world!"), LineBreak],
+            vec![text!("\u{4E2D}\u{6587}")],
+        ],
+    );
+
+    assert_eq!(validate(&tokens), []);
+}
+
+#[test]
+fn test_title_and_pages() {
+    let tokens = book(&"t".repeat(33), (0..=MAX_WRITABLE_PAGES).map(|_| vec![]));
+    assert_eq!(
+        violations(&validate(&tokens)),
+        [
+            (Violation::TitleTooLong { length: 33 }, None, Some(32)),
+            (
+                Violation::TooManyWritablePages { count: 51 },
+                Some(51),
+                None
+            ),
+        ]
+    );
+
+    let tokens = book("", (0..=MAX_PAGES).map(|_| vec![]));
+    assert_eq!(
+        violations(&validate(&tokens)),
+        [
+            (Violation::TooManyPages { count: 101 }, Some(101), None),
+            (
+                Violation::TooManyWritablePages { count: 101 },
+                Some(51),
+                None
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_page_length() {
+    use Token::LineBreak;
+
+    // Format codes are two characters, and the last line break is left out
+    let mut page = vec![Token::Format(crate::syntax::minecraft::Format::Bold)];
+    page.extend([
+        text!("a".repeat(MAX_TYPED_PAGE_LENGTH - 3)),
+        LineBreak,
+        LineBreak,
+    ]);
+    assert_eq!(validate(&book("", [page.clone()])), []);
+
+    // Followed by something, the last line break counts
+    page.push(text!("a"));
+    assert_eq!(
+        violations(&validate(&book("", [page]))),
+        [(
+            Violation::PageTooLongToType { length: 258 },
+            Some(1),
+            Some(256)
+        )]
+    );
+
+    let page = vec![text!("a".repeat(MAX_PAGE_LENGTH + 1))];
+    assert_eq!(
+        violations(&validate(&book("", [vec![], page]))),
+        [
+            (Violation::PageTooLong { length: 1025 }, Some(2), Some(1024)),
+            (
+                Violation::PageTooLongToType { length: 1025 },
+                Some(2),
+                Some(256)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_unrenderable() {
+    use Token::Space;
+
+    // Characters outside of the Basic Multilingual Plane count twice
+    let page = vec![text!("\u{1F600}\u{E000}"), Space, text!("\u{0}")];
+    let diagnostics = validate(&book("", [page]));
+
+    assert_eq!(
+        violations(&diagnostics),
+        [
+            (Violation::Unrenderable('\u{E000}'), Some(1), Some(2)),
+            (Violation::Unrenderable('\u{0}'), Some(1), Some(4)),
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "page 1, offset 2: U+E000 cannot be rendered"
+    );
+}
+
+#[test]
+fn test_size() {
+    // Multibyte characters are larger in NBT
+    let page = vec![text!("\u{4E2D}".repeat(MAX_NBT_SIZE / 3))];
+    let diagnostics = validate(&book("", [page]));
+
+    let Some(diagnostic) = diagnostics
+        .iter()
+        .find(|diagnostic| matches!(diagnostic.violation(), Violation::TooLarge { .. }))
+    else {
+        panic!("expected the book to be too large, found {diagnostics:?}");
+    };
+    assert_eq!((diagnostic.page(), diagnostic.offset()), (None, None));
+    assert!(matches!(
+        diagnostic.violation(),
+        Violation::TooLarge { bytes } if *bytes > MAX_NBT_SIZE
+    ));
+    assert!(diagnostic.to_string().starts_with("the book is about"));
+}